@@ -0,0 +1,33 @@
+defmodule Yex.Binary do
+  @moduledoc """
+  Wraps a binary that should be stored as a byte buffer.
+
+  Plain binaries that are valid UTF-8 are stored as strings.
+  Wrap a binary to store it as a buffer regardless of its content.
+  Lists of bytes are stored as lists of numbers unless wrapped, here or with `Yex.Typed`.
+  Buffers read back from a shared type are always returned wrapped in this struct.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "text", "hello")
+      iex> Yex.Map.set(map, "blob", Yex.Binary.new("hello"))
+      iex> Yex.Map.fetch!(map, "text")
+      "hello"
+      iex> Yex.Map.fetch!(map, "blob")
+      %Yex.Binary{data: "hello"}
+  """
+  defstruct [:data]
+
+  @type t :: %__MODULE__{
+          data: binary()
+        }
+
+  @doc """
+  Wraps `data`, which may be any iodata.
+  """
+  @spec new(iodata()) :: t()
+  def new(data) do
+    %__MODULE__{data: IO.iodata_to_binary(data)}
+  end
+end
@@ -10,6 +10,19 @@ defmodule Yex.Doc do
     Make sure the worker process can handle the GenServer call messages properly.
 
     It is recommended to start a GenServer process such as `Yex.DocServer` when executing operations from other processes.
+
+  ### Transaction contention
+    Every read or write opens a transaction on the document unless it runs inside `transaction/3`.
+    If another transaction still holds the document at that moment, the operation returns
+    `{:error, :transaction_busy}` instead of its usual result. This is transient: retrying once the
+    other transaction has been committed succeeds. Failures that retrying cannot fix keep being
+    reported as `:transaction_acq_error`.
+
+    A transaction started with `transaction/3` is committed when its function returns. If the
+    process running it dies before that, the transaction is committed once it is garbage
+    collected, and operations keep returning `{:error, :transaction_busy}` until then; a
+    transaction is never committed on behalf of a process that is still running it.
+    `force_commit_all/1` commits leftover transactions right away.
   """
 
   defmodule Options do
@@ -118,6 +131,26 @@ defmodule Yex.Doc do
     Yex.Nif.doc_with_options(option) |> Map.put(:worker_pid, worker_pid)
   end
 
+  @doc """
+  Create two documents with the given client ids, for tests that edit them
+  independently and then merge them deterministically.
+
+  Client ids must be unique among all peers collaborating on a document, since
+  updates from two peers sharing an id collide and corrupt the document state.
+  Raises `ArgumentError` if both ids are equal.
+
+  ## Examples
+      iex> {doc_a, doc_b} = Yex.Doc.new_pair(1, 2)
+      iex> {Yex.Doc.client_id(doc_a), Yex.Doc.client_id(doc_b)}
+      {1, 2}
+  """
+  @spec new_pair(non_neg_integer(), non_neg_integer(), pid()) :: {Yex.Doc.t(), Yex.Doc.t()}
+  def new_pair(client_id_a, client_id_b, worker_pid \\ self())
+      when is_integer(client_id_a) and is_integer(client_id_b) do
+    {doc_a, doc_b} = Yex.Nif.doc_new_pair(client_id_a, client_id_b)
+    {Map.put(doc_a, :worker_pid, worker_pid), Map.put(doc_b, :worker_pid, worker_pid)}
+  end
+
   def client_id(%__MODULE__{} = doc) do
     run_in_worker_process(doc, do: Yex.Nif.doc_client_id(doc))
   end
@@ -142,6 +175,79 @@ defmodule Yex.Doc do
     run_in_worker_process(doc, do: Yex.Nif.doc_should_load(doc))
   end
 
+  @doc """
+  Returns whether the document content is available in memory.
+
+  Root documents are always loaded. A sub-document received from a remote peer is only a
+  placeholder until it is loaded, either because it was created with `auto_load: true` or
+  because loading was requested for it. Use this to decide whether its content has to be
+  fetched from storage.
+  """
+  @spec loaded?(t) :: boolean()
+  def loaded?(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_is_loaded(doc))
+  end
+
+  @doc """
+  Requests a sub-document to be loaded.
+
+  The `should_load` flag of the document is set and the document is reported in the `loaded`
+  set of the subdocs event of its parent, see `monitor_subdocs/2`. A subdocument provider
+  reacts to that event by fetching the content of the document.
+  Pass the parent document when loading from within one of its transactions,
+  so the request joins that transaction. Root documents are always loaded.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> folder = Yex.Doc.get_map(doc, "folder")
+      iex> placeholder = Yex.Doc.with_options(%Yex.Doc.Options{should_load: false})
+      iex> sub_doc = Yex.Map.set_and_get(folder, "a.txt", placeholder)
+      iex> Yex.Doc.loaded?(sub_doc)
+      false
+      iex> Yex.Doc.load(sub_doc)
+      :ok
+      iex> Yex.Doc.loaded?(sub_doc)
+      true
+  """
+  @spec load(t, t | nil) :: :ok | {:error, term()}
+  def load(%__MODULE__{} = doc, parent \\ nil) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_load(doc, parent_txn(parent)))
+  end
+
+  @doc """
+  Sets the `should_load` flag of a sub-document.
+
+  Setting it to `true` is the same as `load/2`. Loading cannot be revoked, so setting it to
+  `false` on a document that is already loaded returns `{:error, :already_loaded}`.
+  """
+  @spec set_should_load(t, boolean(), t | nil) :: :ok | {:error, term()}
+  def set_should_load(%__MODULE__{} = doc, should_load, parent \\ nil)
+      when is_boolean(should_load) do
+    run_in_worker_process(doc,
+      do: Yex.Nif.doc_set_should_load(doc, parent_txn(parent), should_load)
+    )
+  end
+
+  @doc """
+  Destroys the document.
+
+  Its sub-documents are destroyed with it, and further operations through any handle on
+  them return `{:error, :destroyed}`, so observers registered on them no longer fire.
+  A sub-document is replaced in its parent by an unloaded placeholder; pass the parent
+  document when destroying from within one of its transactions.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> Yex.Doc.destroy(doc)
+      :ok
+      iex> Yex.Doc.destroy(doc)
+      {:error, :destroyed}
+  """
+  @spec destroy(t, t | nil) :: :ok | {:error, term()}
+  def destroy(%__MODULE__{} = doc, parent \\ nil) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_destroy(doc, parent_txn(parent)))
+  end
+
   def offset_kind(%__MODULE__{} = doc) do
     run_in_worker_process(doc, do: Yex.Nif.doc_offset_kind(doc))
   end
@@ -149,7 +255,7 @@ defmodule Yex.Doc do
   @doc """
   Get or insert the text type.
   """
-  @spec get_text(t, String.t()) :: Yex.Text.t()
+  @spec get_text(t, String.t()) :: Yex.Text.t() | {:error, :destroyed}
   def get_text(%__MODULE__{} = doc, name) do
     run_in_worker_process(doc, do: Yex.Nif.doc_get_or_insert_text(doc, name))
   end
@@ -157,7 +263,7 @@ defmodule Yex.Doc do
   @doc """
   Get or insert the array type.
   """
-  @spec get_array(t, String.t()) :: Yex.Array.t()
+  @spec get_array(t, String.t()) :: Yex.Array.t() | {:error, :destroyed}
   def get_array(%__MODULE__{} = doc, name) do
     run_in_worker_process(doc, do: Yex.Nif.doc_get_or_insert_array(doc, name))
   end
@@ -165,7 +271,7 @@ defmodule Yex.Doc do
   @doc """
   Get or insert the map type.
   """
-  @spec get_map(t, String.t()) :: Yex.Map.t()
+  @spec get_map(t, String.t()) :: Yex.Map.t() | {:error, :destroyed}
   def get_map(%__MODULE__{} = doc, name) do
     run_in_worker_process(doc, do: Yex.Nif.doc_get_or_insert_map(doc, name))
   end
@@ -177,6 +283,298 @@ defmodule Yex.Doc do
     run_in_worker_process(doc, do: Yex.Nif.doc_get_or_insert_xml_fragment(doc, name))
   end
 
+  @doc """
+  Gets or inserts several root types at once, within a single transaction.
+
+  `specs` lists `{name, kind}` pairs where kind is one of `:text`, `:array`, `:map` or
+  `:xml_fragment`. Returns the shared types keyed by name.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> %{"title" => %Yex.Text{}, "items" => %Yex.Array{}} =
+      ...>   Doc.init_roots(doc, [{"title", :text}, {"items", :array}])
+      iex> Doc.has_type?(doc, "items")
+      true
+  """
+  @spec init_roots(t, [{String.t(), :text | :array | :map | :xml_fragment}]) ::
+          %{String.t() => Yex.Text.t() | Yex.Array.t() | Yex.Map.t() | Yex.XmlFragment.t()}
+  def init_roots(%__MODULE__{} = doc, specs) when is_list(specs) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_init_roots(doc, cur_txn(doc), specs))
+  end
+
+  @doc """
+  Moves the content of the root type `from` into a new root type `to`, within a single
+  transaction, and returns the new shared type. `kind` is one of `:text`, `:array`, `:map`
+  or `:xml_fragment`.
+
+  This is meant for one-time migrations. Root types cannot be removed, so `from` is left
+  empty. The content is copied rather than moved, so it gets new item ids, and the change
+  does not merge with peers that still write to `from`: their edits stay under the old
+  name and concurrent edits are lost from the new one. Migrate while no other peer is
+  editing the document.
+
+  Returns `{:error, :already_exists}` if `to` is already a root type.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Yex.Text.insert(Doc.get_text(doc, "old"), 0, "Hello")
+      iex> text = Doc.rename_root(doc, "old", "new", :text)
+      iex> Yex.Text.to_string(text)
+      "Hello"
+      iex> Yex.Text.to_string(Doc.get_text(doc, "old"))
+      ""
+  """
+  @spec rename_root(t, String.t(), String.t(), :text | :array | :map | :xml_fragment) ::
+          Yex.Text.t() | Yex.Array.t() | Yex.Map.t() | Yex.XmlFragment.t() | {:error, term()}
+  def rename_root(%__MODULE__{} = doc, from, to, kind) when is_binary(from) and is_binary(to) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_rename_root(doc, cur_txn(doc), from, to, kind))
+  end
+
+  @doc """
+  Returns true if a root type with the given name exists in the document.
+  Unlike `get_text/2`, `get_map/2` and friends, this does not create the type.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Doc.has_type?(doc, "map")
+      false
+      iex> Doc.get_map(doc, "map")
+      iex> Doc.has_type?(doc, "map")
+      true
+  """
+  @spec has_type?(t, String.t()) :: boolean()
+  def has_type?(%__MODULE__{} = doc, name) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_has_type(doc, cur_txn(doc), name))
+  end
+
+  @doc """
+  Returns every root type of the document, keyed by name, without creating any.
+
+  Each root comes back as the struct matching the type it was created with, such as
+  `Yex.Map` or `Yex.XmlFragment`. A root only received from a remote update, and not yet
+  accessed locally with `get_text/2` and friends, has no known type and comes back as
+  `Yex.UndefinedRef`.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Doc.get_map(doc, "map")
+      iex> Doc.get_xml_fragment(doc, "xml")
+      iex> %{"map" => %Yex.Map{}, "xml" => %Yex.XmlFragment{}} = Doc.get_shared_types(doc)
+  """
+  @spec get_shared_types(t) :: %{String.t() => Yex.SharedType.t() | struct()}
+  def get_shared_types(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_get_shared_types(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Returns the sub-document with the given guid, or nil if this document holds no such sub-document.
+
+  Sub-documents are identified by `guid/1`, which is also how the docs delivered by
+  `monitor_subdocs/2` should be keyed in storage. A sub-document that has not been loaded yet
+  is still returned, but its content stays empty until it is loaded (see `loaded?/1`).
+  """
+  @spec subdoc_by_guid(t, String.t()) :: t | nil
+  def subdoc_by_guid(%__MODULE__{} = doc, guid) when is_binary(guid) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_subdoc_by_guid(doc, cur_txn(doc), guid))
+  end
+
+  @doc """
+  Returns all sub-documents held by this document, keyed by their guid.
+
+  Use this to route incoming updates to the nested document named in a subdocs event.
+  """
+  @spec get_subdocs(t) :: %{String.t() => t}
+  def get_subdocs(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_get_subdocs(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Returns false while the document holds pending updates that could not be integrated yet
+  because the updates they depend on have not been received.
+
+  Reads only reflect the integrated portion of what was applied, so a document that is
+  not consistent may be missing content that another peer already sees.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Doc.consistent?(doc)
+      true
+  """
+  @spec consistent?(t) :: boolean()
+  def consistent?(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_is_consistent(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Returns an approximate size of the document in bytes, for capacity monitoring.
+
+  The estimate is the size of the full document encoded as a v1 update, without walking
+  the in-memory store. It grows and shrinks with the content, deleted items included until
+  they are garbage collected, but the memory actually used is larger because of block
+  headers, indexes and allocator overhead. Treat it as a lower bound and calibrate
+  alert thresholds against measured memory use.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> size = Doc.memory_estimate(doc)
+      iex> Yex.Text.insert(Doc.get_text(doc, "text"), 0, "Hello World")
+      iex> Doc.memory_estimate(doc) > size
+      true
+  """
+  @spec memory_estimate(t) :: non_neg_integer()
+  def memory_estimate(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_memory_estimate(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Captures the current state of the document as a snapshot binary: its state vector and
+  delete set, encoded in the v1 format.
+
+  A snapshot is small and does not copy any content. Pass it to
+  `Yex.encode_state_as_update_from_snapshot/3` to rebuild the document as it was, or to
+  `Yex.Text.to_delta/3` to render the changes made since. Both need a document created with
+  `skip_gc: true`, so that deleted content is still around.
+
+  ## Examples
+      iex> doc = Doc.with_options(%Doc.Options{skip_gc: true})
+      iex> {:ok, snapshot} = Doc.snapshot(doc)
+      iex> is_binary(snapshot)
+      true
+  """
+  @spec snapshot(t) :: {:ok, binary()}
+  def snapshot(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_snapshot(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Runs a garbage collection pass over every deleted item of the document, replacing their
+  content with lightweight tombstones.
+
+  Commits only collect the items deleted in that transaction, so this is useful for
+  long-lived documents that kept deleted content around, for instance after loading updates.
+  The deleted content is discarded for good: snapshots taken before can no longer be
+  restored or diffed.
+
+  Returns `{:error, :gc_disabled}` for documents created with `skip_gc: true`.
+
+  ## Examples
+      iex> Doc.gc(Doc.new())
+      :ok
+      iex> Doc.gc(Doc.with_options(%Doc.Options{skip_gc: true}))
+      {:error, :gc_disabled}
+  """
+  @spec gc(t) :: :ok | {:error, :gc_disabled}
+  def gc(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_gc(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Returns a deterministic binary identifying the document state, for hashing and deduplication.
+
+  Documents that integrated the same operations give the same bytes regardless of the order
+  the operations arrived in, which is not the case for `Yex.encode_state_as_update/2`.
+  The binary only identifies the state: it carries no content and cannot be applied as an update.
+
+  ## Examples
+      iex> doc = Doc.with_options(%Doc.Options{client_id: 1})
+      iex> Doc.get_text(doc, "text") |> Yex.Text.insert(0, "abc")
+      iex> {:ok, state} = Doc.canonical_state(doc)
+      iex> state
+      <<1, 1, 3, 0>>
+  """
+  @spec canonical_state(t) :: {:ok, binary()}
+  def canonical_state(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_canonical_state(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Returns the origin of the transaction currently in progress in this process.
+
+  Inside `transaction/3` this is the origin the transaction was started with.
+  Outside of a transaction reads run in their own read-only transaction,
+  which never carries an origin, so `nil` is returned.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Doc.current_origin(doc)
+      nil
+      iex> Doc.transaction(doc, "origin", fn -> Doc.current_origin(doc) end)
+      "origin"
+  """
+  @spec current_origin(t) :: term()
+  def current_origin(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_current_origin(doc, cur_txn(doc)))
+  end
+
+  @doc """
+  Returns the origin the transaction in progress in this process was started with, `nil`
+  when it was started without one.
+
+  Unlike `current_origin/1`, this reads the transaction itself, and returns
+  `{:error, :no_transaction}` outside of `transaction/3`.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Doc.transaction(doc, {:user, 1}, fn -> Doc.transaction_origin(doc) end)
+      {:user, 1}
+      iex> Doc.transaction_origin(doc)
+      {:error, :no_transaction}
+  """
+  @spec transaction_origin(t) :: term() | {:error, :no_transaction}
+  def transaction_origin(%__MODULE__{} = doc) do
+    run_in_worker_process doc do
+      case cur_txn(doc) do
+        nil -> {:error, :no_transaction}
+        txn -> Yex.Nif.transaction_origin(txn)
+      end
+    end
+  end
+
+  @doc """
+  Returns the encoded state vector of the document when the transaction in progress in this
+  process started, as `{:ok, binary}`. Together with `transaction_after_state/1`, it tells
+  what the transaction adds: `Yex.encode_state_as_update(doc, before)` encodes exactly that.
+
+  Returns `{:error, :no_transaction}` outside of `transaction/3`.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> {:ok, sv} = Yex.encode_state_vector(doc)
+      iex> {:ok, before} =
+      ...>   Doc.transaction(doc, fn ->
+      ...>     Yex.Text.insert(Doc.get_text(doc, "text"), 0, "Hello")
+      ...>     Doc.transaction_before_state(doc)
+      ...>   end)
+      iex> before == sv
+      true
+  """
+  @spec transaction_before_state(t) :: {:ok, binary()} | {:error, :no_transaction}
+  def transaction_before_state(%__MODULE__{} = doc) do
+    run_in_worker_process doc do
+      case cur_txn(doc) do
+        nil -> {:error, :no_transaction}
+        txn -> Yex.Nif.transaction_before_state(txn)
+      end
+    end
+  end
+
+  @doc """
+  Returns the encoded state vector the transaction in progress in this process has reached
+  so far, as `{:ok, binary}`. Read just before the transaction ends, it is the state the
+  transaction advances the document to.
+
+  Returns `{:error, :no_transaction}` outside of `transaction/3`.
+  """
+  @spec transaction_after_state(t) :: {:ok, binary()} | {:error, :no_transaction}
+  def transaction_after_state(%__MODULE__{} = doc) do
+    run_in_worker_process doc do
+      case cur_txn(doc) do
+        nil -> {:error, :no_transaction}
+        txn -> Yex.Nif.transaction_after_state(txn)
+      end
+    end
+  end
+
   @doc """
   Start a transaction.
 
@@ -218,9 +616,37 @@ defmodule Yex.Doc do
     end
   end
 
+  @doc """
+  Commits every transaction started with `transaction/3` that is still open on the document,
+  and returns how many there were.
+
+  Meant for recovery, when the process running a transaction died before committing it.
+  Transactions opened through any handle on the document are committed, and the changes
+  made so far in them are kept, as there is no rollback.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> Doc.force_commit_all(doc)
+      0
+  """
+  @spec force_commit_all(t) :: non_neg_integer()
+  def force_commit_all(%__MODULE__{} = doc) do
+    run_in_worker_process(doc, do: Yex.Nif.doc_force_commit_all(doc))
+  end
+
   @doc """
   Monitor document updates.
    You can pass metadata as an option. This value is passed as the fourth element of the message.If omitted, it will be passed as a structure of Doc itself.
+
+  With `stats: true`, messages carry a fifth element `%{added: count, deleted: count}` giving
+  the write volume of the transaction, counted in clock units: one per character of text,
+  array element or map value. This is computed only when requested.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> {:ok, _ref} = Yex.Doc.monitor_update(doc, stats: true)
+      iex> Yex.Text.insert(Yex.Doc.get_text(doc, "text"), 0, "Hello")
+      iex> assert_receive {:update_v1, _update, nil, _metadata, %{added: 5, deleted: 0}}
   """
   @spec monitor_update(t, keyword) :: {:ok, reference()} | {:error, term()}
   def monitor_update(%__MODULE__{} = doc, opt \\ []) do
@@ -231,7 +657,13 @@ defmodule Yex.Doc do
     notify_pid = self()
 
     case run_in_worker_process(doc,
-           do: Yex.Nif.doc_monitor_update_v1(doc, notify_pid, Keyword.get(opt, :metadata, doc))
+           do:
+             Yex.Nif.doc_monitor_update_v1(
+               doc,
+               notify_pid,
+               Keyword.get(opt, :metadata, doc),
+               Keyword.get(opt, :stats, false)
+             )
          ) do
       {:ok, sub} ->
         {:ok, Yex.Subscription.register(sub)}
@@ -271,6 +703,12 @@ defmodule Yex.Doc do
     Yex.Subscription.unsubscribe(sub)
   end
 
+  @doc """
+  Monitor sub-documents being added, removed or loaded.
+
+  The calling process receives `{:subdocs, %{added: docs, removed: docs, loaded: docs}, origin, metadata}`.
+  Each doc can be identified with `guid/1` and looked up again later with `subdoc_by_guid/2`.
+  """
   def monitor_subdocs(%__MODULE__{} = doc, opt \\ []) do
     notify_pid = self()
 
@@ -285,6 +723,68 @@ defmodule Yex.Doc do
     end
   end
 
+  @doc """
+  Monitor the document being destroyed, see `destroy/2`.
+
+  The calling process receives `{:destroy, origin, metadata}`. The subscription ends with the
+  document, as destroying it drops every observer.
+  """
+  def monitor_destroy(%__MODULE__{} = doc, opt \\ []) do
+    notify_pid = self()
+
+    case run_in_worker_process(doc,
+           do: Yex.Nif.doc_observe_destroy(doc, notify_pid, Keyword.get(opt, :metadata, doc))
+         ) do
+      {:ok, sub} ->
+        {:ok, Yex.Subscription.register(sub)}
+
+      error ->
+        error
+    end
+  end
+
+  @doc """
+  Monitor committed transactions, once per transaction rather than once per update.
+
+  The calling process receives
+  `{:after_transaction, root_names, before_state, after_state, origin, metadata}`, where
+  `root_names` lists the root types changed by the transaction, including through their
+  nested types, and `before_state` and `after_state` are the encoded state vectors the
+  transaction advanced the document from and to.
+  Unsubscribe with `Yex.Subscription.unsubscribe/1`.
+
+  ## Examples
+      iex> doc = Doc.new()
+      iex> {:ok, _sub} = Doc.monitor_after_transaction(doc, metadata: :meta)
+      iex> Doc.transaction(doc, :origin, fn ->
+      ...>   Yex.Text.insert(Doc.get_text(doc, "text"), 0, "Hello")
+      ...>   Yex.Map.set(Doc.get_map(doc, "map"), "key", "value")
+      ...> end)
+      iex> assert_receive {:after_transaction, ["map", "text"], _, _, :origin, :meta}
+  """
+  @spec monitor_after_transaction(t, keyword()) :: {:ok, reference()} | {:error, term()}
+  def monitor_after_transaction(%__MODULE__{} = doc, opt \\ []) do
+    notify_pid = self()
+
+    case run_in_worker_process(doc,
+           do:
+             Yex.Nif.doc_observe_after_transaction(
+               doc,
+               notify_pid,
+               Keyword.get(opt, :metadata, doc)
+             )
+         ) do
+      {:ok, sub} ->
+        {:ok, Yex.Subscription.register(sub)}
+
+      error ->
+        error
+    end
+  end
+
+  defp parent_txn(nil), do: nil
+  defp parent_txn(%__MODULE__{} = parent), do: cur_txn(parent)
+
   defp cur_txn(%__MODULE__{reference: ref}) do
     Process.get(ref, nil)
   end
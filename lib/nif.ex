@@ -21,13 +21,34 @@ defmodule Yex.Nif do
 
   def doc_new(), do: :erlang.nif_error(:nif_not_loaded)
   def doc_with_options(_option), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_new_pair(_client_id_a, _client_id_b), do: :erlang.nif_error(:nif_not_loaded)
   def doc_get_or_insert_text(_doc, _name), do: :erlang.nif_error(:nif_not_loaded)
   def doc_get_or_insert_array(_doc, _name), do: :erlang.nif_error(:nif_not_loaded)
   def doc_get_or_insert_map(_doc, _name), do: :erlang.nif_error(:nif_not_loaded)
   def doc_get_or_insert_xml_fragment(_doc, _name), do: :erlang.nif_error(:nif_not_loaded)
-  def doc_monitor_update_v1(_doc, _pid, _metadata), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_init_roots(_doc, _cur_txn, _specs), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_rename_root(_doc, _cur_txn, _from, _to, _kind), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_has_type(_doc, _cur_txn, _name), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_get_shared_types(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_subdoc_by_guid(_doc, _cur_txn, _guid), do: :erlang.nif_error(:nif_not_loaded)
+
+  def doc_get_subdocs(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_is_consistent(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_memory_estimate(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_snapshot(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_gc(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_canonical_state(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_current_origin(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_monitor_update_v1(_doc, _pid, _metadata, _with_stats),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def doc_monitor_update_v2(_doc, _pid, _metadata), do: :erlang.nif_error(:nif_not_loaded)
 
+  def doc_observe_after_transaction(_doc, _pid, _metadata),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def doc_observe_destroy(_doc, _pid, _metadata), do: :erlang.nif_error(:nif_not_loaded)
+
   def doc_monitor_subdocs(_doc, _pid, _metadata),
     do: :erlang.nif_error(:nif_not_loaded)
 
@@ -49,6 +70,15 @@ defmodule Yex.Nif do
   def doc_should_load(_doc),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def doc_is_loaded(_doc),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def doc_load(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
+  def doc_set_should_load(_doc, _cur_txn, _should_load), do: :erlang.nif_error(:nif_not_loaded)
+
+  def doc_destroy(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
   def doc_offset_kind(_doc),
     do: :erlang.nif_error(:nif_not_loaded)
 
@@ -56,10 +86,21 @@ defmodule Yex.Nif do
 
   def doc_begin_transaction(_doc, _origin), do: :erlang.nif_error(:nif_not_loaded)
   def commit_transaction(_doc), do: :erlang.nif_error(:nif_not_loaded)
+  def doc_force_commit_all(_doc), do: :erlang.nif_error(:nif_not_loaded)
+  def transaction_origin(_cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def transaction_before_state(_cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def transaction_after_state(_cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
+  def text_insert(_text, _cur_txn, _index, _content, _origin),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def text_insert(_text, _cur_txn, _index, _content), do: :erlang.nif_error(:nif_not_loaded)
+  def text_insert_with_attributes(_text, _cur_txn, _index, _content, _attr, _origin),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def text_insert_embed(_text, _cur_txn, _index, _embed, _attr),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def text_insert_with_attributes(_text, _cur_txn, _index, _content, _attr),
+  def text_insert_at_sticky(_text, _cur_txn, _sticky_index, _content, _attr),
     do: :erlang.nif_error(:nif_not_loaded)
 
   def text_quote(_text, _cur_txn, _index, _len), do: :erlang.nif_error(:nif_not_loaded)
@@ -67,15 +108,44 @@ defmodule Yex.Nif do
   def text_apply_delta(_text, _cur_txn, _delta),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def text_apply_delta_checked(_text, _cur_txn, _delta),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def text_apply_quill_delta(_text, _cur_txn, _delta_json),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def text_to_delta(_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
 
+  def text_to_delta_with_snapshots(_text, _cur_txn, _snapshot, _prev_snapshot),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def text_run_stats(_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
   def text_delete(_text, _cur_txn, _index, _len), do: :erlang.nif_error(:nif_not_loaded)
   def text_format(_text, _cur_txn, _index, _len, _attr), do: :erlang.nif_error(:nif_not_loaded)
+  def text_apply_ops(_text, _cur_txn, _ops), do: :erlang.nif_error(:nif_not_loaded)
   def text_to_string(_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
+  def text_to_string_with_embeds(_text, _cur_txn, _placeholder),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def text_find_formatted(_text, _cur_txn, _attr_key, _attr_value),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def text_length(_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def text_offset_of_char(_text, _cur_txn, _char_index), do: :erlang.nif_error(:nif_not_loaded)
+  def text_char_at(_text, _cur_txn, _index), do: :erlang.nif_error(:nif_not_loaded)
+  def text_substring(_text, _cur_txn, _index, _len), do: :erlang.nif_error(:nif_not_loaded)
+  def text_word_count(_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def text_line_count(_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
+  def array_insert(_array, _cur_txn, _index, _value, _origin),
+    do: :erlang.nif_error(:nif_not_loaded)
 
-  def array_insert(_array, _cur_txn, _index, _value), do: :erlang.nif_error(:nif_not_loaded)
   def array_insert_list(_array, _cur_txn, _index, _values), do: :erlang.nif_error(:nif_not_loaded)
+  def array_push_back(_array, _cur_txn, _value), do: :erlang.nif_error(:nif_not_loaded)
+  def array_push_front(_array, _cur_txn, _value), do: :erlang.nif_error(:nif_not_loaded)
+  def array_append_list(_array, _cur_txn, _values), do: :erlang.nif_error(:nif_not_loaded)
 
   def array_insert_and_get(_array, _cur_txn, _index, _value),
     do: :erlang.nif_error(:nif_not_loaded)
@@ -87,25 +157,55 @@ defmodule Yex.Nif do
     do: :erlang.nif_error(:nif_not_loaded)
 
   def array_get(_array, _cur_txn, _index), do: :erlang.nif_error(:nif_not_loaded)
+  def array_element_id(_array, _cur_txn, _index), do: :erlang.nif_error(:nif_not_loaded)
+  def array_index_of(_array, _cur_txn, _value), do: :erlang.nif_error(:nif_not_loaded)
 
   def array_delete_range(_array, _cur_txn, _index, _length),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def array_remove_where(_array, _cur_txn, _field, _equals),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def array_clear(_array, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
   def array_move_to(_array, _cur_txn, _from, _to), do: :erlang.nif_error(:nif_not_loaded)
 
   def array_quote(_array, _cur_txn, _index, _len), do: :erlang.nif_error(:nif_not_loaded)
 
   def array_to_json(_array, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
 
-  def map_set(_map, _cur_txn, _key, _value), do: :erlang.nif_error(:nif_not_loaded)
+  def array_to_json_chunked(_array, _cur_txn, _chunk_size), do: :erlang.nif_error(:nif_not_loaded)
+
+  def array_json_size(_array, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
+  def array_content_hash(_array, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def array_deep_equal(_array, _cur_txn, _expected), do: :erlang.nif_error(:nif_not_loaded)
+  def array_iter_open(_array), do: :erlang.nif_error(:nif_not_loaded)
+  def array_iter_next(_iter, _batch_size), do: :erlang.nif_error(:nif_not_loaded)
+  def array_iter_close(_iter), do: :erlang.nif_error(:nif_not_loaded)
+
+  def map_set(_map, _cur_txn, _key, _value, _origin), do: :erlang.nif_error(:nif_not_loaded)
+
+  def map_compare_and_set(_map, _cur_txn, _key, _expected, _new),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def map_size(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def map_get(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
+  def map_fetch(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
+  def map_increment(_map, _cur_txn, _key, _delta), do: :erlang.nif_error(:nif_not_loaded)
+  def map_get_or(_map, _cur_txn, _key, _default), do: :erlang.nif_error(:nif_not_loaded)
+  def map_value_id(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
   def map_contains_key(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
   def map_delete(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
+  def map_pop(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
+  def map_clear(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def map_to_map(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def map_keys(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def map_values(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def map_to_json(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def map_to_json_ordered(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def map_content_hash(_map, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def map_deep_equal(_map, _cur_txn, _expected), do: :erlang.nif_error(:nif_not_loaded)
   def map_link(_map, _cur_txn, _key), do: :erlang.nif_error(:nif_not_loaded)
 
   def xml_fragment_insert(_xml_fragment, _cur_txn, _index, _content),
@@ -122,6 +222,10 @@ defmodule Yex.Nif do
   def xml_fragment_length(_xml_fragment, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
 
   def xml_fragment_parent(_xml_fragment, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def xml_fragment_tree_walk(_xml_fragment, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
+  def xml_fragment_select_by_tag(_xml_fragment, _cur_txn, _tag),
+    do: :erlang.nif_error(:nif_not_loaded)
 
   def xml_element_insert(_xml_element, _cur_txn, _index, _content),
     do: :erlang.nif_error(:nif_not_loaded)
@@ -133,11 +237,16 @@ defmodule Yex.Nif do
     do: :erlang.nif_error(:nif_not_loaded)
 
   def xml_element_get(_xml_element, _cur_txn, _index), do: :erlang.nif_error(:nif_not_loaded)
+  def xml_element_first_child(_xml_element, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def xml_element_children(_xml_element, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def xml_element_length(_xml_element, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
 
   def xml_element_insert_attribute(_xml_element, _cur_txn, _key, _value),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def xml_element_set_attributes(_xml_element, _cur_txn, _attrs),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def xml_element_remove_attribute(_xml_element, _cur_txn, _key),
     do: :erlang.nif_error(:nif_not_loaded)
 
@@ -158,6 +267,9 @@ defmodule Yex.Nif do
   def xml_text_insert_with_attributes(_xml_text, _cur_txn, _index, _content, _attr),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def xml_text_insert_at_sticky(_xml_text, _cur_txn, _sticky_index, _content, _attr),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def xml_text_delete(_xml_text, _cur_txn, _index, _length),
     do: :erlang.nif_error(:nif_not_loaded)
 
@@ -175,33 +287,55 @@ defmodule Yex.Nif do
   def xml_text_parent(_xml_text, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def xml_text_quote(_xml_text, _cur_txn, _index, _len), do: :erlang.nif_error(:nif_not_loaded)
 
-  def shared_type_observe(_map, _cur_txn, _pid, _ref, _metadata),
+  def shared_type_observe(_map, _cur_txn, _pid, _ref, _metadata, _options),
     do: :erlang.nif_error(:nif_not_loaded)
 
-  def shared_type_observe_deep(_map, _cur_txn, _pid, _ref, _metadata),
+  def shared_type_observe_many(_doc, _shared_types, _cur_txn, _pid, _ref, _metadata, _options),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def shared_type_observe_deep(_map, _cur_txn, _pid, _ref, _metadata, _options),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def shared_type_equal(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+  def shared_type_path(_shared_type, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
   def sticky_index_new(_shared_type, _cur_txn, _index, _assoc),
     do: :erlang.nif_error(:nif_not_loaded)
 
   def sticky_index_get_offset(_sticky_index, _cur_txn),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def sticky_index_compare(_a, _b, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+  def sticky_index_encode(_sticky_index), do: :erlang.nif_error(:nif_not_loaded)
+  def sticky_index_decode(_doc, _binary, _assoc), do: :erlang.nif_error(:nif_not_loaded)
+
   def encode_state_vector_v1(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def encode_state_as_update_v1(_doc, _cur_txn, _diff), do: :erlang.nif_error(:nif_not_loaded)
 
+  def encode_state_as_update_from_snapshot_v1(_doc, _cur_txn, _snapshot, _diff),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def encode_diff_and_state_vector_v1(_doc, _cur_txn, _remote_sv),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def apply_update_v1_returning_summary(_doc, _cur_txn, _update),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def apply_update_v1(_doc, _cur_txn, _update), do: :erlang.nif_error(:nif_not_loaded)
   def merge_updates_v1(_updates), do: :erlang.nif_error(:nif_not_loaded)
+  def diff_update_v1(_update, _state_vector), do: :erlang.nif_error(:nif_not_loaded)
+  def state_vector_from_update_v1(_update), do: :erlang.nif_error(:nif_not_loaded)
+  def validate_update_v1(_doc, _cur_txn, _update), do: :erlang.nif_error(:nif_not_loaded)
 
   def encode_state_vector_v2(_doc, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
   def encode_state_as_update_v2(_doc, _cur_txn, _diff), do: :erlang.nif_error(:nif_not_loaded)
   def apply_update_v2(_doc, _cur_txn, _update), do: :erlang.nif_error(:nif_not_loaded)
   def merge_updates_v2(_updates), do: :erlang.nif_error(:nif_not_loaded)
+  def negotiate_update_format(_supported), do: :erlang.nif_error(:nif_not_loaded)
 
+  def sync_message_decode_stream_v1(_message), do: :erlang.nif_error(:nif_not_loaded)
   def sync_message_decode_v1(_message), do: :erlang.nif_error(:nif_not_loaded)
+  def sync_capabilities(), do: :erlang.nif_error(:nif_not_loaded)
   def sync_message_encode_v1(_message), do: :erlang.nif_error(:nif_not_loaded)
   def sync_messages_encode_v1(_messages), do: :erlang.nif_error(:nif_not_loaded)
 
@@ -225,9 +359,14 @@ defmodule Yex.Nif do
   def awareness_client_id(_awareness), do: :erlang.nif_error(:nif_not_loaded)
   def awareness_get_client_ids(_awareness), do: :erlang.nif_error(:nif_not_loaded)
   def awareness_get_states(_awareness), do: :erlang.nif_error(:nif_not_loaded)
+  def awareness_get_states_with_meta(_awareness), do: :erlang.nif_error(:nif_not_loaded)
 
   def awareness_get_local_state(_awareness), do: :erlang.nif_error(:nif_not_loaded)
   def awareness_set_local_state(_awareness, _map), do: :erlang.nif_error(:nif_not_loaded)
+
+  def awareness_set_local_state_field(_awareness, _key, _value),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def awareness_clean_local_state(_awareness), do: :erlang.nif_error(:nif_not_loaded)
 
   def awareness_monitor_update(_awareness, _pid, _metadata),
@@ -237,11 +376,13 @@ defmodule Yex.Nif do
     do: :erlang.nif_error(:nif_not_loaded)
 
   def awareness_encode_update_v1(_awareness, _clients), do: :erlang.nif_error(:nif_not_loaded)
+  def awareness_diff_v1(_awareness, _remote_clocks), do: :erlang.nif_error(:nif_not_loaded)
 
   def awareness_apply_update_v1(_awareness, _update, _origin),
     do: :erlang.nif_error(:nif_not_loaded)
 
   def awareness_remove_states(_awareness, _clients), do: :erlang.nif_error(:nif_not_loaded)
+  def awareness_remove_outdated(_awareness, _timeout_ms), do: :erlang.nif_error(:nif_not_loaded)
 
   def undo_manager_new(_doc, _scope), do: :erlang.nif_error(:nif_not_loaded)
 
@@ -251,11 +392,23 @@ defmodule Yex.Nif do
   def undo_manager_include_origin(_undo_manager, _origin), do: :erlang.nif_error(:nif_not_loaded)
   def undo_manager_undo(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
   def undo_manager_redo(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
+  def undo_manager_can_undo(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
+  def undo_manager_can_redo(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
+  def undo_manager_stack_sizes(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
   def undo_manager_expand_scope(_undo_manager, _scope), do: :erlang.nif_error(:nif_not_loaded)
   def undo_manager_exclude_origin(_undo_manager, _origin), do: :erlang.nif_error(:nif_not_loaded)
   def undo_manager_stop_capturing(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
   def undo_manager_clear(_undo_manager), do: :erlang.nif_error(:nif_not_loaded)
 
+  def undo_manager_observe_item_added(_undo_manager, _pid, _ref, _metadata),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def undo_manager_observe_item_updated(_undo_manager, _pid, _ref, _metadata),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  def undo_manager_observe_item_popped(_undo_manager, _pid, _ref, _metadata),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   def weak_string(_weak, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
 
   def weak_unquote(_weak, _cur_txn),
@@ -267,6 +420,10 @@ defmodule Yex.Nif do
   def weak_as_prelim(_weak, _cur_txn),
     do: :erlang.nif_error(:nif_not_loaded)
 
+  def weak_source_path(_weak, _cur_txn), do: :erlang.nif_error(:nif_not_loaded)
+
   def normalize_number(_number),
     do: :erlang.nif_error(:nif_not_loaded)
+
+  def normalize_typed(_value, _kind), do: :erlang.nif_error(:nif_not_loaded)
 end
@@ -47,6 +47,26 @@ defmodule Yex.Awareness do
     do: Yex.Nif.awareness_set_local_state(awareness, map)
 
   @doc """
+  Sets a single key of the local state, keeping the other keys as they are.
+  Starts from an empty map when no local state is set. Returns `{:error, :not_a_map}` if the
+  local state is set to something other than a map.
+
+  The state is read and written back as separate steps, so a field set concurrently by
+  another process may be lost; update the local state from a single process.
+
+  ## Examples
+      iex> {:ok, awareness} = Yex.Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{ client_id: 100 }))
+      iex> Yex.Awareness.set_local_state(awareness, %{ "name" => "alice" })
+      iex> Yex.Awareness.set_local_state_field(awareness, "status", "typing")
+      :ok
+      iex> Yex.Awareness.get_local_state(awareness)
+      %{"name" => "alice", "status" => "typing"}
+  """
+  @spec set_local_state_field(t, binary(), term()) :: :ok | {:error, :not_a_map}
+  def set_local_state_field(%__MODULE__{} = awareness, key, value) when is_binary(key),
+    do: Yex.Nif.awareness_set_local_state_field(awareness, key, value)
+
+  @doc """
 
 
   ## Examples
@@ -85,6 +105,22 @@ defmodule Yex.Awareness do
   def get_states(%__MODULE__{} = awareness),
     do: Yex.Nif.awareness_get_states(awareness)
 
+  @doc """
+  Like `get_states/1`, with the clock of each state and the local time it was last received
+  or set, in milliseconds since the Unix epoch.
+
+  ## Examples
+      iex> {:ok, awareness} = Yex.Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{ client_id: 100 }))
+      iex> Yex.Awareness.set_local_state(awareness, %{ "key" => "value" })
+      iex> %{100 => %{state: %{"key" => "value"}, clock: 1, last_updated_ms: _}} =
+      ...>   Yex.Awareness.get_states_with_meta(awareness)
+  """
+  @spec get_states_with_meta(t) :: %{
+          integer() => %{state: term(), clock: non_neg_integer(), last_updated_ms: integer()}
+        }
+  def get_states_with_meta(%__MODULE__{} = awareness),
+    do: Yex.Nif.awareness_get_states_with_meta(awareness)
+
   @doc """
    Monitor to remote and local awareness changes. This event is called even when the awareness state does not change but is only updated to notify other users that this client is still online. Use this event if you want to propagate awareness state to other users.
    You can pass metadata as an option. This value is passed as the fourth element of the message.If omitted, it will be passed as a structure of awareness itself.
@@ -147,6 +183,25 @@ defmodule Yex.Awareness do
     Yex.Nif.awareness_encode_update_v1(awareness, clients)
   end
 
+  @doc """
+  Encodes an update containing only the client states the remote side is missing or has outdated.
+
+  `remote_clocks` maps each client id the remote knows about to the clock it has seen for it,
+  which keeps presence traffic small when a peer reconnects.
+
+  ## Examples
+      iex> {:ok, awareness} = Yex.Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{ client_id: 10 }))
+      iex> Yex.Awareness.set_local_state(awareness, %{ "key" => "value" })
+      iex> Yex.Awareness.diff(awareness, %{10 => 1})
+      {:ok, <<0>>}
+      iex> Yex.Awareness.diff(awareness, %{}) == Yex.Awareness.encode_update(awareness)
+      true
+  """
+  @spec diff(t, %{integer() => non_neg_integer()}) :: {:ok, binary()} | {:error, term()}
+  def diff(%__MODULE__{} = awareness, remote_clocks) when is_map(remote_clocks) do
+    Yex.Nif.awareness_diff_v1(awareness, remote_clocks)
+  end
+
   @doc """
     Applies an update (incoming from remote channel or generated using [Awareness.encode_update] method) and modifies a state of a current instance.
 
@@ -173,4 +228,17 @@ defmodule Yex.Awareness do
   def remove_states(awareness, clients) do
     Yex.Nif.awareness_remove_states(awareness, clients)
   end
+
+  @doc """
+  Removes the states of remote clients that have not been updated for `timeout_ms`
+  milliseconds, and returns their client ids. Clients renew their state periodically while
+  online, so calling this regularly expires the presence of clients that went away.
+
+  The local state is never removed.
+  """
+  @spec remove_outdated(t, non_neg_integer()) :: [integer()]
+  def remove_outdated(%__MODULE__{} = awareness, timeout_ms)
+      when is_integer(timeout_ms) and timeout_ms >= 0 do
+    Yex.Nif.awareness_remove_outdated(awareness, timeout_ms)
+  end
 end
@@ -51,6 +51,26 @@ defmodule Yex.Sync do
     end
   end
 
+  @doc """
+  Decodes the first message of a buffer read from a stream transport.
+
+  Returns `{:ok, message, bytes_consumed}` when a complete message is available; the remaining
+  `binary_part(buffer, bytes_consumed, byte_size(buffer) - bytes_consumed)` may hold further messages.
+  Returns `{:incomplete, bytes_needed_hint}` when the buffer ends in the middle of a message,
+  so the caller can wait for more data before trying again. The hint is a lower bound.
+
+  ## Examples
+
+      iex> Yex.Sync.message_decode_stream(<<0, 0, 1, 0, 3>>)
+      {:ok, {:sync, {:sync_step1, <<0>>}}, 4}
+      iex> Yex.Sync.message_decode_stream(<<0, 0, 4, 0>>)
+      {:incomplete, 3}
+  """
+  @spec message_decode_stream(binary) ::
+          {:ok, message, non_neg_integer()} | {:incomplete, pos_integer()} | {:error, term}
+  def message_decode_stream(buffer) when is_binary(buffer),
+    do: Yex.Nif.sync_message_decode_stream_v1(buffer)
+
   @doc """
   Encodes a message into binary format, returning `{:ok, binary}` if encoding
   succeeds or `{:error, reason}` on failure.
@@ -75,6 +95,25 @@ defmodule Yex.Sync do
     end
   end
 
+  @doc """
+  Returns the sync protocol version implemented by this build, and which message types
+  it can encode and decode, so peers can negotiate features instead of assuming them.
+
+  ## Examples
+
+      iex> Yex.Sync.capabilities()
+      %{version: 1, sync: true, awareness: true, auth: true, query_awareness: true, custom: true}
+  """
+  @spec capabilities() :: %{
+          version: pos_integer(),
+          sync: boolean(),
+          awareness: boolean(),
+          auth: boolean(),
+          query_awareness: boolean(),
+          custom: boolean()
+        }
+  def capabilities, do: Yex.Nif.sync_capabilities()
+
   @spec message_decode_v1(binary) :: {:ok, message} | {:error, term}
   def message_decode_v1(message), do: Yex.Nif.sync_message_decode_v1(message)
 
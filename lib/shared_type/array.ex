@@ -35,6 +35,9 @@ defmodule Yex.Array do
     * `array` - The array to modify
     * `index` - The position to insert at (0-based). Supports negative indexing: -1 for end (append), -2 for before last, etc.
     * `content` - The content to insert (can be any JSON-compatible value or shared type)
+    * `opts` - Options:
+      * `:origin` - origin of the transaction opened for this change, as with `Yex.Doc.transaction/3`.
+        Ignored inside an explicit transaction, whose origin applies instead.
 
   ## Examples
       iex> doc = Yex.Doc.new()
@@ -64,10 +67,10 @@ defmodule Yex.Array do
       iex> Yex.Array.to_json(array)
       ["first", "second", "third", "after_last"]
   """
-  @spec insert(t, integer(), Yex.input_type()) :: :ok
-  def insert(%__MODULE__{doc: doc} = array, index, content) when is_integer(index) do
+  @spec insert(t, integer(), Yex.input_type(), keyword()) :: :ok
+  def insert(%__MODULE__{doc: doc} = array, index, content, opts \\ []) when is_integer(index) do
     Doc.run_in_worker_process doc do
-      Yex.Nif.array_insert(array, cur_txn(array), index, content)
+      Yex.Nif.array_insert(array, cur_txn(array), index, content, Keyword.get(opts, :origin))
     end
   end
 
@@ -93,7 +96,8 @@ defmodule Yex.Array do
   ## Parameters
     * `array` - The array to modify
     * `index` - The position to insert at (0-based). Supports negative indexing: -1 for end (append), -2 for before last, etc.
-    * `contents` - A list of contents to insert
+    * `contents` - A list of contents to insert, in order. Prelims such as `Yex.MapPrelim` can be
+      mixed with plain values. An empty list inserts nothing and returns :ok whatever the index.
 
   ## Examples
       iex> doc = Yex.Doc.new()
@@ -101,8 +105,11 @@ defmodule Yex.Array do
       iex> Yex.Array.insert_list(array, 0, [1,2,3,4,5])
       iex> Yex.Array.to_json(array)
       [1.0, 2.0, 3.0, 4.0, 5.0]
+      iex> Yex.Array.insert_list(array, 1, [Yex.MapPrelim.from(%{"a" => 1}), "b"])
+      iex> Yex.Array.to_json(array)
+      [1.0, %{"a" => 1.0}, "b", 2.0, 3.0, 4.0, 5.0]
   """
-  @spec insert_list(t, integer(), list(Yex.any_type())) :: :ok
+  @spec insert_list(t, integer(), list(Yex.input_type())) :: :ok
   def insert_list(%__MODULE__{doc: doc} = array, index, contents) when is_integer(index) do
     Doc.run_in_worker_process doc do
       Yex.Nif.array_insert_list(array, cur_txn(array), index, contents)
@@ -118,8 +125,34 @@ defmodule Yex.Array do
     * `content` - The content to append
   """
   @spec push(t, Yex.input_type()) :: :ok
-  def push(array, content) do
-    insert(array, @u32_max, content)
+  def push(%__MODULE__{doc: doc} = array, content) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_push_back(array, cur_txn(array), content)
+    end
+  end
+
+  @doc """
+  Appends contents to the end of the array.
+  Cheaper than `insert_list/3` with a computed index for append-only data such as logs.
+
+  ## Parameters
+    * `array` - The array to modify
+    * `contents` - A list of contents to append
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.push(array, "a")
+      iex> Yex.Array.push_list(array, ["b", "c"])
+      :ok
+      iex> Yex.Array.to_json(array)
+      ["a", "b", "c"]
+  """
+  @spec push_list(t, list(Yex.any_type())) :: :ok
+  def push_list(%__MODULE__{doc: doc} = array, contents) when is_list(contents) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_append_list(array, cur_txn(array), contents)
+    end
   end
 
   @doc """
@@ -149,9 +182,20 @@ defmodule Yex.Array do
   ## Parameters
     * `array` - The array to modify
     * `content` - The content to prepend
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.push(array, "World")
+      iex> Yex.Array.unshift(array, Yex.MapPrelim.from(%{"greeting" => "Hello"}))
+      iex> Yex.Array.to_json(array)
+      [%{"greeting" => "Hello"}, "World"]
   """
-  def unshift(%__MODULE__{} = array, content) do
-    insert(array, 0, content)
+  @spec unshift(t, Yex.input_type()) :: :ok
+  def unshift(%__MODULE__{doc: doc} = array, content) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_push_front(array, cur_txn(array), content)
+    end
   end
 
   @doc """
@@ -199,6 +243,49 @@ defmodule Yex.Array do
     end
   end
 
+  @doc """
+  Removes all elements of the array in a single transaction.
+  Does nothing when the array is already empty.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, [1, 2, 3])
+      iex> Yex.Array.clear(array)
+      :ok
+      iex> Yex.Array.to_list(array)
+      []
+  """
+  @spec clear(t) :: :ok
+  def clear(%__MODULE__{doc: doc} = array) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_clear(array, cur_txn(array))
+    end
+  end
+
+  @doc """
+  Removes every element that is a plain map whose `field` equals `value`, in a single
+  transaction. Returns the number of elements removed.
+
+  Only the scalar fields of plain map elements are inspected; nested shared types such
+  as `Yex.Map` are skipped and never removed. Numbers are compared by value.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, [%{"done" => true}, %{"done" => false}, "x", %{"done" => true}])
+      iex> Yex.Array.remove_where(array, "done", true)
+      2
+      iex> Yex.Array.to_list(array)
+      [%{"done" => false}, "x"]
+  """
+  @spec remove_where(t, binary(), Yex.any_type()) :: non_neg_integer()
+  def remove_where(%__MODULE__{doc: doc} = array, field, value) when is_binary(field) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_remove_where(array, cur_txn(array), field, value)
+    end
+  end
+
   @doc """
   Moves element found at `source` index into `target` index position. Both indexes refer to a current state of the document.
   ## Examples pushes a string then fetches it back
@@ -309,6 +396,52 @@ defmodule Yex.Array do
     end
   end
 
+  @doc """
+  Returns a stable id for the element at the specified index as `{client_id, clock}`.
+
+  The id belongs to the element itself, so it stays the same when other elements
+  are inserted or removed around it. Useful as a key when rendering lists.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.push(array, "Hello")
+      iex> {:ok, id} = Yex.Array.element_id(array, 0)
+      iex> Yex.Array.insert(array, 0, "World")
+      iex> Yex.Array.element_id(array, 1) == {:ok, id}
+      true
+      iex> Yex.Array.element_id(array, 2)
+      :error
+  """
+  @spec element_id(t, integer()) :: {:ok, {integer(), integer()}} | :error
+  def element_id(%__MODULE__{doc: doc} = array, index) when is_integer(index) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_element_id(array, cur_txn(array), index)
+    end
+  end
+
+  @doc """
+  Returns the index of the first element equal to `value` as `{:ok, index}`, or `:error`
+  when there is none. The search runs natively without copying the array.
+
+  Only scalar content is compared; nested shared types such as `Yex.Map` never match.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, ["a", "b", "a"])
+      iex> Yex.Array.index_of(array, "a")
+      {:ok, 0}
+      iex> Yex.Array.index_of(array, "c")
+      :error
+  """
+  @spec index_of(t, Yex.any_type()) :: {:ok, non_neg_integer()} | :error
+  def index_of(%__MODULE__{doc: doc} = array, value) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_index_of(array, cur_txn(array), value)
+    end
+  end
+
   @doc """
   Returns as list
 
@@ -336,13 +469,23 @@ defmodule Yex.Array do
 
   @doc """
     slices the array from start_index for amount of elements, then gets them back as Elixir List and takes every `step` element.
+
+    A negative `step` walks the array backwards: `start_index` then counts from the last
+    element. A `start_index` past the end yields an empty list.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, ["a", "b", "c", "d"])
+      iex> Yex.Array.slice_take_every(array, 0, 4, -2)
+      ["d", "b"]
   """
   def slice_take_every(_array, _start_index, _amount, 0) do
     []
   end
 
   def slice_take_every(%__MODULE__{doc: doc} = array, start_index, amount, step)
-      when is_integer(step) and step > 0 do
+      when is_integer(step) do
     Doc.run_in_worker_process doc do
       Yex.Nif.array_slice(array, cur_txn(array), start_index, amount, step)
     end
@@ -368,6 +511,9 @@ defmodule Yex.Array do
   @doc """
   Convert to json-compatible format.
 
+  The whole array is converted at once, so the memory used peaks at roughly twice the size of
+  the result. For large arrays, check `json_size/1` first or use `stream/2`.
+
   ## Examples adds a few items to an array and returns as Elixir List
       iex> doc = Yex.Doc.new()
       iex> array = Yex.Doc.get_array(doc, "array")
@@ -383,6 +529,181 @@ defmodule Yex.Array do
     end
   end
 
+  @doc """
+  Like `to_json/1`, but returns the elements in batches of at most `chunk_size`.
+
+  Each batch is converted before the next one is started, which limits the intermediate
+  representation built natively to a single batch. The returned list still holds the whole
+  array; use `stream/2` to process a large array without materializing it.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, ["a", "b", "c"])
+      iex> Yex.Array.to_json_chunked(array, 2)
+      [["a", "b"], ["c"]]
+  """
+  @spec to_json_chunked(t, pos_integer()) :: [list()]
+  def to_json_chunked(%__MODULE__{doc: doc} = array, chunk_size)
+      when is_integer(chunk_size) and chunk_size > 0 do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_to_json_chunked(array, cur_txn(array), chunk_size)
+    end
+  end
+
+  @doc """
+  Returns the number of values `to_json/1` would produce, counting nested values and map keys.
+
+  Use it to estimate the size of the result before materializing it.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, ["a", %{"b" => [1, 2.5]}])
+      iex> Yex.Array.json_size(array)
+      6
+  """
+  @spec json_size(t) :: non_neg_integer()
+  def json_size(%__MODULE__{doc: doc} = array) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_json_size(array, cur_txn(array))
+    end
+  end
+
+  @doc """
+  Returns a hash of the array content, as seen by `to_json/1`.
+
+  Equal content gives equal hashes, so comparing hashes is a cheap way to tell
+  whether an array needs to be fetched again. The hash is order sensitive, and it
+  is only stable within the same build of the library, so it should not be persisted.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.push(array, "Hello")
+      iex> hash = Yex.Array.content_hash(array)
+      iex> Yex.Array.push(array, "World")
+      iex> Yex.Array.content_hash(array) == hash
+      false
+  """
+  @spec content_hash(t) :: non_neg_integer()
+  def content_hash(%__MODULE__{doc: doc} = array) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_content_hash(array, cur_txn(array))
+    end
+  end
+
+  @doc """
+  Checks whether the array content, as seen by `to_json/1`, structurally equals `expected`.
+
+  Nested maps and arrays are compared as a whole, and numbers by value, so integers
+  match their float counterparts. Raises `ArgumentError` if `expected` cannot be
+  represented as JSON-like content.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, [1, %{"key" => "value"}])
+      iex> Yex.Array.deep_equal?(array, [1, %{"key" => "value"}])
+      true
+      iex> Yex.Array.deep_equal?(array, [1])
+      false
+  """
+  @spec deep_equal?(t, list()) :: boolean()
+  def deep_equal?(%__MODULE__{doc: doc} = array, expected) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_deep_equal(array, cur_txn(array), expected)
+    end
+  end
+
+  @doc """
+  Opens an iterator over the array, for walking through large arrays in batches
+  without building the whole list at once.
+
+  The iterator holds a read transaction on the document until it is closed, so it
+  sees the content as it was when opened. While it is open, changes to the document
+  fail with `{:error, :transaction_busy}`, and it cannot be opened inside
+  `Yex.Doc.transaction/3`. Always close it with `iter_close/1` once done; prefer
+  `stream/2`, which does so automatically.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, [1, 2, 3])
+      iex> {:ok, iter} = Yex.Array.iter_open(array)
+      iex> Yex.Array.iter_next(iter, 2)
+      {:ok, [1.0, 2.0]}
+      iex> Yex.Array.iter_next(iter, 2)
+      {:ok, [3.0]}
+      iex> Yex.Array.iter_next(iter, 2)
+      {:ok, []}
+      iex> Yex.Array.iter_close(iter)
+      :ok
+  """
+  @spec iter_open(t) :: {:ok, Yex.Array.Iterator.t()} | {:error, term()}
+  def iter_open(%__MODULE__{doc: doc} = array) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_iter_open(array)
+    end
+  end
+
+  @doc """
+  Returns up to `batch_size` values following the previous batch, and advances the iterator.
+  An empty list means the end of the array was reached.
+  Returns `{:error, :iterator_closed}` once the iterator has been closed.
+  """
+  @spec iter_next(Yex.Array.Iterator.t(), pos_integer()) ::
+          {:ok, [value()]} | {:error, :iterator_closed}
+  def iter_next(%Yex.Array.Iterator{doc: doc} = iter, batch_size)
+      when is_integer(batch_size) and batch_size > 0 do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_iter_next(iter, batch_size)
+    end
+  end
+
+  @doc """
+  Closes the iterator, releasing its read transaction. Closing twice is a no-op.
+
+  An iterator that is never closed only releases its transaction when garbage
+  collected, and blocks changes to the document until then.
+  """
+  @spec iter_close(Yex.Array.Iterator.t()) :: :ok
+  def iter_close(%Yex.Array.Iterator{doc: doc} = iter) do
+    Doc.run_in_worker_process doc do
+      Yex.Nif.array_iter_close(iter)
+    end
+  end
+
+  @doc """
+  Returns a lazy stream over the array values, fetched `batch_size` at a time
+  through `iter_open/1`. The iterator is closed when the stream halts, including
+  when it is only partially consumed. Until then, changes to the document fail with
+  `{:error, :transaction_busy}`, so consume the stream before writing to the document.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.insert_list(array, 0, [1, 2, 3])
+      iex> Yex.Array.stream(array, 2) |> Enum.to_list()
+      [1.0, 2.0, 3.0]
+  """
+  @spec stream(t, pos_integer()) :: Enumerable.t()
+  def stream(%__MODULE__{} = array, batch_size \\ 1000) do
+    Stream.resource(
+      fn ->
+        {:ok, iter} = iter_open(array)
+        iter
+      end,
+      fn iter ->
+        case iter_next(iter, batch_size) do
+          {:ok, []} -> {:halt, iter}
+          {:ok, values} -> {values, iter}
+        end
+      end,
+      &iter_close/1
+    )
+  end
+
   def member?(array, val) do
     val = Yex.normalize(val)
     Enum.member?(to_list(array), val)
@@ -447,6 +768,21 @@ defmodule Yex.Array do
   end
 end
 
+defmodule Yex.Array.Iterator do
+  @moduledoc """
+  An open iterator over a `Yex.Array`, see `Yex.Array.iter_open/1`.
+  """
+  defstruct [
+    :reference,
+    :doc
+  ]
+
+  @type t :: %__MODULE__{
+          reference: reference(),
+          doc: Yex.Doc.t()
+        }
+end
+
 defmodule Yex.ArrayPrelim do
   @moduledoc """
   A preliminary array. It can be used to early initialize the contents of a Array.
@@ -1,3 +1,10 @@
 defmodule Yex.DeletedSharedTypeError do
   defexception message: "Shared type has been deleted"
 end
+
+defmodule Yex.KeyError do
+  @moduledoc """
+  Raised when a required key is missing from a `Yex.Map`. `key` holds the missing key.
+  """
+  defexception [:key, message: "key not found"]
+end
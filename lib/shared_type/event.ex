@@ -2,19 +2,38 @@ defmodule Yex.ArrayEvent do
   @moduledoc """
   Event when Array type changes
 
+  `old_length` and `new_length` are the lengths of the target before and after the transaction.
+
+  When observed with `deliver_snapshot: true`, `change` is nil and `snapshot` holds the
+  whole array content after the change instead.
+
+  When observed with `deleted_content: true`, each `%{delete: count}` entry of `change` also
+  holds the removed values under `:deleted`.
+
   @see Yex.SharedType.observe/1
   @see Yex.SharedType.observe_deep/1
   """
   defstruct [
     :path,
     :target,
-    :change
+    :change,
+    :old_length,
+    :new_length,
+    :snapshot
   ]
 
   @type t :: %__MODULE__{
           path: list(number() | String.t()),
           target: Yex.Array.t(),
-          change: %{insert: list()} | %{delete: number()} | %{}
+          change:
+            %{insert: list()}
+            | %{delete: number()}
+            | %{delete: number(), deleted: list()}
+            | %{}
+            | nil,
+          old_length: non_neg_integer(),
+          new_length: non_neg_integer(),
+          snapshot: list() | nil
         }
 end
 
@@ -34,18 +34,24 @@ defmodule Yex.Map do
   ## Parameters
     * `map` - The map to modify
     * `key` - The key to set
-    * `content` - The value to associate with the key
+    * `content` - The value to associate with the key. Atoms, including atom keys of nested maps,
+      are stored as strings.
+    * `opts` - Options:
+      * `:origin` - origin of the transaction opened for this change, as with `Yex.Doc.transaction/3`.
+        Ignored inside an explicit transaction, whose origin applies instead.
 
   ## Examples
       iex> doc = Yex.Doc.new()
       iex> map = Yex.Doc.get_map(doc, "map")
       iex> Yex.Map.set(map, "plane", ["Hello", "World"])
       :ok
+      iex> Yex.Map.set(map, "plane", "Hello", origin: "user")
+      :ok
   """
-  @spec set(t, binary(), Yex.input_type()) :: :ok
-  def set(%__MODULE__{doc: doc} = map, key, content) when is_binary(key) do
+  @spec set(t, binary(), Yex.input_type(), keyword()) :: :ok
+  def set(%__MODULE__{doc: doc} = map, key, content, opts \\ []) when is_binary(key) do
     Doc.run_in_worker_process(doc,
-      do: Yex.Nif.map_set(map, cur_txn(map), key, content)
+      do: Yex.Nif.map_set(map, cur_txn(map), key, content, Keyword.get(opts, :origin))
     )
   end
 
@@ -68,7 +74,7 @@ defmodule Yex.Map do
   @spec set_and_get(t, binary(), Yex.input_type()) :: value()
   def set_and_get(%__MODULE__{doc: doc} = map, key, content) when is_binary(key) do
     Doc.run_in_worker_process doc do
-      :ok = Yex.Nif.map_set(map, cur_txn(map), key, content)
+      :ok = Yex.Nif.map_set(map, cur_txn(map), key, content, nil)
 
       case Yex.Nif.map_get(map, cur_txn(map), key) do
         {:ok, value} -> value
@@ -77,6 +83,65 @@ defmodule Yex.Map do
     end
   end
 
+  @doc """
+  Sets `key` to `new` only if its current value equals `expected`, for optimistic concurrency.
+  The check and the write happen in one transaction, so no other writer can interleave.
+
+  Values are compared by their JSON content, so nested shared types compare by content and
+  integers match their float counterparts. A missing key compares equal to `nil`, which allows
+  setting a key only if it is absent.
+
+  Returns `{:ok, :set}` if the value was written, `{:ok, :unchanged}` otherwise.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.compare_and_set(map, "version", nil, 1)
+      {:ok, :set}
+      iex> Yex.Map.compare_and_set(map, "version", 2, 3)
+      {:ok, :unchanged}
+      iex> Yex.Map.compare_and_set(map, "version", 1, 2)
+      {:ok, :set}
+      iex> Yex.Map.fetch(map, "version")
+      {:ok, 2.0}
+  """
+  @spec compare_and_set(t, binary(), Yex.any_type(), Yex.input_type()) ::
+          {:ok, :set | :unchanged}
+  def compare_and_set(%__MODULE__{doc: doc} = map, key, expected, new) when is_binary(key) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_compare_and_set(map, cur_txn(map), key, expected, new)
+    )
+  end
+
+  @doc """
+  Adds `delta` to the number stored under `key` and returns the new value.
+  A missing key starts from 0.
+
+  The read and the write happen in one transaction, so increments issued locally never
+  interleave. This is *not* a conflict-free counter: concurrent increments from different
+  peers still resolve as last writer wins, and one of them is lost.
+
+  Returns `{:error, :not_a_number}` if the key holds something other than a number.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.increment(map, "count")
+      {:ok, 1.0}
+      iex> Yex.Map.increment(map, "count", 5)
+      {:ok, 6.0}
+      iex> Yex.Map.set(map, "name", "counter")
+      iex> Yex.Map.increment(map, "name")
+      {:error, :not_a_number}
+  """
+  @spec increment(t, binary(), integer()) :: {:ok, number()} | {:error, :not_a_number}
+  def increment(%__MODULE__{doc: doc} = map, key, delta \\ 1)
+      when is_binary(key) and is_integer(delta) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_increment(map, cur_txn(map), key, delta)
+    )
+  end
+
   @doc """
   Deletes a key from the map.
   Returns :ok on success, :error on failure.
@@ -99,6 +164,50 @@ defmodule Yex.Map do
     )
   end
 
+  @doc """
+  Removes a key from the map and returns the value it held, in one transaction.
+  Returns `{:ok, value}` when the key was present, `:error` otherwise.
+
+  A nested shared type is removed along with its key, so the returned reference can no
+  longer be read; use `to_json/1` on it beforehand if its content is needed.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "job", "resize")
+      iex> Yex.Map.pop(map, "job")
+      {:ok, "resize"}
+      iex> Yex.Map.pop(map, "job")
+      :error
+  """
+  @spec pop(t, binary()) :: {:ok, value()} | :error
+  def pop(%__MODULE__{doc: doc} = map, key) when is_binary(key) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_pop(map, cur_txn(map), key)
+    )
+  end
+
+  @doc """
+  Removes every key from the map in a single transaction, so observers receive one
+  `Yex.MapEvent` listing all removed keys.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "a", 1)
+      iex> Yex.Map.set(map, "b", 2)
+      iex> Yex.Map.clear(map)
+      :ok
+      iex> Yex.Map.size(map)
+      0
+  """
+  @spec clear(t) :: :ok
+  def clear(%__MODULE__{doc: doc} = map) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_clear(map, cur_txn(map))
+    )
+  end
+
   @doc """
   get a key from the map.
     ## Examples
@@ -109,15 +218,21 @@ defmodule Yex.Map do
       ["Hello", "World"]
       iex> Yex.Map.get(map, "not_found")
       nil
+      iex> Yex.Map.get(map, :plane, :none)
+      :none
   """
-  @spec get(t, binary(), default :: value()) :: value()
-  def get(%__MODULE__{} = map, key, default \\ nil) do
-    case fetch(map, key) do
-      {:ok, value} -> value
-      :error -> default
-    end
+  @spec get(t, term(), default :: value()) :: value()
+  def get(map, key, default \\ nil)
+
+  def get(%__MODULE__{doc: doc} = map, key, default) when is_binary(key) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_get_or(map, cur_txn(map), key, default)
+    )
   end
 
+  # keys are always strings, so any other key is missing
+  def get(%__MODULE__{}, _key, default), do: default
+
   @doc """
   Gets a value by key from the map, or lazily evaluates the given function if the key is not found.
   This is useful when the default value is expensive to compute and should only be evaluated when needed.
@@ -183,21 +298,20 @@ defmodule Yex.Map do
   end
 
   @doc """
-  Similar to fetch/2 but raises ArgumentError if the key is not found.
+  Similar to fetch/2 but raises `Yex.KeyError` if the key is not found.
 
   ## Parameters
     * `map` - The map to query
     * `key` - The key to look up
 
   ## Raises
-    * ArgumentError - If the key is not found
+    * `Yex.KeyError` - If the key is not found, carrying the missing key
   """
   @spec fetch!(t, binary()) :: value()
-  def fetch!(%__MODULE__{} = map, key) when is_binary(key) do
-    case fetch(map, key) do
-      {:ok, value} -> value
-      :error -> raise ArgumentError, "Key not found"
-    end
+  def fetch!(%__MODULE__{doc: doc} = map, key) when is_binary(key) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_fetch(map, cur_txn(map), key)
+    )
   end
 
   @doc """
@@ -215,6 +329,30 @@ defmodule Yex.Map do
     )
   end
 
+  @doc """
+  Returns the id of the value stored under `key` as `{client_id, clock}`.
+
+  Setting the key again replaces the value and yields a new id, while mutating a
+  nested shared type in place keeps it. Returns `:error` if the key is absent.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "key", "value")
+      iex> {:ok, id} = Yex.Map.value_id(map, "key")
+      iex> Yex.Map.set(map, "key", "other")
+      iex> Yex.Map.value_id(map, "key") != {:ok, id}
+      true
+      iex> Yex.Map.value_id(map, "not_found")
+      :error
+  """
+  @spec value_id(t, binary()) :: {:ok, {integer(), integer()}} | :error
+  def value_id(%__MODULE__{doc: doc} = map, key) when is_binary(key) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_value_id(map, cur_txn(map), key)
+    )
+  end
+
   @doc """
   Converts the map to a standard Elixir map.
   This is useful when you need to work with the map's contents in a non-collaborative context.
@@ -313,6 +451,71 @@ defmodule Yex.Map do
     )
   end
 
+  @doc """
+  Like `to_json/1`, but returns a list of `{key, value}` pairs sorted by key, so the output is
+  reproducible, for example in snapshots. Only the top level is ordered.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "b", "World")
+      iex> Yex.Map.set(map, "a", "Hello")
+      iex> Yex.Map.to_json_ordered(map)
+      [{"a", "Hello"}, {"b", "World"}]
+  """
+  @spec to_json_ordered(t) :: [{String.t(), term()}]
+  def to_json_ordered(%__MODULE__{doc: doc} = map) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_to_json_ordered(map, cur_txn(map))
+    )
+  end
+
+  @doc """
+  Returns a hash of the map content, as seen by `to_json/1`.
+
+  Keys are hashed in sorted order, so maps with equal content give equal hashes
+  regardless of the order entries were set in. Nested arrays stay order sensitive.
+  The hash is only stable within the same build of the library, so it should not be persisted.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "key", "value")
+      iex> hash = Yex.Map.content_hash(map)
+      iex> Yex.Map.set(map, "key", "other")
+      iex> Yex.Map.content_hash(map) == hash
+      false
+  """
+  @spec content_hash(t) :: non_neg_integer()
+  def content_hash(%__MODULE__{doc: doc} = map) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_content_hash(map, cur_txn(map))
+    )
+  end
+
+  @doc """
+  Checks whether the map content, as seen by `to_json/1`, structurally equals `expected`.
+
+  Nested maps and arrays are compared as a whole, and numbers by value, so integers
+  match their float counterparts. Raises `ArgumentError` if `expected` cannot be
+  represented as JSON-like content.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "nested", Yex.MapPrelim.from(%{"list" => [1, 2], "flag" => true}))
+      iex> Yex.Map.deep_equal?(map, %{"nested" => %{"list" => [1, 2], "flag" => true}})
+      true
+      iex> Yex.Map.deep_equal?(map, %{"nested" => %{"list" => [2, 1], "flag" => true}})
+      false
+  """
+  @spec deep_equal?(t, map()) :: boolean()
+  def deep_equal?(%__MODULE__{doc: doc} = map, expected) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.map_deep_equal(map, cur_txn(map), expected)
+    )
+  end
+
   @doc """
   ### ⚠️ Experimental
   Creates a weak link to a value in the map by key.
@@ -20,6 +20,26 @@ defmodule Yex.SharedType do
 
   ## Options
     * `:metadata` - provides metadata to be attached to this observe.
+    * `:json_values` - for maps, delivers `old_value`/`new_value` as JSON encoded binaries
+      instead of decoded terms. This is cheaper for processes that forward the changes as JSON
+      anyway, at the cost of values being opaque: nested shared types arrive as their JSON content
+      rather than as references. Defaults to `false`.
+    * `:deliver_snapshot` - for arrays, delivers the whole array content after each change in the
+      event's `snapshot` field instead of the change delta. Simpler to consume for small arrays,
+      but every event carries the full list. Defaults to `false`.
+    * `:deleted_content` - for arrays, adds the removed values to each `delete` entry of the
+      change as `deleted: values`, read from the deleted items before they are garbage collected.
+      Every removed value is copied into the event, so deleting large ranges produces equally
+      large messages. Defaults to `false`.
+    * `:throttle` - for maps, a window in milliseconds over which changes are coalesced. The first
+      change of a window schedules a single event at its end, holding the net change of each key:
+      its value before the window and its latest value. Keys that end up unchanged are left out,
+      and the origin is that of the last transaction. Protects the mailbox of observers of keys
+      updated at a high rate. Pending changes are dropped on unobserve. Defaults to `nil`, which
+      delivers every change.
+    * `:ignore_origins` - a list of transaction origins whose changes are not delivered, for
+      instance the origin used when applying remote updates, to avoid echoing them back.
+      Defaults to `[]`.
 
   ## Returns
     * A reference that can be used to unsubscribe the observer
@@ -49,7 +69,41 @@ defmodule Yex.SharedType do
             cur_txn(shared_type),
             notify_pid,
             ref,
-            Keyword.get(opt, :metadata)
+            Keyword.get(opt, :metadata),
+            observe_options(opt)
+          )
+      )
+
+    Yex.Subscription.register(sub, ref)
+  end
+
+  @doc """
+  Observes several shared types of the same document under a single subscription.
+
+  Accepts the same options as `observe/2`, which apply to every shared type. Events are
+  delivered in the same shape, sharing the returned reference; the `target` of each event
+  tells which shared type changed. `unobserve/1` removes all the observers at once.
+  """
+  @spec observe_many([t, ...], keyword()) :: reference()
+  def observe_many([%{doc: doc} | _] = shared_types, opt \\ []) do
+    unless Enum.all?(shared_types, &(&1.doc == doc)) do
+      raise ArgumentError, "all shared types must belong to the same document"
+    end
+
+    ref = make_ref()
+    notify_pid = self()
+
+    sub =
+      Doc.run_in_worker_process(doc,
+        do:
+          Yex.Nif.shared_type_observe_many(
+            doc,
+            shared_types,
+            cur_txn(hd(shared_types)),
+            notify_pid,
+            ref,
+            Keyword.get(opt, :metadata),
+            observe_options(opt)
           )
       )
 
@@ -80,6 +134,10 @@ defmodule Yex.SharedType do
 
   ## Options
     * `:metadata` - provides metadata to be attached to this observe.
+    * `:ignore_origins` - a list of transaction origins whose changes are not delivered.
+      Defaults to `[]`.
+
+  The other options of `observe/2` are accepted and have no effect on deep events.
 
   ## Returns
     * A reference that can be used to unsubscribe the deep observer
@@ -97,7 +155,8 @@ defmodule Yex.SharedType do
             cur_txn(shared_type),
             notify_pid,
             ref,
-            Keyword.get(opt, :metadata)
+            Keyword.get(opt, :metadata),
+            observe_options(opt)
           )
       )
 
@@ -113,9 +172,56 @@ defmodule Yex.SharedType do
     unsubscribe(observe_ref)
   end
 
+  @doc """
+  Returns true if both handles refer to the same shared type of the same document,
+  even when they were obtained separately. Handles of different kinds are never equal.
+
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "list", Yex.ArrayPrelim.from([1]))
+      iex> Yex.SharedType.equal?(Yex.Map.fetch!(map, "list"), Yex.Map.fetch!(map, "list"))
+      true
+      iex> Yex.SharedType.equal?(map, Yex.Doc.get_array(doc, "map2"))
+      false
+  """
+  @spec equal?(t, t) :: boolean()
+  def equal?(a, b) do
+    Yex.Nif.shared_type_equal(a, b)
+  end
+
+  @doc """
+  Returns the location of the shared type relative to its root type, using the same
+  segments as event paths: map keys and array indexes. Root types return `[]`.
+
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "list", Yex.ArrayPrelim.from([Yex.TextPrelim.from("a")]))
+      iex> text = map |> Yex.Map.fetch!("list") |> Yex.Array.fetch!(0)
+      iex> Yex.SharedType.path(text)
+      ["list", 0]
+      iex> Yex.SharedType.path(map)
+      []
+  """
+  @spec path(t) :: [binary() | non_neg_integer()]
+  def path(%{doc: doc} = shared_type) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.shared_type_path(shared_type, cur_txn(shared_type))
+    )
+  end
+
+  defp observe_options(opt) do
+    %{
+      json_values: Keyword.get(opt, :json_values, false),
+      deliver_snapshot: Keyword.get(opt, :deliver_snapshot, false),
+      deleted_content: Keyword.get(opt, :deleted_content, false),
+      throttle_ms: Keyword.get(opt, :throttle),
+      ignore_origins: Keyword.get(opt, :ignore_origins, [])
+    }
+  end
+
   @doc false
   # Gets the current transaction reference from the process dictionary
-  defp cur_txn(%{doc: doc_ref}) do
+  defp cur_txn(%{doc: %Yex.Doc{reference: doc_ref}}) do
     Process.get(doc_ref, nil)
   end
 
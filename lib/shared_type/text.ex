@@ -21,6 +21,10 @@ defmodule Yex.Text do
           [%{:insert => binary(), optional(:attributes) => map()}]
           | [%{delete: integer()}]
           | [%{:retain => integer(), optional(:attributes) => map()}]
+  @type op ::
+          {:insert, integer(), binary()}
+          | {:delete, integer(), non_neg_integer()}
+          | {:format, integer(), non_neg_integer(), map()}
   @type t :: %__MODULE__{
           doc: Yex.Doc.t(),
           reference: reference()
@@ -38,7 +42,7 @@ defmodule Yex.Text do
   @spec insert(t, integer(), binary()) :: :ok | :error
   def insert(%__MODULE__{doc: doc} = text, index, content) do
     Doc.run_in_worker_process(doc,
-      do: Yex.Nif.text_insert(text, cur_txn(text), index, content)
+      do: Yex.Nif.text_insert(text, cur_txn(text), index, content, nil)
     )
   end
 
@@ -53,9 +57,103 @@ defmodule Yex.Text do
     * `attr` - A map of formatting attributes to apply (e.g. %{"bold" => true})
   """
   @spec insert(t, integer(), binary(), map()) :: :ok | :error
-  def insert(%__MODULE__{doc: doc} = text, index, content, attr) do
+  def insert(%__MODULE__{} = text, index, content, attr) do
+    insert(text, index, content, attr, [])
+  end
+
+  @doc """
+  Inserts text content with formatting attributes at the specified index, with options.
+  Returns :ok on success, :error on failure.
+
+  ## Parameters
+    * `text` - The text object to modify
+    * `index` - The position to insert at (0-based)
+    * `content` - The text content to insert
+    * `attr` - A map of formatting attributes to apply, empty for plain text
+    * `opts` - A keyword list of options:
+      * `:origin` - origin of the transaction opened for this change, as with
+        `Yex.Doc.transaction/3`. Ignored inside an explicit transaction, whose origin applies instead.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello", %{}, origin: "user")
+      :ok
+      iex> Yex.Text.insert(text, 5, " World", %{"bold" => true}, origin: "user")
+      :ok
+      iex> Yex.Text.to_string(text)
+      "Hello World"
+  """
+  @spec insert(t, integer(), binary(), map(), keyword()) :: :ok | :error
+  def insert(%__MODULE__{doc: doc} = text, index, content, attr, opts)
+      when is_map(attr) and is_list(opts) do
+    origin = Keyword.get(opts, :origin)
+
+    Doc.run_in_worker_process doc do
+      Yex.Nif.text_insert_with_attributes(text, cur_txn(text), index, content, attr, origin)
+    end
+  end
+
+  @doc """
+  Inserts an embedded object, such as an image or a mention, at the given index.
+  The embed takes a single position in the text and comes back from `to_delta/1` as an
+  `insert` whose value is the embed itself rather than a string.
+
+  ## Parameters
+    * `text` - The text object to modify
+    * `index` - The position at which to insert the embed
+    * `embed` - The embedded value, usually a map
+    * `attr` - An optional map of formatting attributes to apply
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "ab")
+      iex> Yex.Text.insert_embed(text, 1, %{"image" => "a.png"})
+      :ok
+      iex> Yex.Text.to_delta(text)
+      [%{insert: "a"}, %{insert: %{"image" => "a.png"}}, %{insert: "b"}]
+  """
+  @spec insert_embed(t, integer(), Yex.any_type(), map()) :: :ok | :error
+  def insert_embed(%__MODULE__{doc: doc} = text, index, embed, attr \\ %{})
+      when is_integer(index) and is_map(attr) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_insert_embed(text, cur_txn(text), index, embed, attr)
+    )
+  end
+
+  @doc """
+  Inserts text content at the current position of a sticky index.
+  The position is resolved and the content inserted in a single step, so no
+  concurrent edit can shift the position in between.
+  Returns :error if the sticky index can no longer be resolved.
+
+  ## Parameters
+    * `text` - The text object to modify
+    * `sticky_index` - A `Yex.StickyIndex` created on this text
+    * `content` - The text content to insert
+    * `attr` - An optional map of formatting attributes to apply
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "ac")
+      iex> cursor = Yex.StickyIndex.new(text, 1, :after)
+      iex> Yex.Text.insert(text, 0, "_")
+      iex> Yex.Text.insert_at_sticky(text, cursor, "b")
+      :ok
+      iex> Yex.Text.to_string(text)
+      "_abc"
+  """
+  @spec insert_at_sticky(t, Yex.StickyIndex.t(), binary(), map() | nil) :: :ok | :error
+  def insert_at_sticky(
+        %__MODULE__{doc: doc} = text,
+        %Yex.StickyIndex{} = sticky_index,
+        content,
+        attr \\ nil
+      ) do
     Doc.run_in_worker_process(doc,
-      do: Yex.Nif.text_insert_with_attributes(text, cur_txn(text), index, content, attr)
+      do: Yex.Nif.text_insert_at_sticky(text, cur_txn(text), sticky_index, content, attr)
     )
   end
 
@@ -93,6 +191,30 @@ defmodule Yex.Text do
     )
   end
 
+  @doc """
+  Applies a batch of edits in order within a single transaction, so observers receive a single
+  event for the whole batch.
+
+  Each op behaves like the matching call: `{:insert, index, content}` like `insert/3`,
+  `{:delete, index, length}` like `delete/3` and `{:format, index, length, attributes}` like
+  `format/4`. Indexes refer to the text as left by the previous ops.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> ops = [{:insert, 0, "Hello"}, {:insert, 5, " World"}, {:delete, 0, 1}]
+      iex> Yex.Text.apply_ops(text, ops)
+      :ok
+      iex> Yex.Text.to_string(text)
+      "ello World"
+  """
+  @spec apply_ops(t, [op]) :: :ok | :error
+  def apply_ops(%__MODULE__{doc: doc} = text, ops) when is_list(ops) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_apply_ops(text, cur_txn(text), ops)
+    )
+  end
+
   @doc """
   Returns the text content as a string.
 
@@ -106,6 +228,55 @@ defmodule Yex.Text do
     )
   end
 
+  @doc """
+  Returns the text content as a string, with each embed replaced by `placeholder`.
+
+  Embeds count as a single position in the text, so a placeholder that also takes a single
+  position, such as the object replacement character (U+FFFC) with the default UTF-16 offsets,
+  keeps offsets in the string aligned with the offsets used by `insert/3`, `delete/3` and `format/4`.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.apply_delta(text, [%{insert: "a"}, %{insert: %{"image" => "b.png"}}, %{insert: "c"}])
+      iex> Yex.Text.to_string_with_embeds(text, "\uFFFC")
+      "a\uFFFCc"
+  """
+  @spec to_string_with_embeds(t, binary()) :: binary()
+  def to_string_with_embeds(%__MODULE__{doc: doc} = text, placeholder)
+      when is_binary(placeholder) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_to_string_with_embeds(text, cur_txn(text), placeholder)
+    )
+  end
+
+  @doc """
+  Returns the `{index, length}` ranges of the text formatted with `attr_key` set to
+  `attr_value`, in document order. Adjacent runs that match are merged into one range, even
+  when their other attributes differ. Returns an empty list when nothing matches.
+
+  Offsets and lengths follow the document's offset kind, like `insert/3` and `delete/3`, and
+  an embed counts as one.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello World")
+      iex> Yex.Text.format(text, 0, 5, %{"bold" => true})
+      iex> Yex.Text.format(text, 6, 5, %{"bold" => true})
+      iex> Yex.Text.find_formatted(text, "bold", true)
+      [{0, 5}, {6, 5}]
+      iex> Yex.Text.find_formatted(text, "italic", true)
+      []
+  """
+  @spec find_formatted(t, binary(), Yex.any_type()) :: [{non_neg_integer(), non_neg_integer()}]
+  def find_formatted(%__MODULE__{doc: doc} = text, attr_key, attr_value)
+      when is_binary(attr_key) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_find_formatted(text, cur_txn(text), attr_key, attr_value)
+    )
+  end
+
   @doc """
   Returns the length of the text content in characters.
 
@@ -119,6 +290,125 @@ defmodule Yex.Text do
     )
   end
 
+  @doc """
+  Returns the character at `index` as `{:ok, char}`, or `:error` when the index is out of
+  range or points at an embed. Negative indexes count from the end.
+
+  The index follows the document's offset kind, like `insert/3`; an index inside a
+  multi-unit character returns that whole character.
+
+  The character is a single codepoint, not a grapheme: a letter followed by a combining
+  accent, or an emoji sequence, spans several indexes. Use `substring/3` and
+  `String.graphemes/1` to read whole graphemes.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello")
+      iex> Yex.Text.char_at(text, 1)
+      {:ok, "e"}
+      iex> Yex.Text.char_at(text, 10)
+      :error
+  """
+  @spec char_at(t, integer()) :: {:ok, binary()} | :error
+  def char_at(%__MODULE__{doc: doc} = text, index) when is_integer(index) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_char_at(text, cur_txn(text), index)
+    )
+  end
+
+  @doc """
+  Translates a codepoint index into an index in the document's offset kind (bytes or UTF-16
+  code units), as expected by `insert/3`, `delete/3` and the other index based functions.
+
+  Elixir strings are naturally indexed by codepoint, which does not match either offset kind
+  as soon as the text holds non-ASCII characters. Note that `String.length/1` counts
+  graphemes, use `String.codepoints/1` to count codepoints. Embeds count as one codepoint.
+  Returns `{:error, :out_of_bounds}` when `char_index` is past the end of the text.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "héllo")
+      iex> Yex.Text.offset_of_char(text, 2)
+      {:ok, 3}
+      iex> Yex.Text.offset_of_char(text, 6)
+      {:error, :out_of_bounds}
+  """
+  @spec offset_of_char(t, non_neg_integer()) :: {:ok, non_neg_integer()} | {:error, term()}
+  def offset_of_char(%__MODULE__{doc: doc} = text, char_index)
+      when is_integer(char_index) and char_index >= 0 do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_offset_of_char(text, cur_txn(text), char_index)
+    )
+  end
+
+  @doc """
+  Returns up to `length` of text starting at `index`, without reading the whole string.
+  The range is clamped to the text, and embeds inside it are left out.
+
+  `index` and `length` follow the document's offset kind, like `delete/3`; characters only
+  partly covered by the range are included whole.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello World")
+      iex> Yex.Text.substring(text, 6, 5)
+      "World"
+      iex> Yex.Text.substring(text, 6, 100)
+      "World"
+  """
+  @spec substring(t, integer(), non_neg_integer()) :: binary()
+  def substring(%__MODULE__{doc: doc} = text, index, length)
+      when is_integer(index) and is_integer(length) and length >= 0 do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_substring(text, cur_txn(text), index, length)
+    )
+  end
+
+  @doc """
+  Returns the number of words in the text.
+
+  A word is a maximal run of characters that are not Unicode whitespace, so punctuation
+  attached to a word is part of it, as with `wc -w`. Embeds are not counted.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello, wide\\u3000world!\\n")
+      iex> Yex.Text.word_count(text)
+      3
+  """
+  @spec word_count(t) :: non_neg_integer()
+  def word_count(%__MODULE__{doc: doc} = text) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_word_count(text, cur_txn(text))
+    )
+  end
+
+  @doc """
+  Returns the number of lines in the text.
+
+  Lines are separated by `\\n`, so a trailing newline opens an empty last line, the way
+  editors display it. An empty text has no lines.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.line_count(text)
+      0
+      iex> Yex.Text.insert(text, 0, "Hello\\nWorld\\n")
+      iex> Yex.Text.line_count(text)
+      3
+  """
+  @spec line_count(t) :: non_neg_integer()
+  def line_count(%__MODULE__{doc: doc} = text) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_line_count(text, cur_txn(text))
+    )
+  end
+
   @doc """
   Converts the text object to its preliminary representation.
   This is useful when you need to serialize or transfer the text content and formatting.
@@ -184,6 +474,55 @@ defmodule Yex.Text do
     )
   end
 
+  @doc """
+  Like `apply_delta/2`, but validates the delta first.
+
+  Returns `{:error, :delta_out_of_range}`, leaving the text untouched, when the retains and
+  deletes of the delta reach past the end of the text. `apply_delta/2` would clamp them instead.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "123")
+      iex> Yex.Text.apply_delta_checked(text, [%{"retain" => 2}, %{"delete" => 2}])
+      {:error, :delta_out_of_range}
+      iex> Yex.Text.apply_delta_checked(text, [%{"retain" => 2}, %{"delete" => 1}])
+      :ok
+      iex> Yex.Text.to_string(text)
+      "12"
+  """
+  @spec apply_delta_checked(t, delta) :: :ok | {:error, term()}
+  def apply_delta_checked(%__MODULE__{doc: doc} = text, delta) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_apply_delta_checked(text, cur_txn(text), delta)
+    )
+  end
+
+  @doc """
+  Applies a Quill Delta given as JSON, either `{"ops": [...]}` or the bare list of ops.
+
+  The JSON is decoded natively, so embeds and attributes keep the shape Quill sends
+  without a translation step. Embeds are inserted as maps, the way `to_delta/1` reports them.
+  Returns `{:error, :invalid_quill_delta}` if the JSON is not a valid delta.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello")
+      iex> Yex.Text.apply_quill_delta(text, ~s({"ops":[{"retain":5},{"insert":"!","attributes":{"bold":true}}]}))
+      :ok
+      iex> Yex.Text.to_delta(text)
+      [%{insert: "Hello"}, %{insert: "!", attributes: %{"bold" => true}}]
+      iex> Yex.Text.apply_quill_delta(text, ~s({"ops":[{"retain":"5"}]}))
+      {:error, :invalid_quill_delta}
+  """
+  @spec apply_quill_delta(t, binary()) :: :ok | {:error, :invalid_quill_delta}
+  def apply_quill_delta(%__MODULE__{doc: doc} = text, delta_json) when is_binary(delta_json) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_apply_quill_delta(text, cur_txn(text), delta_json)
+    )
+  end
+
   @doc """
   Transforms this type to a Quill Delta
 
@@ -202,6 +541,49 @@ defmodule Yex.Text do
     )
   end
 
+  @doc """
+  Transforms this type to a Quill Delta as it was at `snapshot`, annotated against
+  `prev_snapshot`. Content inserted after `prev_snapshot` carries a
+  `"ychange" => %{"kind" => "added"}` attribute and content deleted since carries
+  `"ychange" => %{"kind" => "removed"}`, so editors can render tracked changes.
+
+  Snapshots are taken with `Yex.Doc.snapshot/1`. Deleted content is only kept when the
+  document was created with `skip_gc: true`.
+  """
+  @spec to_delta(t, binary(), binary() | nil) :: delta()
+  def to_delta(%__MODULE__{doc: doc} = text, snapshot, prev_snapshot \\ nil)
+      when is_binary(snapshot) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_to_delta_with_snapshots(text, cur_txn(text), snapshot, prev_snapshot)
+    )
+  end
+
+  @doc """
+  Reports how fragmented the formatting runs of the text are.
+
+  Returns `{runs, coalesced_runs}` where `runs` is the number of entries `to_delta/1` returns
+  and `coalesced_runs` the number left after merging adjacent string runs with equal attributes.
+  Consecutive inserts with identical attributes are merged automatically when a transaction is
+  committed, so both numbers are normally equal. They differ when formatting markers that no
+  longer change anything split a run, e.g. after formatting a range with the attributes it
+  already has, or after concurrent formatting of overlapping ranges.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello", %{"bold" => true})
+      iex> Yex.Text.insert(text, 5, " World", %{"bold" => true})
+      iex> Yex.Text.insert(text, 11, "!")
+      iex> Yex.Text.run_stats(text)
+      {2, 2}
+  """
+  @spec run_stats(t) :: {non_neg_integer(), non_neg_integer()}
+  def run_stats(%__MODULE__{doc: doc} = text) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.text_run_stats(text, cur_txn(text))
+    )
+  end
+
   defp cur_txn(%{doc: %Yex.Doc{reference: doc_ref}}) do
     Process.get(doc_ref, nil)
   end
@@ -66,6 +66,28 @@ defmodule Yex.WeakLink do
     )
   end
 
+  @doc """
+  Describes where the weak link points to without dereferencing it.
+
+  `:path` starts with the root type name, followed by the keys and indexes leading to the
+  linked collection. `:key` is set for map links, and `:range` holds the half-open
+  `{start, end}` element range for array quotes.
+  Returns `:error` when the linked content can no longer be found.
+  """
+  @spec source_path(t()) ::
+          {:ok,
+           %{
+             path: [binary() | non_neg_integer()],
+             key: binary() | nil,
+             range: {non_neg_integer(), non_neg_integer()} | nil
+           }}
+          | :error
+  def source_path(%Yex.WeakLink{doc: doc} = weak) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.weak_source_path(weak, cur_txn(weak))
+    )
+  end
+
   defimpl Yex.Output do
     def as_prelim(weak_link) do
       Yex.WeakLink.as_prelim(weak_link)
@@ -5,8 +5,6 @@ defmodule Yex.XmlElement do
 
   """
 
-  alias Yex.Xml
-
   defstruct [
     :doc,
     :reference
@@ -26,25 +24,19 @@ defmodule Yex.XmlElement do
   Returns nil if the element has no children.
   """
   @spec first_child(t) :: Yex.XmlElement.t() | Yex.XmlText.t() | nil
-  def first_child(%__MODULE__{} = xml_element) do
-    fetch(xml_element, 0)
-    |> case do
-      {:ok, node} -> node
-      :error -> nil
-    end
+  def first_child(%__MODULE__{doc: doc} = xml_element) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.xml_element_first_child(xml_element, cur_txn(xml_element))
+    )
   end
 
   @doc """
-  Returns a stream of all child nodes of the XML element.
+  Returns all child nodes of the XML element in document order.
   """
   @spec children(t) :: Enumerable.t(Yex.XmlElement.t() | Yex.XmlText.t())
   def children(%__MODULE__{doc: doc} = xml_element) do
     Doc.run_in_worker_process(doc,
-      do:
-        Stream.unfold(first_child(xml_element), fn
-          nil -> nil
-          xml -> {xml, Xml.next_sibling(xml)}
-        end)
+      do: Yex.Nif.xml_element_children(xml_element, cur_txn(xml_element))
     )
   end
 
@@ -271,6 +263,25 @@ defmodule Yex.XmlElement do
     )
   end
 
+  @doc """
+  Adds or updates several attributes at once, within a single transaction.
+  Returns :ok on success, :error on failure.
+
+      iex> doc = Yex.Doc.new()
+      iex> xml = Yex.Doc.get_xml_fragment(doc, "xml")
+      iex> el = Yex.XmlFragment.push_and_get(xml, Yex.XmlElementPrelim.empty("a"))
+      iex> Yex.XmlElement.set_attributes(el, %{"href" => "/home", "title" => "Home"})
+      :ok
+      iex> Yex.XmlElement.get_attributes(el)
+      %{"href" => "/home", "title" => "Home"}
+  """
+  @spec set_attributes(t, %{binary() => binary() | Yex.PrelimType.t()}) :: :ok | :error
+  def set_attributes(%__MODULE__{doc: doc} = xml_element, attrs) when is_map(attrs) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.xml_element_set_attributes(xml_element, cur_txn(xml_element), attrs)
+    )
+  end
+
   @doc """
   Removes the attribute with the specified key.
   Returns :ok on success, :error on failure.
@@ -408,7 +419,7 @@ defmodule Yex.XmlElementPrelim do
 
   @type t :: %__MODULE__{
           tag: String.t(),
-          attributes: %{String.t() => String.t() | Yex.PrelimType.t()},
+          attributes: %{String.t() => Yex.any_type()},
           children: [Yex.XmlElementPrelim.t() | Yex.XmlTextPrelim.t()]
         }
 
@@ -280,6 +280,36 @@ defmodule Yex.XmlFragment do
     )
   end
 
+  @doc """
+  Returns every descendant node of the fragment in document (depth-first, pre-order) order,
+  each paired with its depth. Direct children of the fragment have depth 0.
+
+      iex> alias Yex.{Doc, XmlFragment, XmlElementPrelim, XmlTextPrelim}
+      iex> doc = Doc.new()
+      iex> fragment = Doc.get_xml_fragment(doc, "xml")
+      iex> XmlFragment.push(fragment, XmlElementPrelim.new("p", [XmlTextPrelim.from("hi")]))
+      iex> XmlFragment.push(fragment, XmlTextPrelim.from("tail"))
+      iex> [{0, %Yex.XmlElement{}}, {1, %Yex.XmlText{}}, {0, %Yex.XmlText{}}] =
+      ...>   XmlFragment.tree_walk(fragment)
+  """
+  @spec tree_walk(t) :: [{non_neg_integer(), Yex.XmlElement.t() | Yex.XmlText.t()}]
+  def tree_walk(%__MODULE__{doc: doc} = xml_fragment) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.xml_fragment_tree_walk(xml_fragment, cur_txn(xml_fragment))
+    )
+  end
+
+  @doc """
+  Returns every descendant element of the fragment whose tag is exactly `tag`,
+  in document order. Matching is case-sensitive and text nodes are skipped.
+  """
+  @spec select_by_tag(t, binary()) :: [Yex.XmlElement.t()]
+  def select_by_tag(%__MODULE__{doc: doc} = xml_fragment, tag) when is_binary(tag) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.xml_fragment_select_by_tag(xml_fragment, cur_txn(xml_fragment), tag)
+    )
+  end
+
   @doc false
   # Gets the current transaction reference from the process dictionary for the given document
   defp cur_txn(%{doc: %Yex.Doc{reference: doc_ref}}) do
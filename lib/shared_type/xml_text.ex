@@ -43,6 +43,24 @@ defmodule Yex.XmlText do
     )
   end
 
+  @doc """
+  Inserts text content at the current position of a sticky index, resolving the position
+  and inserting in a single step. See `Yex.Text.insert_at_sticky/4`.
+  Returns :error if the sticky index can no longer be resolved.
+  """
+  @spec insert_at_sticky(t, Yex.StickyIndex.t(), binary(), map() | nil) :: :ok | :error
+  def insert_at_sticky(
+        %__MODULE__{doc: doc} = xml_text,
+        %Yex.StickyIndex{} = sticky_index,
+        content,
+        attr \\ nil
+      ) do
+    Doc.run_in_worker_process(doc,
+      do:
+        Yex.Nif.xml_text_insert_at_sticky(xml_text, cur_txn(xml_text), sticky_index, content, attr)
+    )
+  end
+
   @doc """
   Deletes text content starting at the specified index.
   Supports negative indices for deletion from the end.
@@ -220,7 +238,7 @@ defmodule Yex.XmlTextPrelim do
 
   @type t :: %__MODULE__{
           delta: Yex.Text.delta(),
-          attributes: %{binary() => binary()}
+          attributes: %{binary() => Yex.any_type()}
         }
 
   @doc """
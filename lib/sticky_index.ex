@@ -64,6 +64,23 @@ defmodule Yex.StickyIndex do
     )
   end
 
+  @doc """
+  Creates a sticky index at an offset in the shape returned by `get_offset/1`,
+  for instance to anchor a resolved position in another shared type.
+
+      iex> alias Yex.{StickyIndex, Doc, Text}
+      iex> doc = Doc.new()
+      iex> txt = Doc.get_text(doc, "text")
+      iex> Text.insert(txt, 0, "abc")
+      iex> pos = StickyIndex.at_offset(txt, %{index: 1, assoc: :before})
+      iex> StickyIndex.get_offset(pos)
+      {:ok, %{index: 1, assoc: :before}}
+  """
+  @spec at_offset(shared_type, %{index: integer(), assoc: :before | :after}) :: t
+  def at_offset(shared_type, %{index: index, assoc: assoc}) do
+    new(shared_type, index, assoc)
+  end
+
   @spec get_offset(t) :: {:ok, %{index: integer(), assoc: :before | :after}} | :error
   def get_offset(%__MODULE__{doc: doc} = sticky_index) do
     Doc.run_in_worker_process(doc,
@@ -71,6 +88,60 @@ defmodule Yex.StickyIndex do
     )
   end
 
+  @doc """
+  Compares the current positions of two sticky indices of the same document.
+
+  Indices resolving to the same offset are ordered by association, `:before` first.
+  Returns `:error` if the indices point into different documents or shared types,
+  or if either index can no longer be resolved.
+
+      iex> alias Yex.{StickyIndex, Doc, Text}
+      iex> doc = Doc.new()
+      iex> txt = Doc.get_text(doc, "text")
+      iex> Text.insert(txt, 0, "abc")
+      iex> a = StickyIndex.new(txt, 1, :after)
+      iex> b = StickyIndex.new(txt, 2, :after)
+      iex> StickyIndex.compare(a, b)
+      :lt
+      iex> StickyIndex.compare(b, a)
+      :gt
+  """
+  @spec compare(t, t) :: :lt | :eq | :gt | :error
+  def compare(%__MODULE__{doc: doc} = a, %__MODULE__{} = b) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.sticky_index_compare(a, b, cur_txn(a))
+    )
+  end
+
+  @doc """
+  Encodes the sticky index into a binary that can be stored or sent to other peers.
+
+  Use `decode/3` to restore it against a document holding the same content.
+  """
+  @spec encode(t) :: binary()
+  def encode(%__MODULE__{} = sticky_index) do
+    Yex.Nif.sticky_index_encode(sticky_index)
+  end
+
+  @doc """
+  Restores a sticky index previously produced by `encode/1` for the given document.
+
+  The index keeps the association it was encoded with, unless `assoc` is given to override it.
+
+      iex> alias Yex.{StickyIndex, Doc, Text}
+      iex> doc = Doc.new()
+      iex> txt = Doc.get_text(doc, "text")
+      iex> Text.insert(txt, 0, "abc")
+      iex> bin = txt |> StickyIndex.new(1, :after) |> StickyIndex.encode()
+      iex> Text.insert(txt, 0, "xyz")
+      iex> {:ok, pos} = StickyIndex.decode(doc, bin)
+      iex> {:ok, %{index: 4, assoc: :after}} = StickyIndex.get_offset(pos)
+  """
+  @spec decode(Yex.Doc.t(), binary(), :before | :after | nil) :: {:ok, t} | :error
+  def decode(%Yex.Doc{} = doc, binary, assoc \\ nil) when is_binary(binary) do
+    Yex.Nif.sticky_index_decode(doc, binary, assoc)
+  end
+
   defp cur_txn(%{doc: %Yex.Doc{reference: doc_ref}}) do
     Process.get(doc_ref, nil)
   end
@@ -0,0 +1,60 @@
+defmodule Yex.Typed do
+  @moduledoc """
+  Wraps a value together with the kind it should be stored as.
+
+  Plain Elixir values are converted by guessing their type, which is ambiguous for some inputs:
+  lists of bytes are stored as lists of numbers, and integers within the safe range are stored
+  as floats.
+  When the schema is known, wrap the value to force its interpretation.
+  `Yex.Typed` can be used anywhere a value is inserted into a shared type.
+
+  ## Kinds
+    * `:integer` - integers, or floats without a fractional part, read back as integers
+    * `:float` - any number, stored as a float
+    * `:string` - UTF-8 binaries
+    * `:binary` - binaries or lists of bytes, stored as a buffer
+    * `:list` - lists, never interpreted as a buffer
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> array = Yex.Doc.get_array(doc, "array")
+      iex> Yex.Array.push(array, [1, 2, 3])
+      iex> Yex.Array.push(array, Yex.Typed.new([1, 2, 3], :binary))
+      iex> Yex.Array.to_list(array)
+      [[1.0, 2.0, 3.0], %Yex.Binary{data: <<1, 2, 3>>}]
+  """
+  defstruct [:value, :kind]
+
+  @type kind :: :integer | :float | :string | :binary | :list
+  @type t :: %__MODULE__{
+          value: term(),
+          kind: kind()
+        }
+
+  defguardp is_kind(kind) when kind in [:integer, :float, :string, :binary, :list]
+
+  @doc """
+  Creates a typed value.
+  """
+  @spec new(term(), kind()) :: t()
+  def new(value, kind) when is_kind(kind) do
+    %__MODULE__{value: value, kind: kind}
+  end
+
+  @doc """
+  Converts the value the same way it would be stored as the given kind.
+  Raises `ArgumentError` if the value cannot be interpreted as that kind.
+
+  ## Examples
+      iex> Yex.Typed.normalize(3, :float)
+      3.0
+      iex> Yex.Typed.normalize(3.0, :integer)
+      3
+      iex> Yex.Typed.normalize([1, 2], :list)
+      [1.0, 2.0]
+  """
+  @spec normalize(term(), kind()) :: term()
+  def normalize(value, kind) when is_kind(kind) do
+    Yex.Nif.normalize_typed(value, kind)
+  end
+end
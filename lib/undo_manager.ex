@@ -3,12 +3,30 @@ defmodule Yex.UndoManager.Options do
   Options for creating an UndoManager.
 
   * `:capture_timeout` - Time in milliseconds to wait before creating a new capture group
+  * `:tracked_origins` - Transaction origins to track. When empty, changes from every origin
+    are tracked. Origins can also be added later with `Yex.UndoManager.include_origin/2`.
   """
   # Default from Yrs
-  defstruct capture_timeout: 500
+  defstruct capture_timeout: 500, tracked_origins: []
 
   @type t :: %__MODULE__{
-          capture_timeout: non_neg_integer()
+          capture_timeout: non_neg_integer(),
+          tracked_origins: [term()]
+        }
+end
+
+defmodule Yex.UndoManager.Event do
+  @moduledoc """
+  Event delivered to undo manager observers.
+
+  * `:kind` - `:undo` when the stack item belongs to the undo stack, `:redo` for the redo stack
+  * `:changed_parent_types` - Shared types modified by the stack item
+  """
+  defstruct [:kind, :changed_parent_types]
+
+  @type t :: %__MODULE__{
+          kind: :undo | :redo,
+          changed_parent_types: [Yex.SharedType.t()]
         }
 end
 
@@ -94,7 +112,9 @@ defmodule Yex.UndoManager do
 
   @doc """
   Undoes the last tracked change.
+  Returns `{:ok, :undone}`, or `{:ok, :nothing}` when there was nothing to undo.
   """
+  @spec undo(t) :: {:ok, :undone | :nothing}
   def undo(%{doc: doc} = undo_manager) do
     Doc.run_in_worker_process(doc,
       do: Yex.Nif.undo_manager_undo(undo_manager)
@@ -103,13 +123,47 @@ defmodule Yex.UndoManager do
 
   @doc """
   Redoes the last undone change.
+  Returns `{:ok, :redone}`, or `{:ok, :nothing}` when there was nothing to redo.
   """
+  @spec redo(t) :: {:ok, :redone | :nothing}
   def redo(%{doc: doc} = undo_manager) do
     Doc.run_in_worker_process(doc,
       do: Yex.Nif.undo_manager_redo(undo_manager)
     )
   end
 
+  @doc """
+  Returns true if there is a change to undo. Does not modify the UndoManager,
+  so it is cheap enough to call after every edit, e.g. to enable an undo button.
+  """
+  @spec can_undo?(t) :: boolean()
+  def can_undo?(%{doc: doc} = undo_manager) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.undo_manager_can_undo(undo_manager)
+    )
+  end
+
+  @doc """
+  Returns true if there is an undone change to redo. Does not modify the UndoManager.
+  """
+  @spec can_redo?(t) :: boolean()
+  def can_redo?(%{doc: doc} = undo_manager) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.undo_manager_can_redo(undo_manager)
+    )
+  end
+
+  @doc """
+  Returns the number of stack items that can be undone and redone, as `%{undo: n, redo: m}`.
+  Changes made within the capture timeout are merged into one stack item.
+  """
+  @spec stack_sizes(t) :: %{undo: non_neg_integer(), redo: non_neg_integer()}
+  def stack_sizes(%{doc: doc} = undo_manager) do
+    Doc.run_in_worker_process(doc,
+      do: Yex.Nif.undo_manager_stack_sizes(undo_manager)
+    )
+  end
+
   @doc """
   Expands the scope of the UndoManager to include additional shared types.
   The scope can be a Text, Array, or Map type.
@@ -158,4 +212,74 @@ defmodule Yex.UndoManager do
       do: Yex.Nif.undo_manager_clear(undo_manager)
     )
   end
+
+  @doc """
+  Registers an observer that is notified every time a new stack item is added.
+
+  Each call registers an independent observer with its own reference; registering again
+  does not replace or leak a previously registered observer.
+
+  A message is delivered to the calling process in the shape of:
+      {:item_added, ref, %Yex.UndoManager.Event{}, origin, metadata}
+
+  ## Options
+    * `:metadata` - provides metadata to be attached to this observer.
+
+  ## Returns
+    * A reference that can be used with `Yex.Subscription.unsubscribe/1`
+  """
+  @spec observe_item_added(t, keyword()) :: reference()
+  def observe_item_added(%{doc: doc} = undo_manager, opt \\ []) do
+    observe(doc, opt, &Yex.Nif.undo_manager_observe_item_added(undo_manager, &1, &2, &3))
+  end
+
+  @doc """
+  Registers an observer that is notified every time an existing stack item is extended,
+  i.e. when a change is captured into the previous stack item within `capture_timeout`.
+
+  A message is delivered to the calling process in the shape of:
+      {:item_updated, ref, %Yex.UndoManager.Event{}, origin, metadata}
+
+  ## Options
+    * `:metadata` - provides metadata to be attached to this observer.
+
+  ## Returns
+    * A reference that can be used with `Yex.Subscription.unsubscribe/1`
+  """
+  @spec observe_item_updated(t, keyword()) :: reference()
+  def observe_item_updated(%{doc: doc} = undo_manager, opt \\ []) do
+    observe(doc, opt, &Yex.Nif.undo_manager_observe_item_updated(undo_manager, &1, &2, &3))
+  end
+
+  @doc """
+  Registers an observer that is notified every time a stack item is popped by undo or redo.
+
+  A message is delivered to the calling process in the shape of:
+      {:item_popped, ref, %Yex.UndoManager.Event{}, origin, metadata}
+
+  ## Options
+    * `:metadata` - provides metadata to be attached to this observer.
+
+  ## Returns
+    * A reference that can be used with `Yex.Subscription.unsubscribe/1`
+  """
+  @spec observe_item_popped(t, keyword()) :: reference()
+  def observe_item_popped(%{doc: doc} = undo_manager, opt \\ []) do
+    observe(doc, opt, &Yex.Nif.undo_manager_observe_item_popped(undo_manager, &1, &2, &3))
+  end
+
+  # Observers run synchronously in the worker process while the transaction is committed,
+  # so no extra process or thread is involved. The subscription is released as soon as
+  # it is unsubscribed, or when the registering process exits.
+  defp observe(doc, opt, observe_fun) do
+    ref = make_ref()
+    notify_pid = self()
+
+    sub =
+      Doc.run_in_worker_process(doc,
+        do: observe_fun.(notify_pid, ref, Keyword.get(opt, :metadata))
+      )
+
+    Yex.Subscription.register(sub, ref)
+  end
 end
@@ -7,12 +7,14 @@ defmodule Yex do
 
   @type any_type ::
           binary
+          | Yex.Binary.t()
           | map
           | list
           | integer
           | float
           | boolean
           | nil
+          | atom
   @type input_type ::
           any_type
           | Yex.PrelimType.t()
@@ -57,6 +59,11 @@ defmodule Yex do
   @doc """
   Encode the document state as a single update message that can be applied on the remote document. Optionally, specify the target state vector to only write the missing differences to the update message.
 
+  The update always covers every root type of the document. Blocks of different root types
+  share the same per-client clock sequence, so an update restricted to some roots would leave
+  gaps that the receiving peer can never integrate. For type-scoped sync channels, keep the
+  independently replicated data in separate documents (or sub-documents).
+
   ## Examples
       iex> doc = Yex.Doc.new()
       iex> {:ok, _binary} = Yex.encode_state_as_update(doc)
@@ -84,6 +91,44 @@ defmodule Yex do
     end
   end
 
+  @doc """
+  Encodes the document as it was when `snapshot` was taken with `Yex.Doc.snapshot/1`, as a
+  v1 update. Applying it to an empty document restores that version. Optionally, specify
+  a target state vector to only write what it is missing of that version.
+
+  The document must be created with `skip_gc: true`, otherwise content deleted since the
+  snapshot may already be gone and `{:error, :gc_enabled}` is returned.
+
+  ## Examples
+      iex> doc = Yex.Doc.with_options(%Yex.Doc.Options{skip_gc: true})
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello")
+      iex> {:ok, snapshot} = Yex.Doc.snapshot(doc)
+      iex> Yex.Text.insert(text, 5, " World")
+      iex> {:ok, update} = Yex.encode_state_as_update_from_snapshot(doc, snapshot)
+      iex> restored = Yex.Doc.new()
+      iex> Yex.apply_update(restored, update)
+      iex> Yex.Doc.get_text(restored, "text") |> Yex.Text.to_string()
+      "Hello"
+  """
+  @spec encode_state_as_update_from_snapshot(Yex.Doc.t(), binary(), binary() | nil) ::
+          {:ok, binary()} | {:error, term()}
+  def encode_state_as_update_from_snapshot(
+        %Yex.Doc{} = doc,
+        snapshot,
+        encoded_state_vector \\ nil
+      )
+      when is_binary(snapshot) do
+    Yex.Doc.run_in_worker_process doc do
+      Yex.Nif.encode_state_as_update_from_snapshot_v1(
+        doc,
+        cur_txn(doc),
+        snapshot,
+        encoded_state_vector
+      )
+    end
+  end
+
   @doc false
   @spec encode_diff_and_state_vector_v1(Yex.Doc.t(), binary()) ::
           {:ok, binary(), binary()} | {:error, term()}
@@ -132,6 +177,76 @@ defmodule Yex do
     end
   end
 
+  @doc """
+  Applies a document update like `apply_update/2` and returns the names of the root types
+  it changed, sorted. Useful to notify only the subscribers of the affected types.
+
+  The changed types are only known once the update's transaction commits, so this can't be
+  called inside `Yex.Doc.transaction/3`; it returns `{:error, :transaction_busy}` there.
+
+  ## Examples
+      iex> remote = Yex.Doc.new()
+      iex> Yex.Text.insert(Yex.Doc.get_text(remote, "text"), 0, "hello")
+      iex> {:ok, update} = Yex.encode_state_as_update(remote)
+      iex> doc = Yex.Doc.new()
+      iex> Yex.apply_update_returning_summary(doc, update)
+      {:ok, ["text"]}
+  """
+  @spec apply_update_returning_summary(Yex.Doc.t(), binary()) ::
+          {:ok, [binary()]} | {:error, term()}
+  def apply_update_returning_summary(%Yex.Doc{} = doc, update) do
+    Yex.Doc.run_in_worker_process doc do
+      Yex.Nif.apply_update_v1_returning_summary(doc, cur_txn(doc), update)
+    end
+  end
+
+  @doc """
+  Checks whether an update applies cleanly to the document without modifying it.
+
+  The update is applied to a throwaway copy of the document built from its full encoded
+  state, so validating is roughly as expensive as encoding and decoding the whole document.
+  Returns `{:error, :missing_dependencies}` when the update depends on changes the document
+  has not seen yet; applying it would leave it pending until those arrive.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> remote = Yex.Doc.new()
+      iex> Yex.Text.insert(Yex.Doc.get_text(remote, "text"), 0, "Hello")
+      iex> {:ok, update} = Yex.encode_state_as_update(remote)
+      iex> Yex.validate_update(doc, update)
+      :ok
+      iex> Yex.Doc.get_text(doc, "text") |> Yex.Text.to_string()
+      ""
+  """
+  @spec validate_update(Yex.Doc.t(), binary()) :: :ok | {:error, term()}
+  def validate_update(%Yex.Doc{} = doc, update) when is_binary(update) do
+    Yex.Doc.run_in_worker_process doc do
+      Yex.Nif.validate_update_v1(doc, cur_txn(doc), update)
+    end
+  end
+
+  @doc """
+  Merges several updates into a single update, without creating a document.
+  Useful to compact stored updates before persisting them.
+
+  An empty list merges into an empty update. Returns
+  `{:error, {:encoding_exception, message}}` when one of the updates cannot be decoded.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> {:ok, sv} = Yex.encode_state_vector(doc)
+      iex> Yex.Text.insert(text, 0, "Hello")
+      iex> {:ok, first} = Yex.encode_state_as_update(doc, sv)
+      iex> {:ok, sv} = Yex.encode_state_vector(doc)
+      iex> Yex.Text.insert(text, 5, " World")
+      iex> {:ok, second} = Yex.encode_state_as_update(doc, sv)
+      iex> {:ok, merged} = Yex.merge_updates([first, second])
+      iex> restored = Yex.Doc.new()
+      iex> Yex.apply_update(restored, merged)
+      iex> Yex.Doc.get_text(restored, "text") |> Yex.Text.to_string()
+      "Hello World"
+  """
   @spec merge_updates([binary()]) :: {:ok, binary()} | {:error, term()}
   def merge_updates(updates) when is_list(updates) do
     merge_updates_v1(updates)
@@ -147,6 +262,72 @@ defmodule Yex do
     Yex.Nif.merge_updates_v2(updates)
   end
 
+  @doc """
+  Returns only the part of `update` that a peer at `encoded_state_vector` is missing,
+  without loading the update into a document. Useful to answer sync step 1 from stored
+  updates.
+
+  Returns `{:error, :invalid_state_vector}` when the state vector cannot be decoded.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> text = Yex.Doc.get_text(doc, "text")
+      iex> Yex.Text.insert(text, 0, "Hello")
+      iex> {:ok, sv} = Yex.encode_state_vector(doc)
+      iex> Yex.Text.insert(text, 5, " World")
+      iex> {:ok, update} = Yex.encode_state_as_update(doc)
+      iex> {:ok, diff} = Yex.diff_update(update, sv)
+      iex> byte_size(diff) < byte_size(update)
+      true
+      iex> Yex.diff_update(update, <<255>>)
+      {:error, :invalid_state_vector}
+  """
+  @spec diff_update(binary(), binary()) :: {:ok, binary()} | {:error, term()}
+  def diff_update(update, encoded_state_vector)
+      when is_binary(update) and is_binary(encoded_state_vector) do
+    Yex.Nif.diff_update_v1(update, encoded_state_vector)
+  end
+
+  @doc """
+  Computes the encoded state vector of the document that `update` describes, without a
+  document at hand. Useful to answer sync step 1 from stored updates.
+
+  The update is loaded into a throwaway document, so this is as expensive as applying it.
+  To handle many stored updates, merge them with `merge_updates/1` first.
+
+  Returns `{:error, {:encoding_exception, message}}` when the update cannot be decoded.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> Yex.Text.insert(Yex.Doc.get_text(doc, "text"), 0, "Hello")
+      iex> {:ok, update} = Yex.encode_state_as_update(doc)
+      iex> Yex.state_vector_from_update(update) == Yex.encode_state_vector(doc)
+      true
+  """
+  @spec state_vector_from_update(binary()) :: {:ok, binary()} | {:error, term()}
+  def state_vector_from_update(update) when is_binary(update) do
+    Yex.Nif.state_vector_from_update_v1(update)
+  end
+
+  @doc """
+  Picks the update encoding to use with a peer, given the formats the peer supports.
+
+  Prefers `:v2` over `:v1` when both sides support it. Returns `:error` when
+  no format is supported by both sides.
+
+  ## Examples
+      iex> Yex.negotiate_update_format([:v1, :v2])
+      {:ok, :v2}
+      iex> Yex.negotiate_update_format([:v1, :v3])
+      {:ok, :v1}
+      iex> Yex.negotiate_update_format([:v3])
+      :error
+  """
+  @spec negotiate_update_format([atom()]) :: {:ok, :v1 | :v2} | :error
+  def negotiate_update_format(supported) when is_list(supported) do
+    Yex.Nif.negotiate_update_format(supported)
+  end
+
   @doc """
   Normalize a number to a format that can be used in Yjs.
   """
@@ -158,6 +339,47 @@ defmodule Yex do
     number
   end
 
+  @max_safe_integer 9_007_199_254_740_991
+
+  @doc """
+  Converts floats without a fractional part back into integers, recursing into lists and maps.
+
+  Integers within the safe range are stored as floats, so `42` is read back as `42.0`.
+  Use this when the values are known to be integers. Structs such as `Yex.Binary` are
+  left untouched.
+
+  ## Examples
+      iex> doc = Yex.Doc.new()
+      iex> map = Yex.Doc.get_map(doc, "map")
+      iex> Yex.Map.set(map, "count", 42)
+      iex> Yex.Map.set(map, "ratio", 0.5)
+      iex> Yex.Map.fetch!(map, "count")
+      42.0
+      iex> Yex.narrow_integers(Yex.Map.to_json(map))
+      %{"count" => 42, "ratio" => 0.5}
+  """
+  @spec narrow_integers(term()) :: term()
+  def narrow_integers(value)
+      when is_float(value) and abs(value) <= @max_safe_integer and trunc(value) == value do
+    trunc(value)
+  end
+
+  def narrow_integers(value) when is_list(value) do
+    Enum.map(value, &narrow_integers/1)
+  end
+
+  def narrow_integers(%_{} = value) do
+    value
+  end
+
+  def narrow_integers(value) when is_map(value) do
+    Map.new(value, fn {k, v} -> {k, narrow_integers(v)} end)
+  end
+
+  def narrow_integers(value) do
+    value
+  end
+
   defp cur_txn(%Yex.Doc{reference: doc_ref}) do
     Process.get(doc_ref, nil)
   end
@@ -1,9 +1,14 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use crate::wrap::NifWrap;
+use crate::wrap::{NifWrap, SliceIntoBinary};
 use rustler::types;
-use rustler::{Decoder, Encoder, Env, Error, ListIterator, MapIterator, NifResult, Term};
+use rustler::{
+    Binary, Decoder, Encoder, Env, Error, ListIterator, MapIterator, NifResult, NifStruct,
+    NifUnitEnum, Term,
+};
 use yrs::any::{F64_MAX_SAFE_INTEGER, F64_MIN_SAFE_INTEGER};
 use yrs::*;
 
@@ -15,7 +20,10 @@ fn encode<'a>(env: Env<'a>, any: &Any) -> Term<'a> {
         Any::Number(num) => num.encode(env),
         Any::BigInt(n) => n.encode(env),
         Any::String(s) => s.encode(env),
-        Any::Buffer(b) => b.encode(env),
+        Any::Buffer(b) => NifBinary {
+            data: SliceIntoBinary::new(b).encode(env),
+        }
+        .encode(env),
         Any::Array(a) => {
             let list: Vec<Term<'a>> = a.iter().map(|item| encode(env, item)).collect();
             list.encode(env)
@@ -43,30 +51,31 @@ fn decode<'a>(term: Term<'a>) -> NifResult<Any> {
         } else if atom == types::atom::undefined() {
             return Ok(Any::Undefined);
         }
-        return Err(rustler::Error::BadArg);
+        return Ok(Any::String(term.atom_to_string()?.into()));
     } else if let Ok(v) = term.decode::<i32>() {
         return Ok(Any::Number(v.into()));
     } else if let Ok(v) = term.decode::<i64>() {
         // Check if the number is within the safe integer range for f64
         // If it is not, we return it as a BigInt
-        if v > F64_MAX_SAFE_INTEGER as i64 || v < F64_MIN_SAFE_INTEGER as i64 {
-            return Ok(Any::BigInt(v));
-        }
-        return Ok(Any::Number(v as f64));
+        return Ok(integer_to_any(v));
     } else if let Ok(v) = term.decode::<f64>() {
         return Ok(Any::Number(v));
     } else if let Ok(v) = term.decode::<&str>() {
         return Ok(Any::String(v.into()));
-    } else if let Ok(v) = term.decode::<Vec<u8>>() {
-        return Ok(Any::Buffer(v.into()));
     } else if let Ok(v) = term.decode::<ListIterator<'a>>() {
         let a = v
             .map(|v| decode(v))
             .collect::<Result<Vec<yrs::Any>, rustler::Error>>()?;
         return Ok(Any::from(a));
+    } else if let Ok(v) = term.decode::<NifBinary<'a>>() {
+        let data: Binary = v.data.decode()?;
+        return Ok(Any::Buffer(data.as_slice().into()));
+    } else if term.map_get(types::atom::__struct__()).is_ok() {
+        // Structs other than the ones recognized above are not plain data.
+        return Err(rustler::Error::BadArg);
     } else if let Ok(v) = term.decode::<MapIterator<'a>>() {
         let a = v
-            .map(|(k, v)| Ok((k.decode::<String>()?, decode(v)?)))
+            .map(|(k, v)| Ok((decode_key(k)?, decode(v)?)))
             .collect::<Result<HashMap<String, yrs::Any>, rustler::Error>>()?;
         return Ok(Any::from(a));
     }
@@ -74,20 +83,186 @@ fn decode<'a>(term: Term<'a>) -> NifResult<Any> {
     Err(rustler::Error::BadArg)
 }
 
+/// Decodes a map key, accepting atoms as well as strings. Keys are always encoded back as strings.
+fn decode_key(term: Term<'_>) -> NifResult<String> {
+    if let Ok(key) = term.decode::<String>() {
+        return Ok(key);
+    }
+    if term.is_atom() {
+        return term.atom_to_string();
+    }
+    Err(rustler::Error::BadArg)
+}
+
+/// Converts parsed JSON into `Any`, treating every JSON number as a float
+/// like the rest of the library does for numbers coming from Elixir.
+pub(crate) fn json_to_any(value: serde_json::Value) -> Any {
+    match value {
+        serde_json::Value::Null => Any::Null,
+        serde_json::Value::Bool(b) => Any::Bool(b),
+        serde_json::Value::Number(n) => Any::Number(n.as_f64().unwrap_or(f64::NAN)),
+        serde_json::Value::String(s) => Any::String(s.into()),
+        serde_json::Value::Array(a) => {
+            Any::from(a.into_iter().map(json_to_any).collect::<Vec<_>>())
+        }
+        serde_json::Value::Object(o) => Any::from(
+            o.into_iter()
+                .map(|(k, v)| (k, json_to_any(v)))
+                .collect::<HashMap<String, Any>>(),
+        ),
+    }
+}
+
+/// Hashes the content of `any`. Map entries are visited in key order so equal
+/// content always yields the same value, regardless of insertion order.
+/// The value is only stable for a given build of the library, do not persist it.
+pub(crate) fn content_hash(any: &Any) -> u64 {
+    fn walk<H: Hasher>(any: &Any, state: &mut H) {
+        std::mem::discriminant(any).hash(state);
+        match any {
+            Any::Null | Any::Undefined => {}
+            Any::Bool(b) => b.hash(state),
+            Any::Number(n) => n.to_bits().hash(state),
+            Any::BigInt(n) => n.hash(state),
+            Any::String(s) => s.hash(state),
+            Any::Buffer(b) => b.hash(state),
+            Any::Array(a) => {
+                a.len().hash(state);
+                a.iter().for_each(|item| walk(item, state));
+            }
+            Any::Map(m) => {
+                let mut entries: Vec<_> = m.iter().collect();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                entries.len().hash(state);
+                for (key, value) in entries {
+                    key.hash(state);
+                    walk(value, state);
+                }
+            }
+        }
+    }
+    let mut hasher = DefaultHasher::new();
+    walk(any, &mut hasher);
+    hasher.finish()
+}
+
+/// Counts the values in `any`, containers and map keys included.
+/// This is a rough measure of the size of the term it encodes to.
+pub(crate) fn value_count(any: &Any) -> usize {
+    match any {
+        Any::Array(a) => 1 + a.iter().map(value_count).sum::<usize>(),
+        Any::Map(m) => 1 + m.values().map(|v| 1 + value_count(v)).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// Scalar kinds a term can be forced into when the schema is known,
+/// bypassing the guessing done by the default decoder.
+#[derive(NifUnitEnum, Clone, Copy)]
+pub enum NifAnyKind {
+    Integer,
+    Float,
+    String,
+    Binary,
+    List,
+}
+
+pub(crate) fn integer_to_any(v: i64) -> Any {
+    if v > F64_MAX_SAFE_INTEGER as i64 || v < F64_MIN_SAFE_INTEGER as i64 {
+        Any::BigInt(v)
+    } else {
+        Any::Number(v as f64)
+    }
+}
+
+pub(crate) fn decode_typed(term: Term<'_>, kind: NifAnyKind) -> NifResult<Any> {
+    match kind {
+        NifAnyKind::Integer => {
+            // Stored as a bigint whatever its size, so it is read back as an integer.
+            if let Ok(v) = term.decode::<i64>() {
+                return Ok(Any::BigInt(v));
+            } else if let Ok(v) = term.decode::<f64>() {
+                if v.fract() == 0.0 && v >= i64::MIN as f64 && v <= i64::MAX as f64 {
+                    return Ok(Any::BigInt(v as i64));
+                }
+            }
+        }
+        NifAnyKind::Float => {
+            if let Ok(v) = term.decode::<f64>() {
+                return Ok(Any::Number(v));
+            } else if let Ok(v) = term.decode::<i64>() {
+                return Ok(Any::Number(v as f64));
+            }
+        }
+        NifAnyKind::String => {
+            if let Ok(v) = term.decode::<&str>() {
+                return Ok(Any::String(v.into()));
+            }
+        }
+        NifAnyKind::Binary => {
+            if let Ok(v) = term.decode::<Binary>() {
+                return Ok(Any::Buffer(v.as_slice().into()));
+            } else if let Ok(v) = term.decode::<Vec<u8>>() {
+                return Ok(Any::Buffer(v.into()));
+            }
+        }
+        NifAnyKind::List => {
+            if let Ok(v) = term.decode::<ListIterator<'_>>() {
+                let a = v
+                    .map(|v| decode(v))
+                    .collect::<Result<Vec<yrs::Any>, rustler::Error>>()?;
+                return Ok(Any::from(a));
+            }
+        }
+    }
+    Err(rustler::Error::BadArg)
+}
+
 pub type NifAny = NifWrap<Any>;
 
+/// A `Yex.Binary` struct, the explicit form of `Any::Buffer` on the Elixir side.
+/// Plain binaries are decoded as strings whenever they are valid UTF-8.
+#[derive(NifStruct)]
+#[module = "Yex.Binary"]
+struct NifBinary<'a> {
+    data: Term<'a>,
+}
+
 impl<'a> Decoder<'a> for NifAny {
     fn decode(term: Term<'a>) -> NifResult<Self> {
         decode(term).map(|any| any.into())
     }
 }
 
+#[derive(NifStruct)]
+#[module = "Yex.Typed"]
+struct NifTyped<'a> {
+    value: Term<'a>,
+    kind: NifAnyKind,
+}
+
+/// A `Yex.Typed` struct, decoded with the kind it carries instead of the default cascade.
+pub struct NifTypedAny(pub Any);
+
+impl<'a> Decoder<'a> for NifTypedAny {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let typed: NifTyped<'a> = term.decode()?;
+        decode_typed(typed.value, typed.kind).map(NifTypedAny)
+    }
+}
+
+impl rustler::Encoder for NifTypedAny {
+    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
+        encode(env, &self.0)
+    }
+}
+
 pub type NifAttr = NifWrap<HashMap<Arc<str>, Any>>;
 impl<'a> Decoder<'a> for NifAttr {
     fn decode(term: Term<'a>) -> NifResult<Self> {
         if let Ok(v) = term.decode::<MapIterator<'a>>() {
             let a = v
-                .map(|(k, v)| Ok((k.decode::<&str>()?.into(), decode(v)?)))
+                .map(|(k, v)| Ok((decode_key(k)?.into(), decode(v)?)))
                 .collect::<Result<HashMap<Arc<str>, yrs::Any>, rustler::Error>>()?;
             return Ok(a.into());
         }
@@ -112,3 +287,8 @@ fn normalize_number(any: NifAny) -> NifAny {
     // For example, even numbers within the SAFE_INTEGER range are converted to float type.
     any
 }
+
+#[rustler::nif]
+fn normalize_typed(term: Term<'_>, kind: NifAnyKind) -> NifResult<NifAny> {
+    decode_typed(term, kind).map(|any| any.into())
+}
@@ -1,14 +1,20 @@
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc};
+use std::sync::Mutex;
+
+use rustler::{Atom, Encoder, Env, NifResult, NifStruct, ResourceArc, Term};
+use yrs::block::{ItemContent, ItemPtr};
+use yrs::branch::Branch;
 use yrs::types::ToJson;
 use yrs::*;
 
 use crate::{
+    any::{content_hash, value_count},
     atoms,
     doc::NifDoc,
     event::{NifArrayEvent, NifSharedTypeDeepObservable, NifSharedTypeObservable},
     shared_type::{NifSharedType, SharedTypeId},
     transaction::TransactionResource,
     utils::{capped_index_and_length, normalize_index, normalize_index_for_insert},
+    wrap::NifWrap,
     yinput::{NifWeakPrelim, NifYInput},
     youtput::NifYOut,
     NifAny,
@@ -54,8 +60,9 @@ fn array_insert(
     current_transaction: Option<ResourceArc<TransactionResource>>,
     index: i64,
     value: NifYInput,
+    origin: Term<'_>,
 ) -> NifResult<Atom> {
-    array.mutably(env, current_transaction, |txn| {
+    array.mutably_with_origin(env, current_transaction, origin, |txn| {
         let array = array.get_ref(txn)?;
 
         let index = normalize_index_for_insert(array.len(txn), index);
@@ -70,12 +77,70 @@ fn array_insert_list(
     array: NifArray,
     current_transaction: Option<ResourceArc<TransactionResource>>,
     index: i64,
-    values: Vec<NifAny>,
+    values: Vec<NifYInput>,
 ) -> NifResult<Atom> {
     array.mutably(env, current_transaction, |txn| {
         let array = array.get_ref(txn)?;
         let index = normalize_index_for_insert(array.len(txn), index);
-        array.insert_range(txn, index, values.into_iter().map(|a| a.0.clone()));
+        if values
+            .iter()
+            .all(|value| matches!(value, NifYInput::Any(_)))
+        {
+            // plain values are packed into a single block
+            let values = values.into_iter().filter_map(|value| match value {
+                NifYInput::Any(any) => Some(any.0),
+                _ => None,
+            });
+            array.insert_range(txn, index, values);
+        } else {
+            for (offset, value) in values.into_iter().enumerate() {
+                array.insert(txn, index + offset as u32, value);
+            }
+        }
+        Ok(atoms::ok())
+    })
+}
+
+#[rustler::nif]
+fn array_push_back(
+    env: Env<'_>,
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    value: NifYInput,
+) -> NifResult<Atom> {
+    array.mutably(env, current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        array.push_back(txn, value);
+        Ok(atoms::ok())
+    })
+}
+
+#[rustler::nif]
+fn array_push_front(
+    env: Env<'_>,
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    value: NifYInput,
+) -> NifResult<Atom> {
+    array.mutably(env, current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        array.push_front(txn, value);
+        Ok(atoms::ok())
+    })
+}
+
+#[rustler::nif]
+fn array_append_list(
+    env: Env<'_>,
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    values: Vec<NifAny>,
+) -> NifResult<Atom> {
+    array.mutably(env, current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        // len() reads the cached content length of the branch, no traversal involved
+        let index = array.len(txn);
+        array.insert_range(txn, index, values.into_iter().map(|a| a.0));
         Ok(atoms::ok())
     })
 }
@@ -126,6 +191,47 @@ fn array_get(
     })
 }
 #[rustler::nif]
+fn array_element_id(
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    index: i64,
+) -> NifResult<(Atom, (u64, u32))> {
+    array.readonly(current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        let mut index = normalize_index(array.len(txn), index);
+        // find the block holding the element, whose id is the block id shifted by the offset
+        let branch: &Branch = array.as_ref();
+        let mut current = branch.start;
+        while let Some(item) = current.as_deref() {
+            if !item.is_deleted() && item.is_countable() {
+                if index < item.len {
+                    return Ok((atoms::ok(), (item.id.client, item.id.clock + index)));
+                }
+                index -= item.len;
+            }
+            current = item.right;
+        }
+        Err(rustler::Error::Atom("error"))
+    })
+}
+/// Only scalar elements are compared; nested shared types never match.
+#[rustler::nif]
+fn array_index_of(
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    value: NifAny,
+) -> NifResult<(Atom, u32)> {
+    array.readonly(current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        array
+            .iter(txn)
+            .position(|out| matches!(out, Out::Any(ref any) if *any == value.0))
+            .and_then(|index| u32::try_from(index).ok())
+            .map(|index| (atoms::ok(), index))
+            .ok_or(rustler::Error::Atom("error"))
+    })
+}
+#[rustler::nif]
 fn array_delete_range(
     env: Env<'_>,
     array: NifArray,
@@ -143,6 +249,58 @@ fn array_delete_range(
     })
 }
 #[rustler::nif]
+fn array_clear(
+    env: Env<'_>,
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Atom> {
+    array.mutably(env, current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        let len = array.len(txn);
+        if len > 0 {
+            array.remove_range(txn, 0, len);
+        }
+        Ok(atoms::ok())
+    })
+}
+/// Only plain map elements are inspected; nested shared types and other values are kept.
+#[rustler::nif]
+fn array_remove_where(
+    env: Env<'_>,
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    field: &str,
+    equals: NifAny,
+) -> NifResult<u32> {
+    array.mutably(env, current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        let matches: Vec<u32> = array
+            .iter(txn)
+            .enumerate()
+            .filter(|(_, value)| match value {
+                Out::Any(Any::Map(map)) => map.get(field) == Some(&equals.0),
+                _ => false,
+            })
+            .map(|(index, _)| index as u32)
+            .collect();
+        // remove contiguous runs back to front, so earlier indexes stay valid
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for index in matches.iter().rev() {
+            match runs.last_mut() {
+                Some((start, len)) if *start == index + 1 => {
+                    *start = *index;
+                    *len += 1;
+                }
+                _ => runs.push((*index, 1)),
+            }
+        }
+        for (start, len) in runs {
+            array.remove_range(txn, start, len);
+        }
+        Ok(matches.len() as u32)
+    })
+}
+#[rustler::nif]
 fn array_move_to(
     env: Env<'_>,
     array: NifArray,
@@ -206,16 +364,28 @@ fn array_slice(
     current_transaction: Option<ResourceArc<TransactionResource>>,
     start_index: usize,
     amount: usize,
-    step: usize,
+    step: i64,
 ) -> NifResult<Vec<NifYOut>> {
+    if step == 0 {
+        return Err(rustler::Error::Atom("error"));
+    }
     let doc = array.doc();
+    let stride = step.unsigned_abs() as usize;
     array.readonly(current_transaction, |txn| {
         let array = array.get_ref(txn)?;
-        Ok(array
-            .iter(txn)
+        // a negative step walks from the end, so start_index counts from the last element;
+        // only then is the array materialized, as its iterator cannot run backwards
+        let values: Box<dyn Iterator<Item = Out> + '_> = if step < 0 {
+            let mut values: Vec<Out> = array.iter(txn).collect();
+            values.reverse();
+            Box::new(values.into_iter())
+        } else {
+            Box::new(array.iter(txn))
+        };
+        Ok(values
             .skip(start_index)
             .take(amount)
-            .step_by(step)
+            .step_by(stride)
             .map(|b| NifYOut::from_native(b, doc.clone()))
             .collect())
     })
@@ -231,3 +401,177 @@ fn array_to_json(
         Ok(array.to_json(txn).into())
     })
 }
+
+/// Converts the array to JSON in batches of at most `chunk_size` elements. Each batch is
+/// encoded as soon as it is complete, so at most one batch is held as `Any` at a time.
+/// The returned list still spans the whole array.
+#[rustler::nif]
+fn array_to_json_chunked<'a>(
+    env: Env<'a>,
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    chunk_size: usize,
+) -> NifResult<Vec<Term<'a>>> {
+    if chunk_size == 0 {
+        return Err(rustler::Error::BadArg);
+    }
+    array.readonly(current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        let mut chunks = Vec::new();
+        let mut chunk: Vec<NifAny> = Vec::with_capacity(chunk_size);
+        for value in array.iter(txn) {
+            chunk.push(value.to_json(txn).into());
+            if chunk.len() == chunk_size {
+                chunks.push(chunk.encode(env));
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            chunks.push(chunk.encode(env));
+        }
+        Ok(chunks)
+    })
+}
+
+/// Number of values `array_to_json` would produce, nested ones and map keys included.
+/// Elements are converted one at a time, so this stays cheap on memory for large arrays.
+#[rustler::nif]
+fn array_json_size(
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<usize> {
+    array.readonly(current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        Ok(array
+            .iter(txn)
+            .map(|value| value_count(&value.to_json(txn)))
+            .sum())
+    })
+}
+
+#[rustler::nif]
+fn array_content_hash(
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<u64> {
+    array.readonly(current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        Ok(content_hash(&array.to_json(txn)))
+    })
+}
+
+#[rustler::nif]
+fn array_deep_equal(
+    array: NifArray,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    expected: NifAny,
+) -> NifResult<bool> {
+    array.readonly(current_transaction, |txn| {
+        let array = array.get_ref(txn)?;
+        Ok(array.to_json(txn) == expected.0)
+    })
+}
+
+/// Cursor over an array, pinned to the read transaction opened with it.
+/// The transaction holds the document lock until the cursor is closed, so writers fail
+/// with `transaction_busy` meanwhile; in exchange the item the cursor points at stays valid.
+/// Fields drop in order, so the transaction is released before the document it borrows.
+pub struct ArrayCursor {
+    txn: Transaction<'static>,
+    /// Item holding the next value, and how many of its values were already returned.
+    next: Option<ItemPtr>,
+    offset: u32,
+    doc: NifDoc,
+}
+
+pub type ArrayIterResource = NifWrap<Mutex<Option<ArrayCursor>>>;
+#[rustler::resource_impl]
+impl rustler::Resource for ArrayIterResource {}
+
+#[derive(NifStruct)]
+#[module = "Yex.Array.Iterator"]
+pub struct NifArrayIterator {
+    reference: ResourceArc<ArrayIterResource>,
+    doc: NifDoc,
+}
+
+#[rustler::nif]
+fn array_iter_open(array: NifArray) -> NifResult<(Atom, NifArrayIterator)> {
+    let doc = array.doc();
+    doc.ensure_alive()?;
+    let txn = yrs::Transact::try_transact(&doc.reference.0).map_err(crate::error::Error::from)?;
+    // The cursor keeps `doc` alive for as long as the transaction borrowing it.
+    let txn: Transaction<'static> = unsafe { std::mem::transmute(txn) };
+    let array = array.get_ref(&txn)?;
+    let branch: &Branch = array.as_ref();
+    let cursor = ArrayCursor {
+        next: branch.start,
+        txn,
+        offset: 0,
+        doc: doc.clone(),
+    };
+    Ok((
+        atoms::ok(),
+        NifArrayIterator {
+            reference: ResourceArc::new(NifWrap(Mutex::new(Some(cursor)))),
+            doc,
+        },
+    ))
+}
+
+#[rustler::nif]
+fn array_iter_next(iter: NifArrayIterator, batch_size: u32) -> NifResult<(Atom, Vec<NifYOut>)> {
+    let mut inner = match iter.reference.0.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let cursor = inner
+        .as_mut()
+        .ok_or_else(|| rustler::Error::Term(Box::new(atoms::iterator_closed())))?;
+    let mut values: Vec<NifYOut> = Vec::new();
+    while values.len() < batch_size as usize {
+        let Some(item) = cursor.next else {
+            break;
+        };
+        if item.is_deleted() || !item.is_countable() {
+            cursor.next = item.right;
+            cursor.offset = 0;
+            continue;
+        }
+        let start = cursor.offset as usize;
+        let end = (start + batch_size as usize - values.len()).min(item.len as usize);
+        // Plain values are copied out of the block one slice at a time; other content
+        // (shared types, sub-documents, binaries) takes a single slot per item.
+        match &item.content {
+            ItemContent::Any(any) => values.extend(
+                any[start..end]
+                    .iter()
+                    .map(|value| NifYOut::from_native(Out::Any(value.clone()), cursor.doc.clone())),
+            ),
+            content => values.extend(
+                content
+                    .get_content()
+                    .into_iter()
+                    .skip(start)
+                    .take(end - start)
+                    .map(|value| NifYOut::from_native(value, cursor.doc.clone())),
+            ),
+        }
+        cursor.offset = end as u32;
+        if cursor.offset >= item.len {
+            cursor.next = item.right;
+            cursor.offset = 0;
+        }
+    }
+    Ok((atoms::ok(), values))
+}
+
+#[rustler::nif]
+fn array_iter_close(iter: NifArrayIterator) -> Atom {
+    let mut inner = match iter.reference.0.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *inner = None;
+    atoms::ok()
+}
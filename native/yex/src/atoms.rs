@@ -4,15 +4,47 @@ rustler::atoms! {
     terminated,
     poison_error,
     transaction_acq_error,
+    transaction_busy,
     encoding_exception,
     update_v1,
     update_v2,
     subdocs,
+    destroy,
+    after_transaction,
 
     observe_event,
     observe_deep_event,
 
+    item_added,
+    item_updated,
+    item_popped,
+    undone,
+    redone,
+    nothing,
+
     out_of_bounds,
+    incomplete,
+    missing_dependencies,
+    gc_enabled,
+    gc_disabled,
+    no_transaction,
+    not_a_map,
+    not_a_number,
+    invalid_state_vector,
+    invalid_quill_delta,
+    delta_out_of_range,
+    iterator_closed,
+    already_exists,
+    already_loaded,
+    destroyed,
+    set,
+    unchanged,
+    lt,
+    eq,
+    gt,
+
+    v1,
+    v2,
 
 // messages types
   sync,
@@ -35,6 +67,8 @@ rustler::atoms! {
   update,
   insert,
   delete,
+  deleted,
+  format,
   retain,
   attributes,
 
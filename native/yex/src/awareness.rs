@@ -1,7 +1,10 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::error::Error;
-use crate::subscription::NifSubscription;
+use crate::subscription::{NifSubscription, SubscriptionResource};
 use crate::term_box::TermBox;
 use crate::utils::{origin_to_term, term_to_origin_binary};
 use crate::{
@@ -16,6 +19,7 @@ use yrs::{
     block::ClientID,
     sync::{Awareness, AwarenessUpdate},
     updates::{decoder::Decode, encoder::Encode},
+    Any,
 };
 
 pub type AwarenessResource = NifWrap<Awareness>;
@@ -39,6 +43,15 @@ pub struct NifAwarenessUpdateSummary {
     pub removed: Vec<ClientID>,
 }
 
+#[derive(NifMap)]
+pub struct NifAwarenessStateMeta {
+    pub state: NifAny,
+    /// Incremented by the client on every update of its state.
+    pub clock: u32,
+    /// Local time the state was last received or set, in milliseconds since the Unix epoch.
+    pub last_updated_ms: u64,
+}
+
 #[rustler::nif]
 fn awareness_new(doc: NifDoc) -> NifAwareness {
     let awareness = Awareness::new(doc.reference.0.clone());
@@ -87,6 +100,28 @@ fn awareness_get_states(awareness: NifAwareness) -> HashMap<ClientID, NifAny> {
         .collect()
 }
 
+#[rustler::nif]
+fn awareness_get_states_with_meta(
+    awareness: NifAwareness,
+) -> HashMap<ClientID, NifAwarenessStateMeta> {
+    awareness
+        .reference
+        .iter()
+        .filter_map(|(id, state)| {
+            let data = state.data?;
+            let any = serde_json::from_str::<yrs::Any>(&data).ok()?;
+            Some((
+                id,
+                NifAwarenessStateMeta {
+                    state: any.into(),
+                    clock: state.clock,
+                    last_updated_ms: state.last_updated,
+                },
+            ))
+        })
+        .collect()
+}
+
 #[rustler::nif]
 fn awareness_get_local_state(awareness: NifAwareness) -> Option<NifAny> {
     awareness
@@ -109,6 +144,31 @@ fn awareness_set_local_state(
     })
 }
 
+/// Sets one key of the local state, which starts as an empty map when unset.
+/// The state is read and written back as two steps, so a concurrent write to the local state
+/// from another process may be overwritten.
+#[rustler::nif]
+fn awareness_set_local_state_field(
+    env: Env<'_>,
+    awareness: NifAwareness,
+    key: String,
+    value: NifAny,
+) -> NifResult<Atom> {
+    let mut state = match awareness.reference.local_state::<Any>() {
+        Some(Any::Map(map)) => map.as_ref().clone(),
+        None => HashMap::new(),
+        Some(_) => return Err(rustler::Error::Term(Box::new(atoms::not_a_map()))),
+    };
+    state.insert(key, value.0);
+    ENV.set(&mut env.clone(), || {
+        awareness
+            .reference
+            .set_local_state(Any::from(state))
+            .map(|_| atoms::ok())
+            .map_err(|e| Error::from(e).into())
+    })
+}
+
 #[rustler::nif]
 fn awareness_clean_local_state(env: Env<'_>, awareness: NifAwareness) -> NifResult<Atom> {
     ENV.set(&mut env.clone(), || {
@@ -148,7 +208,7 @@ fn awareness_monitor_update(
             })
         });
     NifSubscription {
-        reference: ResourceArc::new(Mutex::new(Some(sub)).into()),
+        reference: SubscriptionResource::arc(sub),
         doc: awareness.doc.clone(),
     }
 }
@@ -184,7 +244,7 @@ fn awareness_monitor_change(
             })
         });
     NifSubscription {
-        reference: ResourceArc::new(Mutex::new(Some(sub)).into()),
+        reference: SubscriptionResource::arc(sub),
         doc: awareness.doc.clone(),
     }
 }
@@ -210,6 +270,34 @@ pub fn awareness_encode_update_v1(
     )
         .encode(env))
 }
+/// Encodes only the client states that are missing or older on the remote side,
+/// given the `client_id => clock` pairs the remote already knows about.
+#[rustler::nif]
+pub fn awareness_diff_v1(
+    env: Env<'_>,
+    awareness: NifAwareness,
+    remote_clocks: HashMap<ClientID, u32>,
+) -> NifResult<Term<'_>> {
+    let clients: Vec<ClientID> = awareness
+        .reference
+        .iter()
+        .filter(|(id, state)| {
+            !matches!(remote_clocks.get(id), Some(remote_clock) if *remote_clock >= state.clock)
+        })
+        .map(|(id, _)| id)
+        .collect();
+    let update = awareness
+        .reference
+        .update_with_clients(clients)
+        .map_err(Error::from)?;
+
+    Ok((
+        atoms::ok(),
+        SliceIntoBinary::new(update.encode_v1().as_slice()),
+    )
+        .encode(env))
+}
+
 #[rustler::nif]
 pub fn awareness_apply_update_v1(
     env: Env<'_>,
@@ -243,3 +331,34 @@ pub fn awareness_remove_states(env: Env<'_>, awareness: NifAwareness, clients: V
         }
     })
 }
+
+/// Removes the remote states not updated within `timeout_ms`, and returns their client ids.
+/// The local state is kept, as it is renewed by this client.
+#[rustler::nif]
+pub fn awareness_remove_outdated(
+    env: Env<'_>,
+    awareness: NifAwareness,
+    timeout_ms: u64,
+) -> Vec<ClientID> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let local_id = awareness.reference.client_id();
+    let outdated: Vec<ClientID> = awareness
+        .reference
+        .iter()
+        .filter(|(id, state)| {
+            *id != local_id
+                && state.data.is_some()
+                && now.saturating_sub(state.last_updated) >= timeout_ms
+        })
+        .map(|(id, _)| id)
+        .collect();
+    ENV.set(&mut env.clone(), || {
+        for client_id in &outdated {
+            awareness.reference.remove_state(*client_id);
+        }
+    });
+    outdated
+}
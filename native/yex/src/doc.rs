@@ -1,12 +1,21 @@
 // Standard library imports
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Deref;
-use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
 
 // External crates
 use rustler::{
     Atom, Binary, Encoder, Env, LocalPid, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term,
 };
-use yrs::updates::{decoder::Decode, encoder::Encode};
+use yrs::encoding::write::Write;
+use yrs::id_set::IdRange;
+use yrs::types::text::YChange;
+use yrs::types::{AsPrelim, Delta};
+use yrs::updates::{
+    decoder::Decode,
+    encoder::{Encode, Encoder, EncoderV1},
+};
 use yrs::*;
 
 use crate::event::NifSubdocsEvent;
@@ -14,20 +23,141 @@ use crate::event::NifSubdocsEvent;
 use crate::{
     atoms,
     error::Error,
-    subscription::NifSubscription,
+    shared_type::root_name,
+    subscription::{NifSubscription, SubscriptionResource},
     term_box::TermBox,
-    transaction::{ReadTransaction, TransactionResource},
+    transaction::{OpenTransactions, ReadTransaction, TransactionResource},
     utils::{origin_to_term, term_to_origin_binary},
-    wrap::{NifWrap, SliceIntoBinary},
+    wrap::SliceIntoBinary,
     xml::NifXmlFragment,
+    youtput::NifYOut,
     NifArray, NifMap, NifText, ENV,
 };
 
-pub type DocResource = NifWrap<Doc>;
+/// A handle on a document, sharing the state of the document with its other handles.
+pub struct DocResource(pub Doc, pub(crate) Arc<DocState>);
+
+impl From<Doc> for DocResource {
+    fn from(doc: Doc) -> Self {
+        let state = DocState::of(&doc);
+        DocResource(doc, state)
+    }
+}
+
+impl std::panic::RefUnwindSafe for DocResource {}
+
+impl Deref for DocResource {
+    type Target = Doc;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
 
 #[rustler::resource_impl]
 impl rustler::Resource for DocResource {}
 
+/// Identifies a document across handles: peers of one document never share a client id.
+type DocKey = (String, u64);
+
+/// State shared by every handle on the same document.
+pub struct DocState {
+    key: DocKey,
+    /// Set once the document is destroyed, after which every handle refuses transactions.
+    destroyed: AtomicBool,
+    /// Explicit transactions opened on the document and not committed yet.
+    pub(crate) transactions: OpenTransactions,
+    /// Destroy monitors, which `doc_destroy` notifies itself when the document is a root one.
+    destroy_monitors: Mutex<Vec<Weak<DestroyMonitor>>>,
+}
+
+/// A process monitoring a document being destroyed. yrs notifies the monitors of a
+/// sub-document, as it only destroys documents that have a parent.
+pub struct DestroyMonitor {
+    pid: LocalPid,
+    metadata: TermBox,
+}
+
+impl DestroyMonitor {
+    fn notify<'a>(&self, env: Env<'a>, origin: Term<'a>) {
+        let _ = env.send(
+            &self.pid,
+            (atoms::destroy(), origin, self.metadata.get(env)),
+        );
+    }
+}
+
+static DOC_STATES: Mutex<BTreeMap<DocKey, Weak<DocState>>> = Mutex::new(BTreeMap::new());
+
+fn doc_states() -> MutexGuard<'static, BTreeMap<DocKey, Weak<DocState>>> {
+    DOC_STATES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl DocState {
+    /// The state of `doc`, shared with the other live handles on it.
+    fn of(doc: &Doc) -> Arc<DocState> {
+        let key = (doc.guid().to_string(), doc.client_id());
+        let mut states = doc_states();
+        if let Some(state) = states.get(&key).and_then(Weak::upgrade) {
+            return state;
+        }
+        let state = Arc::new(DocState {
+            key: key.clone(),
+            destroyed: AtomicBool::new(false),
+            transactions: OpenTransactions::default(),
+            destroy_monitors: Mutex::new(Vec::new()),
+        });
+        states.insert(key, Arc::downgrade(&state));
+        state
+    }
+
+    /// Keeps `monitor` for as long as the observer holding it is subscribed.
+    fn watch_destroy(&self, monitor: &Arc<DestroyMonitor>) {
+        if let Ok(mut monitors) = self.destroy_monitors.lock() {
+            monitors.retain(|monitor| monitor.strong_count() > 0);
+            monitors.push(Arc::downgrade(monitor));
+        }
+    }
+
+    fn take_destroy_monitors(&self) -> Vec<Arc<DestroyMonitor>> {
+        match self.destroy_monitors.lock() {
+            Ok(mut monitors) => monitors
+                .drain(..)
+                .filter_map(|monitor| monitor.upgrade())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Marks the documents destroyed for every handle on them. Their keys are released, as a
+    /// destroyed sub-document is replaced in its parent by one with the same guid and client id.
+    fn destroy_all(docs: &[Doc]) {
+        let mut states = doc_states();
+        let destroyed: Vec<Arc<DocState>> = docs
+            .iter()
+            .filter_map(|doc| states.remove(&(doc.guid().to_string(), doc.client_id())))
+            .filter_map(|state| state.upgrade())
+            .collect();
+        // dropping a state locks the registry again
+        drop(states);
+        for state in destroyed {
+            state.destroyed.store(true, Ordering::Release);
+        }
+    }
+}
+
+impl Drop for DocState {
+    fn drop(&mut self) {
+        let mut states = doc_states();
+        if states
+            .get(&self.key)
+            .is_some_and(|state| state.strong_count() == 0)
+        {
+            states.remove(&self.key);
+        }
+    }
+}
+
 #[derive(NifUnitEnum)]
 pub enum NifOffsetKind {
     Bytes,
@@ -43,6 +173,14 @@ impl From<OffsetKind> for NifOffsetKind {
     }
 }
 
+#[derive(NifUnitEnum)]
+pub enum NifRootKind {
+    Text,
+    Array,
+    Map,
+    XmlFragment,
+}
+
 #[derive(NifStruct)]
 #[module = "Yex.Doc.Options"]
 pub struct NifOptions {
@@ -159,6 +297,7 @@ impl NifDoc {
     where
         F: FnOnce(&mut TransactionMut<'_>) -> NifResult<T>,
     {
+        self.ensure_alive()?;
         ENV.set(&mut env.clone(), || match current_transaction {
             Some(txn) => {
                 if let Ok(mut txn_guard) = txn.0.write() {
@@ -174,6 +313,38 @@ impl NifDoc {
         })
     }
 
+    /// Like `mutably`, but a transaction opened here is tagged with `origin` unless it is nil.
+    /// Within an explicit transaction the origin of that transaction applies instead.
+    pub fn mutably_with_origin<F, T>(
+        &self,
+        env: Env<'_>,
+        current_transaction: Option<ResourceArc<TransactionResource>>,
+        origin: Term<'_>,
+        f: F,
+    ) -> NifResult<T>
+    where
+        F: FnOnce(&mut TransactionMut<'_>) -> NifResult<T>,
+    {
+        match term_to_origin_binary(origin) {
+            Some(origin) if current_transaction.is_none() => ENV.set(&mut env.clone(), || {
+                self.ensure_alive()?;
+                let mut txn =
+                    yrs::Transact::try_transact_mut_with(&self.reference.0, origin.as_slice())
+                        .map_err(Error::from)?;
+                f(&mut txn)
+            }),
+            _ => self.mutably(env, current_transaction, f),
+        }
+    }
+
+    /// Fails with `{:error, :destroyed}` once the document was destroyed, through any handle.
+    pub fn ensure_alive(&self) -> NifResult<()> {
+        if self.reference.1.destroyed.load(Ordering::Acquire) {
+            return Err(rustler::Error::Term(Box::new(atoms::destroyed())));
+        }
+        Ok(())
+    }
+
     pub fn readonly<F, T>(
         &self,
         current_transaction: Option<ResourceArc<TransactionResource>>,
@@ -182,6 +353,7 @@ impl NifDoc {
     where
         F: FnOnce(&ReadTransaction) -> NifResult<T>,
     {
+        self.ensure_alive()?;
         match current_transaction {
             Some(txn) => {
                 if let Ok(txn_guard) = txn.0.read() {
@@ -221,6 +393,7 @@ impl DocOperations for NifDoc {
     where
         F: FnOnce(&Transaction) -> NifResult<T>,
     {
+        self.ensure_alive()?;
         let txn = yrs::Transact::try_transact(&self.reference.0).map_err(Error::from)?;
         f(&txn)
     }
@@ -229,6 +402,7 @@ impl DocOperations for NifDoc {
     where
         F: FnOnce(&mut TransactionMut) -> NifResult<T>,
     {
+        self.ensure_alive()?;
         let mut txn = yrs::Transact::try_transact_mut(&self.reference.0).map_err(Error::from)?;
         f(&mut txn)
     }
@@ -245,23 +419,272 @@ fn doc_with_options(option: NifOptions) -> NifDoc {
 }
 
 #[rustler::nif]
-fn doc_get_or_insert_text(env: Env<'_>, doc: NifDoc, name: &str) -> NifText {
-    ENV.set(&mut env.clone(), || doc.get_or_insert_text(name))
+fn doc_new_pair(client_id_a: u64, client_id_b: u64) -> NifResult<(NifDoc, NifDoc)> {
+    // two peers with one client id would produce colliding updates once merged
+    if client_id_a == client_id_b {
+        return Err(rustler::Error::BadArg);
+    }
+    Ok((
+        NifDoc::with_worker_pid(Doc::with_client_id(client_id_a), None),
+        NifDoc::with_worker_pid(Doc::with_client_id(client_id_b), None),
+    ))
+}
+
+#[rustler::nif]
+fn doc_get_or_insert_text(env: Env<'_>, doc: NifDoc, name: &str) -> NifResult<NifText> {
+    doc.ensure_alive()?;
+    Ok(ENV.set(&mut env.clone(), || doc.get_or_insert_text(name)))
+}
+
+#[rustler::nif]
+fn doc_get_or_insert_array(env: Env<'_>, doc: NifDoc, name: &str) -> NifResult<NifArray> {
+    doc.ensure_alive()?;
+    Ok(ENV.set(&mut env.clone(), || doc.get_or_insert_array(name)))
+}
+
+#[rustler::nif]
+fn doc_get_or_insert_map(env: Env<'_>, doc: NifDoc, name: &str) -> NifResult<NifMap> {
+    doc.ensure_alive()?;
+    Ok(ENV.set(&mut env.clone(), || doc.get_or_insert_map(name)))
+}
+
+#[rustler::nif]
+fn doc_get_or_insert_xml_fragment(
+    env: Env<'_>,
+    doc: NifDoc,
+    name: &str,
+) -> NifResult<NifXmlFragment> {
+    doc.ensure_alive()?;
+    Ok(ENV.set(&mut env.clone(), || doc.get_or_insert_xml_fragment(name)))
+}
+
+/// Creates all listed root types within a single transaction.
+#[rustler::nif]
+fn doc_init_roots(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    specs: Vec<(String, NifRootKind)>,
+) -> NifResult<HashMap<String, NifYOut>> {
+    doc.mutably(env, current_transaction, |txn| {
+        Ok(specs
+            .into_iter()
+            .map(|(name, kind)| {
+                let name_ref = name.as_str();
+                let root = match kind {
+                    NifRootKind::Text => {
+                        NifYOut::YText(NifText::new(doc.clone(), txn.get_or_insert_text(name_ref)))
+                    }
+                    NifRootKind::Array => NifYOut::YArray(NifArray::new(
+                        doc.clone(),
+                        txn.get_or_insert_array(name_ref),
+                    )),
+                    NifRootKind::Map => {
+                        NifYOut::YMap(NifMap::new(doc.clone(), txn.get_or_insert_map(name_ref)))
+                    }
+                    NifRootKind::XmlFragment => NifYOut::YXmlFragment(NifXmlFragment::new(
+                        doc.clone(),
+                        txn.get_or_insert_xml_fragment(name_ref),
+                    )),
+                };
+                (name, root)
+            })
+            .collect())
+    })
+}
+
+/// Moves the content of root `from` into a new root `to`, leaving `from` empty.
+/// Root types cannot be removed, and the content is re-created from prelims, so it gets new ids.
+#[rustler::nif]
+fn doc_rename_root(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    from: String,
+    to: String,
+    kind: NifRootKind,
+) -> NifResult<NifYOut> {
+    doc.mutably(env, current_transaction, |txn| {
+        if from == to {
+            return Err(rustler::Error::BadArg);
+        }
+        if txn.root_refs().any(|(name, _)| name == to.as_str()) {
+            return Err(rustler::Error::Term(Box::new(atoms::already_exists())));
+        }
+        let root = match kind {
+            NifRootKind::Text => {
+                let old = txn.get_or_insert_text(from.as_str());
+                let delta: Vec<Delta<In>> = old
+                    .diff(txn, YChange::identity)
+                    .into_iter()
+                    .map(|chunk| Delta::Inserted(chunk.insert.as_prelim(txn), chunk.attributes))
+                    .collect();
+                let len = old.len(txn);
+                old.remove_range(txn, 0, len);
+                let new = txn.get_or_insert_text(to.as_str());
+                new.apply_delta(txn, delta);
+                NifYOut::YText(NifText::new(doc.clone(), new))
+            }
+            NifRootKind::Array => {
+                let old = txn.get_or_insert_array(from.as_str());
+                let values: Vec<In> = old.iter(txn).map(|value| value.as_prelim(txn)).collect();
+                let len = old.len(txn);
+                old.remove_range(txn, 0, len);
+                let new = txn.get_or_insert_array(to.as_str());
+                for value in values {
+                    new.push_back(txn, value);
+                }
+                NifYOut::YArray(NifArray::new(doc.clone(), new))
+            }
+            NifRootKind::Map => {
+                let old = txn.get_or_insert_map(from.as_str());
+                let entries: Vec<(String, In)> = old
+                    .iter(txn)
+                    .map(|(key, value)| (key.to_string(), value.as_prelim(txn)))
+                    .collect();
+                old.clear(txn);
+                let new = txn.get_or_insert_map(to.as_str());
+                for (key, value) in entries {
+                    new.insert(txn, key, value);
+                }
+                NifYOut::YMap(NifMap::new(doc.clone(), new))
+            }
+            NifRootKind::XmlFragment => {
+                let old = txn.get_or_insert_xml_fragment(from.as_str());
+                let children: Vec<XmlIn> = old
+                    .children(txn)
+                    .map(|child| child.as_prelim(txn))
+                    .collect();
+                let len = old.len(txn);
+                old.remove_range(txn, 0, len);
+                let new = txn.get_or_insert_xml_fragment(to.as_str());
+                for child in children {
+                    new.push_back(txn, child);
+                }
+                NifYOut::YXmlFragment(NifXmlFragment::new(doc.clone(), new))
+            }
+        };
+        Ok(root)
+    })
+}
+
+#[rustler::nif]
+fn doc_has_type(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    name: &str,
+) -> NifResult<bool> {
+    doc.readonly(current_transaction, |txn| {
+        Ok(txn.root_refs().any(|(root_name, _)| root_name == name))
+    })
+}
+
+/// Roots are wrapped according to the type they were created with; roots only known from
+/// remote updates and never accessed locally come back as `Yex.UndefinedRef`.
+#[rustler::nif]
+fn doc_get_shared_types(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<HashMap<String, NifYOut>> {
+    doc.readonly(current_transaction, |txn| {
+        Ok(txn
+            .root_refs()
+            .map(|(name, value)| (name.to_string(), NifYOut::from_native(value, doc.clone())))
+            .collect())
+    })
+}
+
+#[rustler::nif]
+fn doc_subdoc_by_guid(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    guid: &str,
+) -> NifResult<Option<NifDoc>> {
+    let worker_pid = doc.worker_pid;
+    doc.readonly(current_transaction, |txn| {
+        Ok(txn
+            .subdocs()
+            .find(|subdoc| subdoc.guid().as_ref() == guid)
+            .map(|subdoc| NifDoc::with_worker_pid(subdoc.clone(), worker_pid)))
+    })
 }
 
+/// Sub-documents held by this document, keyed by guid.
 #[rustler::nif]
-fn doc_get_or_insert_array(env: Env<'_>, doc: NifDoc, name: &str) -> NifArray {
-    ENV.set(&mut env.clone(), || doc.get_or_insert_array(name))
+fn doc_get_subdocs(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<HashMap<String, NifDoc>> {
+    let worker_pid = doc.worker_pid;
+    doc.readonly(current_transaction, |txn| {
+        Ok(txn
+            .subdocs()
+            .map(|subdoc| {
+                let guid = subdoc.guid().to_string();
+                (guid, NifDoc::with_worker_pid(subdoc.clone(), worker_pid))
+            })
+            .collect())
+    })
 }
 
+/// False while parts of applied updates are held back waiting for missing dependencies,
+/// meaning reads only see the integrated portion of what was received.
 #[rustler::nif]
-fn doc_get_or_insert_map(env: Env<'_>, doc: NifDoc, name: &str) -> NifMap {
-    ENV.set(&mut env.clone(), || doc.get_or_insert_map(name))
+fn doc_is_consistent(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<bool> {
+    doc.readonly(current_transaction, |txn| Ok(!txn.has_missing_updates()))
 }
 
+/// Approximate size of the document, taken as the length of its full v1 update.
+/// The store itself is not walked, so this is a lower bound on the memory in use.
 #[rustler::nif]
-fn doc_get_or_insert_xml_fragment(env: Env<'_>, doc: NifDoc, name: &str) -> NifXmlFragment {
-    ENV.set(&mut env.clone(), || doc.get_or_insert_xml_fragment(name))
+fn doc_memory_estimate(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<u64> {
+    doc.readonly(current_transaction, |txn| {
+        Ok(txn.encode_diff_v1(&StateVector::default()).len() as u64)
+    })
+}
+
+#[rustler::nif]
+fn doc_snapshot(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Term<'_>> {
+    let snapshot = doc.readonly(current_transaction, |txn| Ok(txn.snapshot().encode_v1()))?;
+    Ok((atoms::ok(), SliceIntoBinary::new(snapshot.as_slice())).encode(env))
+}
+
+/// Commits only collect what was deleted in that transaction, so this pass walks the whole
+/// delete set to also collect tombstones left behind by earlier transactions.
+#[rustler::nif]
+fn doc_gc(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Atom> {
+    if doc.skip_gc() {
+        return Err(rustler::Error::Term(Box::new(atoms::gc_disabled())));
+    }
+    doc.mutably(env, current_transaction, |txn| {
+        txn.gc(None);
+        Ok(atoms::ok())
+    })
+}
+
+#[rustler::nif]
+fn doc_current_origin(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Term<'_>> {
+    doc.readonly(current_transaction, |txn| {
+        Ok(origin_to_term(&mut env.clone(), txn.origin()))
+    })
 }
 
 #[rustler::nif]
@@ -269,28 +692,57 @@ fn doc_begin_transaction(
     doc: NifDoc,
     origin: Term<'_>,
 ) -> NifResult<ResourceArc<TransactionResource>> {
-    if let Some(origin) = term_to_origin_binary(origin) {
-        let txn: TransactionMut =
-            yrs::Transact::try_transact_mut_with(&doc.reference.0, origin.as_slice())
-                .map_err(Error::from)?;
-        let txn: TransactionMut<'static> = unsafe { std::mem::transmute(txn) };
-
-        Ok(TransactionResource(RwLock::new(Some(txn))).into())
-    } else {
-        let txn: TransactionMut =
-            yrs::Transact::try_transact_mut(&doc.reference.0).map_err(Error::from)?;
-        let txn: TransactionMut<'static> = unsafe { std::mem::transmute(txn) };
-        Ok(TransactionResource(RwLock::new(Some(txn))).into())
+    doc.ensure_alive()?;
+    let txn = match term_to_origin_binary(origin) {
+        Some(origin) => yrs::Transact::try_transact_mut_with(&doc.reference.0, origin.as_slice()),
+        None => yrs::Transact::try_transact_mut(&doc.reference.0),
     }
+    .map_err(Error::from)?;
+    // The resource keeps a handle on the document, so the store outlives the transaction.
+    let txn: TransactionMut<'static> = unsafe { std::mem::transmute(txn) };
+    let resource = TransactionResource::new(&doc.reference.0, txn);
+    doc.reference.1.transactions.register(&resource);
+    Ok(ResourceArc::new(resource))
 }
 
 #[rustler::nif]
 fn commit_transaction(env: Env<'_>, current_transaction: ResourceArc<TransactionResource>) {
-    ENV.set(&mut env.clone(), || {
-        if let Ok(mut txn) = current_transaction.0.write() {
-            *txn = None;
-        }
-    })
+    current_transaction.commit(env);
+}
+
+/// Commits every explicit transaction still open on the document, through any of its handles.
+#[rustler::nif]
+fn doc_force_commit_all(env: Env<'_>, doc: NifDoc) -> usize {
+    doc.reference.1.transactions.commit_all(env)
+}
+
+/// Write volume of a committed transaction, counted in clock units
+/// (characters for text, elements for arrays, one per map value).
+#[derive(rustler::NifMap)]
+pub struct NifUpdateStats {
+    pub added: u32,
+    pub deleted: u32,
+}
+
+impl NifUpdateStats {
+    fn from_transaction(txn: &TransactionMut) -> Self {
+        let before = txn.before_state();
+        let added = txn
+            .after_state()
+            .iter()
+            .map(|(client, clock)| clock - before.get(client))
+            .sum();
+        let mut delete_set = txn.delete_set().clone();
+        delete_set.squash();
+        let deleted = delete_set
+            .iter()
+            .map(|(_, range)| match range {
+                IdRange::Continuous(range) => range.end - range.start,
+                IdRange::Fragmented(ranges) => ranges.iter().map(|r| r.end - r.start).sum(),
+            })
+            .sum();
+        NifUpdateStats { added, deleted }
+    }
 }
 
 #[rustler::nif]
@@ -298,19 +750,73 @@ fn doc_monitor_update_v1(
     doc: NifDoc,
     pid: LocalPid,
     metadata: Term<'_>,
+    with_stats: bool,
 ) -> NifResult<(Atom, NifSubscription)> {
+    doc.ensure_alive()?;
     let metadata = TermBox::new(metadata);
 
     doc.observe_update_v1(move |txn, event| {
         ENV.with(|env| {
             let metadata = metadata.get(*env);
+            let update = SliceIntoBinary::new(event.update.as_slice());
+            let origin = origin_to_term(env, txn.origin());
+            let _ = if with_stats {
+                let stats = NifUpdateStats::from_transaction(txn);
+                env.send(&pid, (atoms::update_v1(), update, origin, metadata, stats))
+            } else {
+                env.send(&pid, (atoms::update_v1(), update, origin, metadata))
+            };
+        })
+    })
+    .map(|sub| {
+        (
+            atoms::ok(),
+            NifSubscription {
+                reference: SubscriptionResource::arc(sub),
+                doc: doc.clone(),
+            },
+        )
+    })
+    .map_err(|e| Error::from(e).into())
+}
+
+/// Sorted names of the root types touched by a committed transaction.
+fn changed_root_names(txn: &TransactionMut<'_>) -> Vec<String> {
+    let mut roots: Vec<String> = txn
+        .changed_parent_types()
+        .iter()
+        .filter_map(|branch| root_name(*branch))
+        .map(|name| name.to_string())
+        .collect();
+    roots.sort_unstable();
+    roots.dedup();
+    roots
+}
+
+/// Sends `{:after_transaction, root_names, before_sv, after_sv, origin, metadata}` once per
+/// committed transaction, where `root_names` lists the root types it changed.
+#[rustler::nif]
+fn doc_observe_after_transaction(
+    doc: NifDoc,
+    pid: LocalPid,
+    metadata: Term<'_>,
+) -> NifResult<(Atom, NifSubscription)> {
+    doc.ensure_alive()?;
+    let metadata = TermBox::new(metadata);
+    doc.observe_after_transaction(move |txn| {
+        ENV.with(|env| {
+            let roots = changed_root_names(txn);
+            let before = txn.before_state().encode_v1();
+            let after = txn.after_state().encode_v1();
             let _ = env.send(
                 &pid,
                 (
-                    atoms::update_v1(),
-                    SliceIntoBinary::new(event.update.as_slice()),
+                    atoms::after_transaction(),
+                    roots,
+                    SliceIntoBinary::new(before.as_slice()),
+                    SliceIntoBinary::new(after.as_slice()),
                     origin_to_term(env, txn.origin()),
-                    metadata,
+                    metadata.get(*env),
                 ),
             );
         })
@@ -319,7 +825,7 @@ fn doc_monitor_update_v1(
         (
             atoms::ok(),
             NifSubscription {
-                reference: ResourceArc::new(Mutex::new(Some(sub)).into()),
+                reference: SubscriptionResource::arc(sub),
                 doc: doc.clone(),
             },
         )
@@ -332,6 +838,7 @@ fn doc_monitor_update_v2(
     pid: LocalPid,
     metadata: Term<'_>,
 ) -> NifResult<(Atom, NifSubscription)> {
+    doc.ensure_alive()?;
     let metadata = TermBox::new(metadata);
     doc.observe_update_v2(move |txn, event| {
         ENV.with(|env| {
@@ -351,7 +858,7 @@ fn doc_monitor_update_v2(
         (
             atoms::ok(),
             NifSubscription {
-                reference: ResourceArc::new(Mutex::new(Some(sub)).into()),
+                reference: SubscriptionResource::arc(sub),
                 doc: doc.clone(),
             },
         )
@@ -393,18 +900,135 @@ fn apply_update_v2(
     })
 }
 
+/// Applies `update` in its own transaction and returns the names of the root types it changed.
+/// Those are only known once the transaction commits, so it can't join an open transaction.
 #[rustler::nif]
-fn merge_updates_v1<'a>(env: Env<'a>, updates: Vec<Binary<'a>>) -> NifResult<Term<'a>> {
-    let merged =
-        yrs::merge_updates_v1(updates.iter().map(Binary::as_slice)).map_err(Error::from)?;
-    Ok((atoms::ok(), SliceIntoBinary::new(merged.as_slice())).encode(env))
+fn apply_update_v1_returning_summary(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    update: Binary,
+) -> NifResult<(Atom, Vec<String>)> {
+    if current_transaction.is_some() {
+        return Err(rustler::Error::Term(Box::new(atoms::transaction_busy())));
+    }
+    let update = Update::decode_v1(update.as_slice()).map_err(Error::from)?;
+
+    let changed = Arc::new(Mutex::new(Vec::new()));
+    let sub = {
+        let changed = changed.clone();
+        doc.observe_after_transaction(move |txn| {
+            if let Ok(mut changed) = changed.lock() {
+                *changed = changed_root_names(txn);
+            }
+        })
+        .map_err(Error::from)?
+    };
+    let applied = doc.mutably(env, None, |txn| {
+        txn.apply_update(update).map_err(|e| Error::from(e).into())
+    });
+    drop(sub);
+    applied?;
+
+    let changed = match changed.lock() {
+        Ok(mut guard) => std::mem::take(&mut *guard),
+        Err(poisoned) => std::mem::take(&mut *poisoned.into_inner()),
+    };
+    Ok((atoms::ok(), changed))
 }
 
+/// Applies `update` to a throwaway copy of the document, leaving the document itself untouched.
+/// The copy is built from the full encoded state, so the cost grows with the document size.
 #[rustler::nif]
-fn merge_updates_v2<'a>(env: Env<'a>, updates: Vec<Binary<'a>>) -> NifResult<Term<'a>> {
-    let merged =
-        yrs::merge_updates_v2(updates.iter().map(Binary::as_slice)).map_err(Error::from)?;
-    Ok((atoms::ok(), SliceIntoBinary::new(merged.as_slice())).encode(env))
+fn validate_update_v1(
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    update: Binary,
+) -> NifResult<Atom> {
+    let update = Update::decode_v1(update.as_slice()).map_err(Error::from)?;
+    let state = doc.readonly(current_transaction, |txn| {
+        Ok(txn.encode_state_as_update_v1(&StateVector::default()))
+    })?;
+
+    let fork = Doc::with_options(doc.options().clone());
+    let mut txn = fork.transact_mut();
+    txn.apply_update(Update::decode_v1(state.as_slice()).map_err(Error::from)?)
+        .map_err(Error::from)?;
+    txn.apply_update(update).map_err(Error::from)?;
+
+    if txn.has_missing_updates() {
+        return Err(rustler::Error::Term(
+            Box::new(atoms::missing_dependencies()),
+        ));
+    }
+    Ok(atoms::ok())
+}
+
+/// Encodes the state vector and delete set of the document with clients and ranges sorted.
+/// Two documents that integrated the same operations produce the same bytes, whatever the
+/// order they received them in, which makes the output suitable for hashing. The content
+/// itself is not included: every operation id maps to exactly one piece of content, so the
+/// ids known and the ids deleted already identify the state.
+#[rustler::nif]
+fn doc_canonical_state(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Term<'_>> {
+    let snapshot = doc.readonly(current_transaction, |txn| Ok(txn.snapshot()))?;
+
+    let mut clocks: Vec<(u64, u32)> = snapshot
+        .state_map
+        .iter()
+        .map(|(client, clock)| (*client, *clock))
+        .collect();
+    clocks.sort_unstable();
+
+    let mut delete_set = snapshot.delete_set;
+    delete_set.squash();
+    let mut deleted: Vec<(u64, Vec<std::ops::Range<u32>>)> = delete_set
+        .iter()
+        .map(|(client, range)| {
+            let ranges = match range {
+                IdRange::Continuous(range) => vec![range.clone()],
+                IdRange::Fragmented(ranges) => ranges.clone(),
+            };
+            (*client, ranges)
+        })
+        .collect();
+    deleted.sort_unstable_by_key(|(client, _)| *client);
+
+    let mut buf = Vec::new();
+    buf.write_var(clocks.len());
+    for (client, clock) in clocks {
+        buf.write_var(client);
+        buf.write_var(clock);
+    }
+    buf.write_var(deleted.len());
+    for (client, ranges) in deleted {
+        buf.write_var(client);
+        buf.write_var(ranges.len());
+        for range in ranges {
+            buf.write_var(range.start);
+            buf.write_var(range.end - range.start);
+        }
+    }
+
+    Ok((atoms::ok(), SliceIntoBinary::new(buf.as_slice())).encode(env))
+}
+
+/// Update encodings this crate can produce and apply, most preferred first.
+fn supported_update_formats() -> [Atom; 2] {
+    [atoms::v2(), atoms::v1()]
+}
+
+#[rustler::nif]
+fn negotiate_update_format(supported: Vec<Atom>) -> NifResult<(Atom, Atom)> {
+    supported_update_formats()
+        .into_iter()
+        .find(|format| supported.contains(format))
+        .map(|format| (atoms::ok(), format))
+        .ok_or(rustler::Error::Atom("error"))
 }
 
 #[rustler::nif]
@@ -436,6 +1060,47 @@ fn encode_state_as_update_v1<'a>(
         .map(|vec| (atoms::ok(), SliceIntoBinary::new(vec.as_slice())).encode(env))
 }
 
+/// Restoring a snapshot needs the items deleted since it was taken, which garbage collection
+/// would have dropped, so documents that do not skip gc are rejected up front.
+#[rustler::nif]
+fn encode_state_as_update_from_snapshot_v1<'a>(
+    env: Env<'a>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    snapshot: Binary,
+    state_vector: Option<Binary>,
+) -> NifResult<Term<'a>> {
+    if !doc.skip_gc() {
+        return Err(rustler::Error::Term(Box::new(atoms::gc_enabled())));
+    }
+    let snapshot = Snapshot::decode_v1(snapshot.as_slice()).map_err(Error::from)?;
+    let sv = state_vector
+        .map(|vector| StateVector::decode_v1(vector.as_slice()).map_err(Error::from))
+        .transpose()?;
+
+    let update = doc.readonly(current_transaction, |txn| {
+        let mut encoder = EncoderV1::new();
+        txn.encode_state_from_snapshot(&snapshot, &mut encoder)
+            .map_err(|e| Error::Message(e.to_string()))?;
+        Ok(encoder.to_vec())
+    })?;
+    let update = match sv {
+        // the snapshot state only exists as an update, so diff it on a scratch document
+        Some(sv) => {
+            let scratch = Doc::with_options(Options {
+                skip_gc: true,
+                ..Options::default()
+            });
+            let mut txn = scratch.transact_mut();
+            txn.apply_update(Update::decode_v1(update.as_slice()).map_err(Error::from)?)
+                .map_err(Error::from)?;
+            txn.encode_diff_v1(&sv)
+        }
+        None => update,
+    };
+    Ok((atoms::ok(), SliceIntoBinary::new(update.as_slice())).encode(env))
+}
+
 /// Single read transaction for sync step1 response: missing diff vs remote SV + local encoded SV.
 #[rustler::nif]
 fn encode_diff_and_state_vector_v1<'a>(
@@ -493,6 +1158,7 @@ fn doc_monitor_subdocs(
     pid: LocalPid,
     metadata: Term<'_>,
 ) -> NifResult<(Atom, NifSubscription)> {
+    doc.ensure_alive()?;
     let metadata = TermBox::new(metadata);
     let worker_pid = doc.worker_pid;
     doc.observe_subdocs(move |txn, event: &SubdocsEvent| {
@@ -514,7 +1180,34 @@ fn doc_monitor_subdocs(
         (
             atoms::ok(),
             NifSubscription {
-                reference: ResourceArc::new(Mutex::new(Some(sub)).into()),
+                reference: SubscriptionResource::arc(sub),
+                doc: doc.clone(),
+            },
+        )
+    })
+    .map_err(|e| Error::from(e).into())
+}
+
+#[rustler::nif]
+fn doc_observe_destroy(
+    doc: NifDoc,
+    pid: LocalPid,
+    metadata: Term<'_>,
+) -> NifResult<(Atom, NifSubscription)> {
+    doc.ensure_alive()?;
+    let monitor = Arc::new(DestroyMonitor {
+        pid,
+        metadata: TermBox::new(metadata),
+    });
+    doc.reference.1.watch_destroy(&monitor);
+    doc.observe_destroy(move |txn, _doc| {
+        ENV.with(|env| monitor.notify(*env, origin_to_term(env, txn.origin())));
+    })
+    .map(|sub| {
+        (
+            atoms::ok(),
+            NifSubscription {
+                reference: SubscriptionResource::arc(sub),
                 doc: doc.clone(),
             },
         )
@@ -554,6 +1247,119 @@ fn doc_should_load(doc: NifDoc) -> bool {
     doc.should_load()
 }
 
+/// Root documents are always loaded. A sub-document is loaded once `load` was requested
+/// for it, either explicitly or because it was created with `auto_load`.
+#[rustler::nif]
+fn doc_is_loaded(doc: NifDoc) -> bool {
+    doc.parent_doc().is_none() || doc.should_load()
+}
+
+/// Requests a sub-document to be loaded, flipping its `should_load` flag and reporting it in the
+/// `loaded` set of the parent's subdocs event. `current_transaction` belongs to the parent.
+/// Root documents are always loaded, so this is a no-op for them.
+#[rustler::nif]
+fn doc_load(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Atom> {
+    load_subdoc(env, &doc, current_transaction)?;
+    Ok(atoms::ok())
+}
+
+/// Sets the `should_load` flag of a sub-document. Loading cannot be revoked, so clearing
+/// the flag of a document that is already loaded fails with `{:error, :already_loaded}`.
+#[rustler::nif]
+fn doc_set_should_load(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    should_load: bool,
+) -> NifResult<Atom> {
+    if should_load {
+        load_subdoc(env, &doc, current_transaction)?;
+    } else if doc.should_load() {
+        return Err(rustler::Error::Term(Box::new(atoms::already_loaded())));
+    }
+    Ok(atoms::ok())
+}
+
+fn load_subdoc(
+    env: Env<'_>,
+    doc: &NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<()> {
+    let Some(parent) = doc.parent_doc() else {
+        return Ok(());
+    };
+    let parent = NifDoc::with_worker_pid(parent, doc.worker_pid);
+    parent.mutably(env, current_transaction, |txn| {
+        doc.load(txn);
+        Ok(())
+    })
+}
+
+/// `doc` and its sub-documents, recursively.
+fn with_subdocs(doc: &Doc) -> NifResult<Vec<Doc>> {
+    let mut docs = vec![doc.clone()];
+    let mut next = 0;
+    while next < docs.len() {
+        let txn = docs[next].try_transact().map_err(Error::from)?;
+        let subdocs: Vec<Doc> = txn.subdocs().cloned().collect();
+        drop(txn);
+        docs.extend(subdocs);
+        next += 1;
+    }
+    Ok(docs)
+}
+
+/// Destroys the document and its sub-documents, after which every handle on them fails with
+/// `{:error, :destroyed}`. A sub-document is replaced in its parent by an unloaded placeholder,
+/// so `current_transaction` belongs to the parent, and yrs notifies its destroy observers and
+/// drops every observer registered on it.
+#[rustler::nif]
+fn doc_destroy(
+    env: Env<'_>,
+    doc: NifDoc,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Atom> {
+    doc.ensure_alive()?;
+    let destroyed = with_subdocs(&doc)?;
+    let parent = doc.parent_doc();
+    let is_subdoc = parent.is_some();
+    // Marked within the transaction, before the parent reports the placeholder that takes the
+    // keys of the destroyed documents.
+    let destroy = |txn: &mut TransactionMut<'_>| -> NifResult<()> {
+        if is_subdoc {
+            doc.destroy(txn);
+        } else {
+            // yrs hands a destroyed document over to the transaction of its parent, which a
+            // root document does not have: its sub-documents are destroyed from its own.
+            let subdocs: Vec<Doc> = txn.subdocs().cloned().collect();
+            for subdoc in subdocs {
+                subdoc.destroy(txn);
+            }
+        }
+        DocState::destroy_all(&destroyed);
+        doc.reference.1.destroyed.store(true, Ordering::Release);
+        Ok(())
+    };
+    match parent {
+        Some(parent) => {
+            let parent = NifDoc::with_worker_pid(parent, doc.worker_pid);
+            parent.mutably(env, current_transaction, destroy)?;
+        }
+        None => {
+            doc.mutably(env, None, destroy)?;
+            let origin = origin_to_term(&mut env.clone(), None);
+            for monitor in doc.reference.1.take_destroy_monitors() {
+                monitor.notify(env, origin);
+            }
+        }
+    }
+    Ok(atoms::ok())
+}
+
 #[rustler::nif]
 fn doc_offset_kind(doc: NifDoc) -> NifOffsetKind {
     doc.offset_kind().into()
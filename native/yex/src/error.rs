@@ -1,8 +1,9 @@
 use crate::atoms;
+use rustler::Encoder as _;
 
 #[derive(Debug)]
 pub enum Error {
-    Transaction,
+    Transaction(yrs::TransactionAcqError),
     Update(yrs::error::UpdateError),
     Encoding(yrs::encoding::read::Error),
     Awareness(yrs::sync::awareness::Error),
@@ -13,7 +14,10 @@ pub enum Error {
 impl rustler::Encoder for Error {
     fn encode<'a>(&self, env: rustler::Env<'a>) -> rustler::Term<'a> {
         match self {
-            Error::Transaction => atoms::transaction_acq_error().encode(env),
+            Error::Transaction(error) if is_retriable(error) => {
+                (atoms::error(), atoms::transaction_busy()).encode(env)
+            }
+            Error::Transaction(_) => atoms::transaction_acq_error().encode(env),
             Error::Encoding(error) => (
                 atoms::error(),
                 (atoms::encoding_exception(), error.to_string()),
@@ -22,7 +26,26 @@ impl rustler::Encoder for Error {
             Error::Update(error) => (atoms::error(), error.to_string()).encode(env),
             Error::Awareness(error) => (atoms::error(), error.to_string()).encode(env),
             Error::Message(error) => (atoms::error(), error).encode(env),
-            Error::Rustler(_) => panic!("RustlerError not supported"),
+            Error::Rustler(error) => encode_rustler_error(env, error),
+        }
+    }
+}
+
+/// Encodes a rustler error as the value a NIF returning it produces. Errors meant to be
+/// raised can't be raised from here, so they are returned as `{:error, reason}` instead.
+fn encode_rustler_error<'a>(env: rustler::Env<'a>, error: &rustler::Error) -> rustler::Term<'a> {
+    match error {
+        rustler::Error::BadArg => (atoms::error(), rustler::types::atom::badarg()).encode(env),
+        rustler::Error::Atom(name) => match rustler::Atom::from_str(env, name) {
+            Ok(atom) => atom.encode(env),
+            Err(_) => (atoms::error(), "internal error").encode(env),
+        },
+        rustler::Error::RaiseAtom(name) => match rustler::Atom::from_str(env, name) {
+            Ok(atom) => (atoms::error(), atom).encode(env),
+            Err(_) => (atoms::error(), "internal error").encode(env),
+        },
+        rustler::Error::Term(term) | rustler::Error::RaiseTerm(term) => {
+            (atoms::error(), term.encode(env)).encode(env)
         }
     }
 }
@@ -48,15 +71,26 @@ impl From<rustler::Error> for Error {
     }
 }
 impl From<yrs::TransactionAcqError> for Error {
-    fn from(_: yrs::TransactionAcqError) -> Self {
-        Error::Transaction
+    fn from(error: yrs::TransactionAcqError) -> Self {
+        Error::Transaction(error)
     }
 }
 
+/// Lock contention fails only until the other transactions end, so it is worth retrying.
+fn is_retriable(error: &yrs::TransactionAcqError) -> bool {
+    matches!(
+        error,
+        yrs::TransactionAcqError::SharedAcqFailed | yrs::TransactionAcqError::ExclusiveAcqFailed
+    )
+}
+
 impl From<Error> for rustler::Error {
     fn from(error: Error) -> rustler::Error {
         match error {
-            Error::Transaction => rustler::Error::Atom("transaction_acq_error"),
+            Error::Transaction(error) if is_retriable(&error) => {
+                rustler::Error::Term(Box::new(atoms::transaction_busy()))
+            }
+            Error::Transaction(_) => rustler::Error::Atom("transaction_acq_error"),
             Error::Encoding(error) => {
                 rustler::Error::Term(Box::new((atoms::encoding_exception(), error.to_string())))
             }
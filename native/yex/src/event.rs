@@ -1,19 +1,26 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use rustler::{Encoder, Env, LocalPid, NifResult, NifStruct, NifUntaggedEnum, ResourceArc, Term};
+use rustler::{
+    env::OwnedEnv, Encoder, Env, LocalPid, NifMap, NifResult, NifStruct, NifUntaggedEnum,
+    ResourceArc, Term,
+};
 use yrs::{
+    block::{ItemContent, ItemPtr},
+    branch::{Branch, BranchPtr},
+    id_set::IdRange,
     types::{
         array::ArrayEvent,
         map::MapEvent,
         text::TextEvent,
         weak::WeakEvent,
         xml::{XmlEvent, XmlTextEvent},
-        Change, Delta, EntryChange,
+        Change, Delta, EntryChange, ToJson, TypePtr,
     },
-    DeepObservable, Observable, TransactionMut,
+    Array, ArrayRef, DeepObservable, MapRef, Observable, Origin, Out, ReadTxn, TransactionMut, ID,
 };
 
 use crate::{
@@ -27,7 +34,7 @@ use crate::{
     term_box::TermBox,
     text::NifText,
     transaction::TransactionResource,
-    utils::origin_to_term,
+    utils::{origin_to_term, term_to_origin_binary},
     weak::NifWeakLink,
     wrap::NifWrap,
     xml::NifXmlText,
@@ -36,7 +43,7 @@ use crate::{
     ENV,
 };
 
-type NifPath = NifWrap<yrs::types::Path>;
+pub type NifPath = NifWrap<yrs::types::Path>;
 
 impl rustler::Encoder for NifPath {
     fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
@@ -74,10 +81,95 @@ impl<'a> rustler::Decoder<'a> for NifPath {
 pub struct NifYArrayChange {
     doc: NifDoc,
     change: Vec<yrs::types::Change>,
+    /// Values of each removed range in order, set when observing with `deleted_content`.
+    deleted: Option<Vec<Vec<Out>>>,
+}
+
+impl NifYArrayChange {
+    fn with_deleted_content(self, array: &ArrayRef, txn: &TransactionMut<'_>) -> Self {
+        let mut values = removed_values(array, txn).into_iter();
+        let deleted = self
+            .change
+            .iter()
+            .filter_map(|change| match change {
+                Change::Removed(len) => Some(values.by_ref().take(*len as usize).collect()),
+                _ => None,
+            })
+            .collect();
+        NifYArrayChange {
+            deleted: Some(deleted),
+            ..self
+        }
+    }
+}
+
+/// Values of the elements removed from `array` by `txn`, in document order.
+/// Only the items in the transaction's delete set are read. Observers run before deleted items
+/// are garbage collected, so their content is still there.
+fn removed_values(array: &ArrayRef, txn: &TransactionMut<'_>) -> Vec<Out> {
+    let branch: &Branch = array.as_ref();
+    let branch = BranchPtr::from(branch);
+    let before = txn.before_state();
+    // Removed part of each item, as offsets into its content.
+    let mut removed: Vec<(ItemPtr, u32, u32)> = Vec::new();
+    for (client, range) in txn.delete_set().iter() {
+        let ranges = match range {
+            IdRange::Continuous(range) => std::slice::from_ref(range),
+            IdRange::Fragmented(ranges) => ranges.as_slice(),
+        };
+        // Items added by the transaction itself are not part of the change.
+        let added_from = before.get(client);
+        for range in ranges {
+            let end = range.end.min(added_from);
+            let mut clock = range.start;
+            while clock < end {
+                let Some(item) = txn.store().blocks.get_item(&ID::new(*client, clock)) else {
+                    clock += 1;
+                    continue;
+                };
+                let in_array = matches!(item.parent, TypePtr::Branch(parent) if parent == branch);
+                if in_array && item.parent_sub.is_none() && item.is_countable() {
+                    let stop = end.min(item.id.clock + item.len);
+                    removed.push((item, clock - item.id.clock, stop - item.id.clock));
+                }
+                clock = item.id.clock + item.len;
+            }
+        }
+    }
+
+    // Chain the removed items in document order, each walk stopping at the previous one.
+    let ids: HashSet<ID> = removed.iter().map(|(item, _, _)| item.id).collect();
+    let mut following: HashMap<Option<ID>, usize> = HashMap::new();
+    for (i, (item, _, _)) in removed.iter().enumerate() {
+        let mut left = item.left;
+        while let Some(prev) = left.filter(|prev| !ids.contains(&prev.id)) {
+            left = prev.left;
+        }
+        following.insert(left.map(|prev| prev.id), i);
+    }
+    let mut values = Vec::new();
+    let mut previous = None;
+    while let Some(i) = following.remove(&previous) {
+        let (item, start, stop) = removed[i];
+        let (start, stop) = (start as usize, stop as usize);
+        match &item.content {
+            ItemContent::Any(any) => values.extend(any[start..stop].iter().cloned().map(Out::Any)),
+            content => values.extend(
+                content
+                    .get_content()
+                    .into_iter()
+                    .skip(start)
+                    .take(stop - start),
+            ),
+        }
+        previous = Some(item.id);
+    }
+    values
 }
 
 impl rustler::Encoder for NifYArrayChange {
     fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        let mut deleted = self.deleted.iter().flatten();
         let v: Vec<Term<'_>> = self
             .change
             .clone()
@@ -96,6 +188,15 @@ impl rustler::Encoder for NifYArrayChange {
                 Change::Removed(index) => {
                     let mut map = Term::map_new(env);
                     map = map.map_put(atoms::delete(), index).unwrap();
+                    if let Some(values) = deleted.next() {
+                        let values: Vec<Term<'_>> = values
+                            .iter()
+                            .map(|item| {
+                                NifYOut::from_native(item.clone(), self.doc.clone()).encode(env)
+                            })
+                            .collect();
+                        map = map.map_put(atoms::deleted(), values).unwrap();
+                    }
                     map
                 }
                 Change::Retain(index) => {
@@ -115,11 +216,70 @@ impl<'a> rustler::Decoder<'a> for NifYArrayChange {
     }
 }
 
+/// Transaction origins whose changes an observer skips, in the encoding of transaction origins.
+#[derive(Default, Clone)]
+pub struct IgnoredOrigins(Vec<Origin>);
+
+impl IgnoredOrigins {
+    pub fn contains(&self, origin: Option<&Origin>) -> bool {
+        origin.is_some_and(|origin| self.0.contains(origin))
+    }
+}
+
+impl rustler::Encoder for IgnoredOrigins {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        self.0
+            .iter()
+            .filter_map(|origin| env.binary_to_term(origin.as_ref()).map(|(term, _)| term))
+            .collect::<Vec<Term>>()
+            .encode(env)
+    }
+}
+
+impl<'a> rustler::Decoder<'a> for IgnoredOrigins {
+    fn decode(term: Term<'a>) -> rustler::NifResult<Self> {
+        let origins: Vec<Term> = term.decode()?;
+        Ok(IgnoredOrigins(
+            origins
+                .into_iter()
+                .filter_map(term_to_origin_binary)
+                .map(|origin| Origin::from(origin.as_slice()))
+                .collect(),
+        ))
+    }
+}
+
+/// Options given to `observe` and `observe_deep`, each defaulting to the plain event delivery.
+/// Deep observers only support `ignore_origins`.
+#[derive(NifMap, Default, Clone)]
+pub struct NifObserveOptions {
+    /// Deliver map values as JSON binaries instead of decoded terms.
+    pub json_values: bool,
+    /// Deliver the whole array content after the change instead of the change delta.
+    pub deliver_snapshot: bool,
+    /// Include the removed values in the `delete` entries of array changes.
+    pub deleted_content: bool,
+    /// Coalesce map changes over windows of this many milliseconds, keeping the latest per key.
+    pub throttle_ms: Option<u64>,
+    /// Skip changes made by transactions with any of these origins.
+    pub ignore_origins: IgnoredOrigins,
+}
+
 pub trait NifEventConstructor<Event>
 where
     Self: Sized + Encoder,
 {
     fn new(doc: &NifDoc, event: &Event, txn: &TransactionMut<'_>) -> Self;
+
+    /// Adjusts a freshly built event to the observe options. Events ignore the options they don't support.
+    fn with_options(
+        self,
+        _event: &Event,
+        _txn: &TransactionMut<'_>,
+        _options: &NifObserveOptions,
+    ) -> Self {
+        self
+    }
 }
 
 #[derive(NifStruct)]
@@ -127,19 +287,64 @@ where
 pub struct NifArrayEvent {
     pub path: NifPath,
     pub target: NifArray,
-    pub change: NifYArrayChange,
+    pub change: Option<NifYArrayChange>,
+    pub old_length: u32,
+    pub new_length: u32,
+    pub snapshot: Option<Vec<NifYOut>>,
 }
 
 impl NifEventConstructor<ArrayEvent> for NifArrayEvent {
     fn new(doc: &NifDoc, event: &ArrayEvent, txn: &TransactionMut<'_>) -> Self {
+        let change = event.delta(txn).to_vec();
+        let new_length = event.target().len(txn);
+        // Replay the change backwards: inserted elements did not exist before, removed ones did.
+        let old_length = change.iter().fold(new_length, |len, change| match change {
+            Change::Added(content) => len - content.len() as u32,
+            Change::Removed(removed) => len + removed,
+            Change::Retain(_) => len,
+        });
         NifArrayEvent {
             path: event.path().into(),
             target: NifArray::new(doc.clone(), event.target().clone()),
-            change: NifYArrayChange {
+            change: Some(NifYArrayChange {
                 doc: doc.clone(),
-                change: event.delta(txn).to_vec(),
-            },
+                change,
+                deleted: None,
+            }),
+            old_length,
+            new_length,
+            snapshot: None,
+        }
+    }
+
+    fn with_options(
+        self,
+        event: &ArrayEvent,
+        txn: &TransactionMut<'_>,
+        options: &NifObserveOptions,
+    ) -> Self {
+        if options.deliver_snapshot {
+            let doc = self.target.doc();
+            let snapshot = event
+                .target()
+                .iter(txn)
+                .map(|value| NifYOut::from_native(value, doc.clone()))
+                .collect();
+            return NifArrayEvent {
+                change: None,
+                snapshot: Some(snapshot),
+                ..self
+            };
+        }
+        if options.deleted_content {
+            return NifArrayEvent {
+                change: self
+                    .change
+                    .map(|change| change.with_deleted_content(event.target(), txn)),
+                ..self
+            };
         }
+        self
     }
 }
 
@@ -221,6 +426,41 @@ impl NifEventConstructor<TextEvent> for NifTextEvent {
 pub struct NifYMapChange {
     doc: NifDoc,
     change: HashMap<Arc<str>, EntryChange>,
+    /// Values serialized up front, set when observing with `json_values`.
+    json: Option<HashMap<Arc<str>, (Option<String>, Option<String>)>>,
+}
+
+impl NifYMapChange {
+    fn into_json(self, txn: &TransactionMut<'_>) -> Self {
+        let to_json = |value: &Out| {
+            serde_json::to_string(&value.to_json(txn)).expect("Any is always serializable")
+        };
+        let json = self
+            .change
+            .iter()
+            .map(|(key, change)| {
+                let values = match change {
+                    EntryChange::Inserted(new_value) => (None, Some(to_json(new_value))),
+                    EntryChange::Removed(old_value) => (Some(to_json(old_value)), None),
+                    EntryChange::Updated(old_value, new_value) => {
+                        (Some(to_json(old_value)), Some(to_json(new_value)))
+                    }
+                };
+                (key.clone(), values)
+            })
+            .collect();
+        NifYMapChange {
+            json: Some(json),
+            ..self
+        }
+    }
+
+    fn encode_value<'a>(&self, env: Env<'a>, value: &Out, json: Option<&String>) -> Term<'a> {
+        match json {
+            Some(json) => json.encode(env),
+            None => NifYOut::from_native(value.clone(), self.doc.clone()).encode(env),
+        }
+    }
 }
 
 impl rustler::Encoder for NifYMapChange {
@@ -229,9 +469,17 @@ impl rustler::Encoder for NifYMapChange {
             .change
             .clone()
             .into_iter()
-            .map(|(key, change)| match change {
+            .map(|(key, change)| {
+                let (old_json, new_json) = self
+                    .json
+                    .as_ref()
+                    .and_then(|json| json.get(&key))
+                    .map_or((None, None), |(old, new)| (old.as_ref(), new.as_ref()));
+                (key, change, old_json, new_json)
+            })
+            .map(|(key, change, old_json, new_json)| match change {
                 EntryChange::Inserted(content) => {
-                    let content = NifYOut::from_native(content, self.doc.clone());
+                    let content = self.encode_value(env, &content, new_json);
                     let map = Term::map_new(env)
                         .map_put(atoms::action(), atoms::add())
                         .unwrap()
@@ -240,7 +488,7 @@ impl rustler::Encoder for NifYMapChange {
                     (key.to_string(), map)
                 }
                 EntryChange::Removed(old_value) => {
-                    let old_value = NifYOut::from_native(old_value, self.doc.clone());
+                    let old_value = self.encode_value(env, &old_value, old_json);
                     let map = Term::map_new(env)
                         .map_put(atoms::action(), atoms::delete())
                         .unwrap()
@@ -249,8 +497,8 @@ impl rustler::Encoder for NifYMapChange {
                     (key.to_string(), map)
                 }
                 EntryChange::Updated(old_value, new_value) => {
-                    let old_value = NifYOut::from_native(old_value, self.doc.clone());
-                    let new_value = NifYOut::from_native(new_value, self.doc.clone());
+                    let old_value = self.encode_value(env, &old_value, old_json);
+                    let new_value = self.encode_value(env, &new_value, new_json);
                     let map = Term::map_new(env)
                         .map_put(atoms::action(), atoms::update())
                         .unwrap()
@@ -288,9 +536,25 @@ impl NifEventConstructor<MapEvent> for NifMapEvent {
             keys: NifYMapChange {
                 doc: doc.clone(),
                 change: event.keys(txn).clone(),
+                json: None,
             },
         }
     }
+
+    fn with_options(
+        self,
+        _event: &MapEvent,
+        txn: &TransactionMut<'_>,
+        options: &NifObserveOptions,
+    ) -> Self {
+        if !options.json_values {
+            return self;
+        }
+        NifMapEvent {
+            keys: self.keys.into_json(txn),
+            ..self
+        }
+    }
 }
 
 #[derive(NifStruct)]
@@ -312,10 +576,12 @@ impl NifEventConstructor<XmlEvent> for NifXmlEvent {
             keys: NifYMapChange {
                 doc: doc.clone(),
                 change: event.keys(txn).clone(),
+                json: None,
             },
             delta: NifYArrayChange {
                 doc: doc.clone(),
                 change: event.delta(txn).to_vec(),
+                deleted: None,
             },
         }
     }
@@ -400,6 +666,7 @@ where
         pid: rustler::LocalPid,
         ref_term: Term<'_>,
         metadata: Term<'_>,
+        options: NifObserveOptions,
     ) -> NifResult<NifSubscription> {
         let doc = self.doc();
 
@@ -411,6 +678,9 @@ where
 
             let doc_ref = doc.clone();
             let sub = ref_value.observe_deep(move |txn, events| {
+                if options.ignore_origins.contains(txn.origin()) {
+                    return;
+                }
                 let doc_ref = doc_ref.clone();
                 ENV.with(|env| {
                     let events: Vec<NifEvent> = events
@@ -431,7 +701,7 @@ where
             });
 
             Ok(NifSubscription {
-                reference: ResourceArc::new(Mutex::new(Some(sub)).into()),
+                reference: SubscriptionResource::arc(sub),
                 doc: doc.clone(),
             })
         })
@@ -452,6 +722,7 @@ where
         pid: rustler::LocalPid,
         ref_term: Term<'_>,
         metadata: Term<'_>,
+        options: NifObserveOptions,
     ) -> NifResult<NifSubscription> {
         let doc = self.doc();
 
@@ -463,6 +734,9 @@ where
 
             let doc_ref = doc.clone();
             let sub = ref_value.observe(move |txn, event| {
+                if options.ignore_origins.contains(txn.origin()) {
+                    return;
+                }
                 let doc_ref = doc_ref.clone();
                 ENV.with(|env| {
                     let _ = env.send(
@@ -470,7 +744,8 @@ where
                         (
                             atoms::observe_event(),
                             ref_box.get(*env),
-                            Self::Event::new(&doc_ref, event, txn),
+                            Self::Event::new(&doc_ref, event, txn)
+                                .with_options(event, txn, &options),
                             origin_to_term(env, txn.origin()),
                             metadata_box.get(*env),
                         ),
@@ -486,6 +761,161 @@ where
     }
 }
 
+/// Change of one key accumulated over a throttle window:
+/// the value before the window and the latest value, each with its JSON form if requested.
+struct PendingEntry {
+    old: Option<(Out, Option<String>)>,
+    new: Option<(Out, Option<String>)>,
+}
+
+struct MapThrottle {
+    doc: NifDoc,
+    target: MapRef,
+    path: Option<NifPath>,
+    origin: Option<Origin>,
+    pending: HashMap<Arc<str>, PendingEntry>,
+    flush_scheduled: bool,
+}
+
+impl MapThrottle {
+    fn record(&mut self, event: &MapEvent, txn: &TransactionMut<'_>, json_values: bool) {
+        let with_json = |value: &Out| {
+            let json = json_values.then(|| {
+                serde_json::to_string(&value.to_json(txn)).expect("Any is always serializable")
+            });
+            (value.clone(), json)
+        };
+        for (key, change) in event.keys(txn) {
+            let (old, new) = match change {
+                EntryChange::Inserted(new) => (None, Some(with_json(new))),
+                EntryChange::Removed(old) => (Some(with_json(old)), None),
+                EntryChange::Updated(old, new) => (Some(with_json(old)), Some(with_json(new))),
+            };
+            match self.pending.get_mut(key) {
+                Some(entry) => entry.new = new,
+                None => {
+                    self.pending.insert(key.clone(), PendingEntry { old, new });
+                }
+            }
+        }
+        self.path = Some(event.path().into());
+        self.origin = txn.origin().cloned();
+    }
+
+    /// Takes the net change of the window. Keys that ended where they started are dropped.
+    fn take_event(&mut self) -> Option<NifMapEvent> {
+        let mut change = HashMap::new();
+        let mut json = HashMap::new();
+        for (key, entry) in std::mem::take(&mut self.pending) {
+            let (old, old_json) = entry.old.map_or((None, None), |(v, j)| (Some(v), j));
+            let (new, new_json) = entry.new.map_or((None, None), |(v, j)| (Some(v), j));
+            let entry_change = match (old, new) {
+                (None, None) => continue,
+                (Some(Out::Any(old)), Some(Out::Any(new))) if old == new => continue,
+                (None, Some(new)) => EntryChange::Inserted(new),
+                (Some(old), None) => EntryChange::Removed(old),
+                (Some(old), Some(new)) => EntryChange::Updated(old, new),
+            };
+            change.insert(key.clone(), entry_change);
+            json.insert(key, (old_json, new_json));
+        }
+        if change.is_empty() {
+            return None;
+        }
+        let json_values = json
+            .values()
+            .any(|(old, new)| old.is_some() || new.is_some());
+        Some(NifMapEvent {
+            path: self.path.take()?,
+            target: NifMap::new(self.doc.clone(), self.target.clone()),
+            keys: NifYMapChange {
+                doc: self.doc.clone(),
+                change,
+                json: json_values.then_some(json),
+            },
+        })
+    }
+}
+
+/// Observes a map delivering at most one event per throttle window, holding the net
+/// change of each key. The first change of a window schedules its delivery at the window end,
+/// and pending changes are dropped along with the subscription.
+fn observe_map_throttled(
+    map: &NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    pid: rustler::LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+    options: NifObserveOptions,
+    window: Duration,
+) -> NifResult<NifSubscription> {
+    let doc = map.doc();
+    let ref_box = Arc::new(TermBox::new(ref_term));
+    let metadata_box = Arc::new(TermBox::new(metadata));
+
+    doc.readonly(current_transaction, |txn| {
+        let target = map.get_ref(txn)?;
+        let throttle = Arc::new(Mutex::new(MapThrottle {
+            doc: doc.clone(),
+            target: target.clone(),
+            path: None,
+            origin: None,
+            pending: HashMap::new(),
+            flush_scheduled: false,
+        }));
+
+        let sub = target.observe(move |txn, event| {
+            if options.ignore_origins.contains(txn.origin()) {
+                return;
+            }
+            let mut state = match throttle.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            state.record(event, txn, options.json_values);
+            if state.flush_scheduled {
+                return;
+            }
+            state.flush_scheduled = true;
+
+            let throttle = Arc::downgrade(&throttle);
+            let ref_box = ref_box.clone();
+            let metadata_box = metadata_box.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(window);
+                let Some(throttle) = throttle.upgrade() else {
+                    return;
+                };
+                let mut state = match throttle.lock() {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                state.flush_scheduled = false;
+                let origin = state.origin.take();
+                let Some(event) = state.take_event() else {
+                    return;
+                };
+                let _ = OwnedEnv::new().send_and_clear(&pid, |mut env| {
+                    let origin = origin_to_term(&mut env, origin.as_ref());
+                    (
+                        atoms::observe_event(),
+                        ref_box.get(env),
+                        event,
+                        origin,
+                        metadata_box.get(env),
+                    )
+                        .encode(env)
+                });
+            });
+        });
+
+        Ok(NifSubscription {
+            reference: SubscriptionResource::arc(sub),
+            doc: doc.clone(),
+        })
+    })
+}
+
 #[rustler::nif]
 fn shared_type_observe(
     shared_type: NifSharedTypeInput,
@@ -493,26 +923,86 @@ fn shared_type_observe(
     pid: rustler::LocalPid,
     ref_term: Term<'_>,
     metadata: Term<'_>,
+    options: NifObserveOptions,
+) -> NifResult<NifSubscription> {
+    observe_shared_type(
+        shared_type,
+        current_transaction,
+        pid,
+        ref_term,
+        metadata,
+        options,
+    )
+}
+
+/// Observes every shared type with the same ref, metadata and options under one subscription.
+/// Each event's `target` tells which of them changed.
+#[rustler::nif]
+fn shared_type_observe_many(
+    doc: NifDoc,
+    shared_types: Vec<NifSharedTypeInput>,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    pid: rustler::LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+    options: NifObserveOptions,
+) -> NifResult<NifSubscription> {
+    let mut subs = Vec::with_capacity(shared_types.len());
+    for shared_type in shared_types {
+        let sub = observe_shared_type(
+            shared_type,
+            current_transaction.clone(),
+            pid,
+            ref_term,
+            metadata,
+            options.clone(),
+        )?;
+        subs.extend(sub.reference.take());
+    }
+    Ok(NifSubscription {
+        reference: SubscriptionResource::arc_all(subs),
+        doc,
+    })
+}
+
+fn observe_shared_type(
+    shared_type: NifSharedTypeInput,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    pid: rustler::LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+    options: NifObserveOptions,
 ) -> NifResult<NifSubscription> {
     match shared_type {
-        NifSharedTypeInput::Map(map) => map.observe(current_transaction, pid, ref_term, metadata),
+        NifSharedTypeInput::Map(map) => match options.throttle_ms {
+            Some(ms) => observe_map_throttled(
+                &map,
+                current_transaction,
+                pid,
+                ref_term,
+                metadata,
+                options,
+                Duration::from_millis(ms),
+            ),
+            None => map.observe(current_transaction, pid, ref_term, metadata, options),
+        },
         NifSharedTypeInput::Array(array) => {
-            array.observe(current_transaction, pid, ref_term, metadata)
+            array.observe(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::Text(text) => {
-            text.observe(current_transaction, pid, ref_term, metadata)
+            text.observe(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::XmlText(xml_text) => {
-            xml_text.observe(current_transaction, pid, ref_term, metadata)
+            xml_text.observe(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::XmlFragment(xml_fragment) => {
-            xml_fragment.observe(current_transaction, pid, ref_term, metadata)
+            xml_fragment.observe(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::XmlElement(xml_element) => {
-            xml_element.observe(current_transaction, pid, ref_term, metadata)
+            xml_element.observe(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::WeakLink(weak_link) => {
-            weak_link.observe(current_transaction, pid, ref_term, metadata)
+            weak_link.observe(current_transaction, pid, ref_term, metadata, options)
         }
     }
 }
@@ -524,28 +1014,29 @@ fn shared_type_observe_deep(
     pid: rustler::LocalPid,
     ref_term: Term<'_>,
     metadata: Term<'_>,
+    options: NifObserveOptions,
 ) -> NifResult<NifSubscription> {
     match shared_type {
         NifSharedTypeInput::Map(map) => {
-            map.observe_deep(current_transaction, pid, ref_term, metadata)
+            map.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::Array(array) => {
-            array.observe_deep(current_transaction, pid, ref_term, metadata)
+            array.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::Text(text) => {
-            text.observe_deep(current_transaction, pid, ref_term, metadata)
+            text.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::XmlText(xml_text) => {
-            xml_text.observe_deep(current_transaction, pid, ref_term, metadata)
+            xml_text.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::XmlFragment(xml_fragment) => {
-            xml_fragment.observe_deep(current_transaction, pid, ref_term, metadata)
+            xml_fragment.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::XmlElement(xml_element) => {
-            xml_element.observe_deep(current_transaction, pid, ref_term, metadata)
+            xml_element.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
         NifSharedTypeInput::WeakLink(weak_link) => {
-            weak_link.observe_deep(current_transaction, pid, ref_term, metadata)
+            weak_link.observe_deep(current_transaction, pid, ref_term, metadata, options)
         }
     }
 }
@@ -14,6 +14,7 @@ mod term_box;
 mod text;
 mod transaction;
 mod undo;
+mod update;
 mod utils;
 mod weak;
 mod wrap;
@@ -1,12 +1,13 @@
+use crate::any::{content_hash, integer_to_any};
 use crate::atoms;
 use crate::doc::NifDoc;
 use crate::event::{NifMapEvent, NifSharedTypeDeepObservable, NifSharedTypeObservable};
-use crate::shared_type::NifSharedType;
 use crate::shared_type::SharedTypeId;
+use crate::shared_type::{key_error, NifSharedType};
 use crate::transaction::TransactionResource;
 use crate::yinput::NifWeakPrelim;
 use crate::{yinput::NifYInput, youtput::NifYOut, NifAny};
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc};
+use rustler::{Atom, Encoder, Env, NifResult, NifStruct, ResourceArc, Term};
 use std::collections::HashMap;
 use yrs::types::ToJson;
 use yrs::*;
@@ -50,13 +51,63 @@ fn map_set(
     current_transaction: Option<ResourceArc<TransactionResource>>,
     key: &str,
     value: NifYInput,
+    origin: Term<'_>,
 ) -> NifResult<Atom> {
-    map.mutably(env, current_transaction, |txn| {
+    map.mutably_with_origin(env, current_transaction, origin, |txn| {
         let map = map.get_ref(txn)?;
         map.insert(txn, key, value);
         Ok(atoms::ok())
     })
 }
+/// Sets `key` to `new` only if its current value, compared as JSON, equals `expected`.
+/// A missing key compares equal to nil.
+#[rustler::nif]
+fn map_compare_and_set(
+    env: Env<'_>,
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    key: &str,
+    expected: NifAny,
+    new: NifYInput,
+) -> NifResult<(Atom, Atom)> {
+    map.mutably(env, current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        let current = map
+            .get(txn, key)
+            .map_or(Any::Null, |value| value.to_json(txn));
+        if current != expected.0 {
+            return Ok((atoms::ok(), atoms::unchanged()));
+        }
+        map.insert(txn, key, new);
+        Ok((atoms::ok(), atoms::set()))
+    })
+}
+/// Adds `delta` to the number under `key` within a single transaction, starting from 0 when the
+/// key is missing. Concurrent increments from other peers still resolve as last writer wins.
+#[rustler::nif]
+fn map_increment(
+    env: Env<'_>,
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    key: &str,
+    delta: i64,
+) -> NifResult<(Atom, NifAny)> {
+    map.mutably(env, current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        let value = match map.get(txn, key) {
+            None => integer_to_any(delta),
+            Some(Out::Any(Any::Number(n))) => Any::Number(n + delta as f64),
+            Some(Out::Any(Any::BigInt(n))) => n
+                .checked_add(delta)
+                .map(integer_to_any)
+                .ok_or(rustler::Error::Term(Box::new(atoms::out_of_bounds())))?,
+            Some(_) => return Err(rustler::Error::Term(Box::new(atoms::not_a_number()))),
+        };
+        map.insert(txn, key, value.clone());
+        Ok((atoms::ok(), NifAny::from(value)))
+    })
+}
+
 #[rustler::nif]
 fn map_size(
     map: NifMap,
@@ -82,6 +133,60 @@ fn map_get(
     })
 }
 
+#[rustler::nif]
+fn map_fetch(
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    key: &str,
+) -> NifResult<NifYOut> {
+    let doc = map.doc();
+    map.readonly(current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        map.get(txn, key)
+            .map(|b| NifYOut::from_native(b, doc.clone()))
+            .ok_or_else(|| key_error(key))
+    })
+}
+
+/// Returns the id of the item currently holding the value under `key`.
+/// Overwriting the key creates a new item, so the id changes on replacement
+/// but not when a nested shared type is mutated in place.
+#[rustler::nif]
+fn map_value_id(
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    key: &str,
+) -> NifResult<(Atom, (u64, u32))> {
+    map.readonly(current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        let branch: &Branch = map.as_ref();
+        branch
+            .map
+            .get(key)
+            .filter(|item| !item.is_deleted())
+            .map(|item| (atoms::ok(), (item.id.client, item.id.clock)))
+            .ok_or(rustler::Error::Atom("error"))
+    })
+}
+
+/// Like `map_get`, but returns `default` untouched when the key is missing.
+#[rustler::nif]
+fn map_get_or<'a>(
+    env: Env<'a>,
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    key: &str,
+    default: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let doc = map.doc();
+    map.readonly(current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        Ok(map.get(txn, key).map_or(default, |b| {
+            NifYOut::from_native(b, doc.clone()).encode(env)
+        }))
+    })
+}
+
 #[rustler::nif]
 fn map_contains_key(
     map: NifMap,
@@ -108,6 +213,37 @@ fn map_delete(
     })
 }
 #[rustler::nif]
+fn map_pop(
+    env: Env<'_>,
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    key: &str,
+) -> NifResult<(Atom, NifYOut)> {
+    let doc = map.doc();
+    map.mutably(env, current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        let value = map.get(txn, key).ok_or(rustler::Error::Atom("error"))?;
+        let value = NifYOut::from_native(value, doc.clone());
+        map.remove(txn, key);
+        Ok((atoms::ok(), value))
+    })
+}
+#[rustler::nif]
+fn map_clear(
+    env: Env<'_>,
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Atom> {
+    map.mutably(env, current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        let keys: Vec<String> = map.keys(txn).map(|key| key.to_string()).collect();
+        for key in keys {
+            map.remove(txn, &key);
+        }
+        Ok(atoms::ok())
+    })
+}
+#[rustler::nif]
 fn map_to_map(
     map: NifMap,
     current_transaction: Option<ResourceArc<TransactionResource>>,
@@ -131,6 +267,45 @@ fn map_to_json(
         Ok(map.to_json(txn).into())
     })
 }
+/// Like `map_to_json`, but as a list of `{key, value}` pairs sorted by key.
+/// Only the top level is ordered, nested maps are returned as maps.
+#[rustler::nif]
+fn map_to_json_ordered(
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Vec<(String, NifAny)>> {
+    map.readonly(current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        let mut entries: Vec<(String, NifAny)> = map
+            .iter(txn)
+            .map(|(key, value)| (key.to_string(), value.to_json(txn).into()))
+            .collect();
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(entries)
+    })
+}
+
+#[rustler::nif]
+fn map_content_hash(
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<u64> {
+    map.readonly(current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        Ok(content_hash(&map.to_json(txn)))
+    })
+}
+#[rustler::nif]
+fn map_deep_equal(
+    map: NifMap,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    expected: NifAny,
+) -> NifResult<bool> {
+    map.readonly(current_transaction, |txn| {
+        let map = map.get_ref(txn)?;
+        Ok(map.to_json(txn) == expected.0)
+    })
+}
 #[rustler::nif]
 fn map_keys(
     map: NifMap,
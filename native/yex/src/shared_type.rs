@@ -1,12 +1,18 @@
 use rustler::{Decoder, Encoder, Env, NifException, NifResult, ResourceArc, Term};
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
+use std::sync::Arc;
+use yrs::block::ItemPtr;
+use yrs::branch::{Branch, BranchID, BranchPtr};
+use yrs::types::{Path, PathSegment, TypePtr};
 use yrs::{Hook, ReadTxn, SharedRef, TransactionMut};
 
 use crate::{
     doc::NifDoc,
+    event::NifPath,
     transaction::{ReadTransaction, TransactionResource},
-    wrap::SliceIntoBinary,
+    wrap::{NifWrap, SliceIntoBinary},
+    yinput::NifSharedTypeInput,
 };
 
 pub struct SharedTypeId<T> {
@@ -26,12 +32,22 @@ impl<T> Deref for SharedTypeId<T> {
         &self.hook
     }
 }
-impl<T> Encoder for SharedTypeId<T> {
-    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
+impl<T> SharedTypeId<T> {
+    fn serialize_hook(&self) -> flexbuffers::FlexbufferSerializer {
         let mut s = flexbuffers::FlexbufferSerializer::new();
 
         self.hook.serialize(&mut s).expect("encode failed");
-        SliceIntoBinary::new(s.view()).encode(env)
+        s
+    }
+}
+impl<T> PartialEq for SharedTypeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.serialize_hook().view() == other.serialize_hook().view()
+    }
+}
+impl<T> Encoder for SharedTypeId<T> {
+    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
+        SliceIntoBinary::new(self.serialize_hook().view()).encode(env)
     }
 }
 impl<'a, T: 'a> Decoder<'a> for SharedTypeId<T> {
@@ -73,6 +89,20 @@ where
         self.doc().mutably(env, current_transaction, f)
     }
 
+    fn mutably_with_origin<F, T>(
+        &self,
+        env: Env<'_>,
+        current_transaction: Option<ResourceArc<TransactionResource>>,
+        origin: Term<'_>,
+        f: F,
+    ) -> NifResult<T>
+    where
+        F: FnOnce(&mut TransactionMut<'_>) -> NifResult<T>,
+    {
+        self.doc()
+            .mutably_with_origin(env, current_transaction, origin, f)
+    }
+
     fn readonly<F, T>(
         &self,
         current_transaction: Option<ResourceArc<TransactionResource>>,
@@ -91,8 +121,130 @@ pub struct DeletedSharedTypeError {
     message: String,
 }
 
+#[derive(Debug, NifException)]
+#[module = "Yex.KeyError"]
+pub struct KeyError {
+    message: String,
+    key: String,
+}
+
+pub fn key_error(key: &str) -> rustler::Error {
+    rustler::Error::RaiseTerm(Box::new(KeyError {
+        message: format!("key {key:?} not found"),
+        key: key.to_string(),
+    }))
+}
+
 pub fn deleted_error(message: &str) -> rustler::Error {
     rustler::Error::RaiseTerm(Box::new(DeletedSharedTypeError {
         message: message.to_string(),
     }))
 }
+
+/// Whether `a` and `b` refer to the same branch of the same document.
+/// Handles on one document obtained separately share its state, so they compare equal.
+fn same_shared_type<T: NifSharedType>(a: &T, b: &T) -> bool {
+    Arc::ptr_eq(&a.doc().reference.1, &b.doc().reference.1) && a.reference() == b.reference()
+}
+
+#[rustler::nif]
+fn shared_type_equal(a: NifSharedTypeInput, b: NifSharedTypeInput) -> bool {
+    match (a, b) {
+        (NifSharedTypeInput::Text(a), NifSharedTypeInput::Text(b)) => same_shared_type(&a, &b),
+        (NifSharedTypeInput::Map(a), NifSharedTypeInput::Map(b)) => same_shared_type(&a, &b),
+        (NifSharedTypeInput::Array(a), NifSharedTypeInput::Array(b)) => same_shared_type(&a, &b),
+        (NifSharedTypeInput::XmlText(a), NifSharedTypeInput::XmlText(b)) => {
+            same_shared_type(&a, &b)
+        }
+        (NifSharedTypeInput::XmlElement(a), NifSharedTypeInput::XmlElement(b)) => {
+            same_shared_type(&a, &b)
+        }
+        (NifSharedTypeInput::XmlFragment(a), NifSharedTypeInput::XmlFragment(b)) => {
+            same_shared_type(&a, &b)
+        }
+        (NifSharedTypeInput::WeakLink(a), NifSharedTypeInput::WeakLink(b)) => {
+            same_shared_type(&a, &b)
+        }
+        _ => false,
+    }
+}
+
+fn shared_type_path_of<T: NifSharedType>(
+    shared_type: &T,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<NifPath> {
+    shared_type.readonly(current_transaction, |txn| {
+        let shared_ref = shared_type.get_ref(txn)?;
+        let branch: &Branch = shared_ref.as_ref();
+        let mut path = branch_path(BranchPtr::from(branch)).ok_or(rustler::Error::Atom("error"))?;
+        // Paths are relative to the root type, whose name is the first segment.
+        path.pop_front();
+        Ok(NifWrap(path))
+    })
+}
+
+#[rustler::nif]
+fn shared_type_path(
+    shared_type: NifSharedTypeInput,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<NifPath> {
+    match shared_type {
+        NifSharedTypeInput::Text(text) => shared_type_path_of(&text, current_transaction),
+        NifSharedTypeInput::Map(map) => shared_type_path_of(&map, current_transaction),
+        NifSharedTypeInput::Array(array) => shared_type_path_of(&array, current_transaction),
+        NifSharedTypeInput::XmlText(xml) => shared_type_path_of(&xml, current_transaction),
+        NifSharedTypeInput::XmlElement(xml) => shared_type_path_of(&xml, current_transaction),
+        NifSharedTypeInput::XmlFragment(xml) => shared_type_path_of(&xml, current_transaction),
+        NifSharedTypeInput::WeakLink(weak) => shared_type_path_of(&weak, current_transaction),
+    }
+}
+
+/// Name of the root type `branch` belongs to, walking up through its parents.
+pub fn root_name(branch: BranchPtr) -> Option<Arc<str>> {
+    let mut branch = branch;
+    while let Some(item) = branch.item {
+        match item.parent {
+            TypePtr::Branch(parent) => branch = parent,
+            _ => return None,
+        }
+    }
+    match branch.id() {
+        BranchID::Root(name) => Some(name),
+        BranchID::Nested(_) => None,
+    }
+}
+
+/// Position of `item` among the live elements of its parent collection.
+pub fn item_index(item: ItemPtr) -> u32 {
+    let mut index = 0;
+    let mut left = item.left;
+    while let Some(prev) = left {
+        if !prev.is_deleted() && prev.is_countable() {
+            index += prev.len;
+        }
+        left = prev.left;
+    }
+    index
+}
+
+/// Path from the document root to `branch`, starting with the root type name.
+/// Returns `None` if the branch is detached from the document.
+pub fn branch_path(branch: BranchPtr) -> Option<Path> {
+    let mut path = Path::new();
+    let mut branch = branch;
+    while let Some(item) = branch.item {
+        let TypePtr::Branch(parent) = item.parent else {
+            return None;
+        };
+        match &item.parent_sub {
+            Some(key) => path.push_front(PathSegment::Key(key.clone())),
+            None => path.push_front(PathSegment::Index(item_index(item))),
+        }
+        branch = parent;
+    }
+    match branch.id() {
+        BranchID::Root(name) => path.push_front(PathSegment::Key(name)),
+        BranchID::Nested(_) => return None,
+    }
+    Some(path)
+}
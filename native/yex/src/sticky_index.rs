@@ -1,6 +1,12 @@
-use rustler::{Atom, Decoder, Encoder, Env, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term};
+use rustler::{
+    Atom, Binary, Decoder, Encoder, Env, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term,
+};
 use serde::{Deserialize as _, Serialize as _};
-use yrs::{Assoc, IndexedSequence, StickyIndex};
+use std::cmp::Ordering;
+use std::sync::Arc;
+use yrs::branch::BranchID;
+use yrs::types::TypePtr;
+use yrs::{Assoc, IndexScope, IndexedSequence, ReadTxn, StickyIndex};
 
 use crate::{
     atoms, doc::NifDoc, shared_type::NifSharedType, transaction::TransactionResource,
@@ -150,3 +156,80 @@ fn sticky_index_get_offset(
         }
     })
 }
+
+/// Identifies the shared type the sticky index points into, if it still exists.
+fn sticky_index_scope<T: ReadTxn>(txn: &T, sticky_index: &StickyIndex) -> Option<BranchID> {
+    match sticky_index.scope() {
+        IndexScope::Relative(id) => match txn.store().blocks.get_item(id)?.parent {
+            TypePtr::Branch(branch) => Some(branch.id()),
+            _ => None,
+        },
+        IndexScope::Nested(id) => Some(BranchID::Nested(*id)),
+        IndexScope::Root(name) => Some(BranchID::Root(name.clone())),
+    }
+}
+
+#[rustler::nif]
+fn sticky_index_compare(
+    a: NifStickyIndex,
+    b: NifStickyIndex,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Atom> {
+    let doc = a.doc.clone();
+    // Offsets are only comparable within one shared type of one document.
+    if !Arc::ptr_eq(&a.doc.reference.1, &b.doc.reference.1) {
+        return Err(rustler::Error::Atom("error"));
+    }
+
+    doc.readonly(current_transaction, |txn| {
+        let (a, b) = (&a.reference.0, &b.reference.0);
+        let same_type = match (sticky_index_scope(txn, a), sticky_index_scope(txn, b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        };
+        let (true, Some(a), Some(b)) = (same_type, a.get_offset(txn), b.get_offset(txn)) else {
+            return Err(rustler::Error::Atom("error"));
+        };
+        // `Before` sorts ahead of `After` when both resolve to the same index.
+        let assoc_rank = |assoc: Assoc| matches!(assoc, Assoc::After);
+        let ordering = (a.index, assoc_rank(a.assoc)).cmp(&(b.index, assoc_rank(b.assoc)));
+        Ok(match ordering {
+            Ordering::Less => atoms::lt(),
+            Ordering::Equal => atoms::eq(),
+            Ordering::Greater => atoms::gt(),
+        })
+    })
+}
+
+#[rustler::nif]
+fn sticky_index_encode<'a>(env: Env<'a>, sticky_index: NifStickyIndex) -> Term<'a> {
+    sticky_index.reference.encode(env)
+}
+
+#[rustler::nif]
+fn sticky_index_decode(
+    doc: NifDoc,
+    binary: Binary,
+    assoc: Option<NifAssoc>,
+) -> NifResult<(Atom, NifStickyIndex)> {
+    let reader = flexbuffers::Reader::get_root(binary.as_slice())
+        .map_err(|_e| rustler::Error::Atom("error"))?;
+    let mut sticky_index =
+        StickyIndex::deserialize(reader).map_err(|_e| rustler::Error::Atom("error"))?;
+    // The association stored in the binary is kept unless the caller overrides it.
+    let assoc = match assoc {
+        Some(assoc) => {
+            sticky_index.assoc = (&assoc).into();
+            assoc
+        }
+        None => sticky_index.assoc.into(),
+    };
+    Ok((
+        atoms::ok(),
+        NifStickyIndex {
+            doc,
+            reference: StickyIndexRef::new(sticky_index),
+            assoc,
+        },
+    ))
+}
@@ -4,13 +4,27 @@ use yrs::*;
 
 use crate::{atoms, doc::NifDoc, wrap::NifWrap, ENV};
 
-pub type SubscriptionResource = NifWrap<Mutex<Option<Subscription>>>;
+/// Observers kept alive by a `Yex.Subscription`; unsubscribing drops all of them at once.
+pub type SubscriptionResource = NifWrap<Mutex<Vec<Subscription>>>;
 #[rustler::resource_impl]
 impl rustler::Resource for SubscriptionResource {}
 
 impl SubscriptionResource {
     pub fn arc(sub: Subscription) -> ResourceArc<Self> {
-        ResourceArc::new(NifWrap(Mutex::new(Some(sub))))
+        Self::arc_all(vec![sub])
+    }
+
+    pub fn arc_all(subs: Vec<Subscription>) -> ResourceArc<Self> {
+        ResourceArc::new(NifWrap(Mutex::new(subs)))
+    }
+
+    /// Moves the observers out of this resource, leaving it unsubscribed.
+    pub fn take(&self) -> Vec<Subscription> {
+        let mut inner = match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        std::mem::take(&mut *inner)
     }
 }
 
@@ -24,11 +38,7 @@ pub struct NifSubscription {
 #[rustler::nif]
 fn sub_unsubscribe(env: Env<'_>, sub: NifSubscription) -> NifResult<Atom> {
     ENV.set(&mut env.clone(), || {
-        let mut inner = match sub.reference.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        *inner = None;
+        drop(sub.reference.take());
         Ok(atoms::ok())
     })
 }
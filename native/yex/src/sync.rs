@@ -221,6 +221,56 @@ fn sync_message_decode_v1<'a>(env: Env<'a>, msg: Binary<'a>) -> NifResult<(Atom,
         .map_err(|e| e.into())
 }
 
+/// Length of the first v1 message in `bytes` without decoding its payload.
+/// Returns `Err(needed)` with a hint of how many more bytes are required when the input is truncated.
+fn message_span_v1(bytes: &[u8]) -> Result<usize, usize> {
+    fn buf_end(bytes: &[u8], pos: &mut usize) -> Result<(), usize> {
+        let len = usize::try_from(read_var_u64(bytes, pos).ok_or(1usize)?).map_err(|_| 1usize)?;
+        let end = pos.saturating_add(len);
+        if end > bytes.len() {
+            return Err(end - bytes.len());
+        }
+        *pos = end;
+        Ok(())
+    }
+
+    let tag = *bytes.first().ok_or(1usize)?;
+    let mut pos = 1;
+    match tag {
+        MSG_QUERY_AWARENESS => {}
+        MSG_SYNC => {
+            read_var_u64(bytes, &mut pos).ok_or(1usize)?;
+            buf_end(bytes, &mut pos)?;
+        }
+        MSG_AUTH => {
+            if read_var_u64(bytes, &mut pos).ok_or(1usize)? == PERMISSION_DENIED as u64 {
+                buf_end(bytes, &mut pos)?;
+            }
+        }
+        _ => buf_end(bytes, &mut pos)?,
+    }
+    Ok(pos)
+}
+
+/// Decode the first message of a stream buffer that may hold a partial or several messages.
+#[rustler::nif]
+fn sync_message_decode_stream_v1<'a>(env: Env<'a>, msg: Binary<'a>) -> NifResult<Term<'a>> {
+    let len = match message_span_v1(msg.as_slice()) {
+        Ok(len) => len,
+        Err(needed) => return Ok((atoms::incomplete(), needed).encode(env)),
+    };
+    let msg = msg.make_subbinary(0, len)?;
+
+    let term = match try_decode_message_v1_fast(env, msg) {
+        Some(term) => term,
+        None => {
+            let mut decoder = DecoderV1::new(Cursor::new(msg.as_slice()));
+            decode_message(env, &mut decoder)?
+        }
+    };
+    Ok((atoms::ok(), term, len).encode(env))
+}
+
 #[rustler::nif]
 fn apply_sync_update_payload_v1(
     env: Env<'_>,
@@ -336,6 +386,32 @@ fn awareness_message_encode_v1<'a>(env: Env<'a>, update: Binary<'a>) -> NifResul
     Ok((atoms::ok(), encode_awareness_term(env, update.as_slice())).encode(env))
 }
 
+/// Version of the y-protocols sync protocol implemented by the codecs above.
+const SYNC_PROTOCOL_VERSION: u32 = 1;
+
+/// Message tags understood by `decode_message` and `encode_message`.
+#[derive(rustler::NifMap)]
+struct NifSyncCapabilities {
+    version: u32,
+    sync: bool,
+    awareness: bool,
+    auth: bool,
+    query_awareness: bool,
+    custom: bool,
+}
+
+#[rustler::nif]
+fn sync_capabilities() -> NifSyncCapabilities {
+    NifSyncCapabilities {
+        version: SYNC_PROTOCOL_VERSION,
+        sync: true,
+        awareness: true,
+        auth: true,
+        query_awareness: true,
+        custom: true,
+    }
+}
+
 #[rustler::nif]
 fn sync_message_decode_v2<'a>(env: Env<'a>, msg: Binary<'a>) -> NifResult<(Atom, Term<'a>)> {
     let mut decoder = DecoderV2::new(Cursor::new(msg.as_slice())).map_err(Error::from)?;
@@ -4,16 +4,22 @@ use crate::{
     utils::{capped_index_and_length, normalize_index_for_insert},
     yinput::NifWeakPrelim,
 };
-use rustler::{Atom, Encoder, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Binary, Decoder, Encoder, Env, NifResult, NifStruct, ResourceArc, Term};
 use types::text::{Diff, YChange};
+use types::{Attrs, Delta};
+use yrs::block::ItemContent;
+use yrs::branch::Branch;
+use yrs::updates::decoder::Decode;
 use yrs::*;
 
 use crate::{
-    any::NifAttr,
+    any::{json_to_any, NifAny, NifAttr},
     atoms,
     doc::NifDoc,
+    error::Error,
     event::{NifSharedTypeDeepObservable, NifSharedTypeObservable, NifTextEvent},
     shared_type::{NifSharedType, SharedTypeId},
+    sticky_index::NifStickyIndex,
     transaction::TransactionResource,
     yinput::NifYInputDelta,
     youtput::NifYOut,
@@ -60,8 +66,9 @@ fn text_insert(
     current_transaction: Option<ResourceArc<TransactionResource>>,
     index: i64,
     chunk: &str,
+    origin: Term<'_>,
 ) -> NifResult<Atom> {
-    text.mutably(env, current_transaction, |txn| {
+    text.mutably_with_origin(env, current_transaction, origin, |txn| {
         let text = text.get_ref(txn)?;
         let index = normalize_index_for_insert(text.len(txn), index);
         text.insert(txn, index, chunk);
@@ -77,8 +84,9 @@ fn text_insert_with_attributes(
     index: i64,
     chunk: &str,
     attr: NifAttr,
+    origin: Term<'_>,
 ) -> NifResult<Atom> {
-    text.mutably(env, current_transaction, |txn| {
+    text.mutably_with_origin(env, current_transaction, origin, |txn| {
         let text = text.get_ref(txn)?;
         let index = normalize_index_for_insert(text.len(txn), index);
         text.insert_with_attributes(txn, index, chunk, attr.0);
@@ -86,6 +94,51 @@ fn text_insert_with_attributes(
     })
 }
 
+#[rustler::nif]
+fn text_insert_embed(
+    env: Env<'_>,
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    index: i64,
+    embed: NifAny,
+    attr: NifAttr,
+) -> NifResult<Atom> {
+    text.mutably(env, current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        let index = normalize_index_for_insert(text.len(txn), index);
+        if attr.0.is_empty() {
+            text.insert_embed(txn, index, embed.0);
+        } else {
+            text.insert_embed_with_attributes(txn, index, embed.0, attr.0);
+        }
+        Ok(atoms::ok())
+    })
+}
+
+#[rustler::nif]
+fn text_insert_at_sticky(
+    env: Env<'_>,
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    sticky_index: NifStickyIndex,
+    chunk: &str,
+    attr: Option<NifAttr>,
+) -> NifResult<Atom> {
+    text.mutably(env, current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        // resolve and insert within the same transaction so no edit can land in between
+        let offset = StickyIndex::from(&sticky_index)
+            .get_offset(txn)
+            .ok_or(rustler::Error::Atom("error"))?;
+        let index = offset.index.min(text.len(txn));
+        match attr {
+            Some(attr) => text.insert_with_attributes(txn, index, chunk, attr.0),
+            None => text.insert(txn, index, chunk),
+        }
+        Ok(atoms::ok())
+    })
+}
+
 #[rustler::nif]
 fn text_delete(
     env: Env<'_>,
@@ -125,6 +178,66 @@ fn text_format(
     })
 }
 
+/// A single edit of `text_apply_ops`, decoded from `{:insert, index, chunk}`,
+/// `{:delete, index, len}` or `{:format, index, len, attributes}`.
+enum NifTextOp {
+    Insert(i64, String),
+    Delete(i64, u32),
+    Format(i64, u32, NifAttr),
+}
+
+impl<'a> Decoder<'a> for NifTextOp {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if let Ok((tag, index, chunk)) = term.decode::<(Atom, i64, String)>() {
+            if tag == atoms::insert() {
+                return Ok(NifTextOp::Insert(index, chunk));
+            }
+        } else if let Ok((tag, index, len)) = term.decode::<(Atom, i64, u32)>() {
+            if tag == atoms::delete() {
+                return Ok(NifTextOp::Delete(index, len));
+            }
+        } else if let Ok((tag, index, len, attr)) = term.decode::<(Atom, i64, u32, NifAttr)>() {
+            if tag == atoms::format() {
+                return Ok(NifTextOp::Format(index, len, attr));
+            }
+        }
+        Err(rustler::Error::BadArg)
+    }
+}
+
+/// Applies the ops in order within a single transaction. Each op behaves like the matching
+/// `text_insert`, `text_delete` or `text_format` call.
+#[rustler::nif]
+fn text_apply_ops(
+    env: Env<'_>,
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    ops: Vec<NifTextOp>,
+) -> NifResult<Atom> {
+    text.mutably(env, current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        for op in ops {
+            match op {
+                NifTextOp::Insert(index, chunk) => {
+                    let index = normalize_index_for_insert(text.len(txn), index);
+                    text.insert(txn, index, &chunk);
+                }
+                NifTextOp::Delete(index, len) => {
+                    if let Some((index, len)) = capped_index_and_length(text.len(txn), index, len) {
+                        text.remove_range(txn, index, len);
+                    }
+                }
+                NifTextOp::Format(index, len, attr) => {
+                    if let Some((index, len)) = capped_index_and_length(text.len(txn), index, len) {
+                        text.format(txn, index, len, attr.0);
+                    }
+                }
+            }
+        }
+        Ok(atoms::ok())
+    })
+}
+
 #[rustler::nif]
 fn text_to_string(
     text: NifText,
@@ -136,6 +249,58 @@ fn text_to_string(
     })
 }
 #[rustler::nif]
+fn text_to_string_with_embeds(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    placeholder: &str,
+) -> NifResult<String> {
+    text.readonly(current_transaction, |txn| {
+        let diff = text.get_ref(txn)?.diff(txn, YChange::identity);
+        Ok(diff
+            .iter()
+            .map(|chunk| match &chunk.insert {
+                Out::Any(Any::String(s)) => s.as_ref(),
+                _ => placeholder,
+            })
+            .collect())
+    })
+}
+/// Ranges `{index, len}` of the runs whose `attr_key` attribute equals `attr_value`.
+/// Adjacent matching runs are merged, and offsets are measured in the doc's offset kind.
+#[rustler::nif]
+fn text_find_formatted(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    attr_key: &str,
+    attr_value: NifAny,
+) -> NifResult<Vec<(u32, u32)>> {
+    let utf16 = matches!(text.doc().offset_kind(), OffsetKind::Utf16);
+    text.readonly(current_transaction, |txn| {
+        let diff = text.get_ref(txn)?.diff(txn, YChange::identity);
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        let mut index = 0;
+        for chunk in diff {
+            let len = match &chunk.insert {
+                Out::Any(Any::String(s)) if utf16 => s.encode_utf16().count() as u32,
+                Out::Any(Any::String(s)) => s.len() as u32,
+                _ => 1,
+            };
+            let matches = chunk
+                .attributes
+                .as_ref()
+                .is_some_and(|attrs| attrs.get(attr_key) == Some(&attr_value.0));
+            if matches {
+                match ranges.last_mut() {
+                    Some((start, range_len)) if *start + *range_len == index => *range_len += len,
+                    _ => ranges.push((index, len)),
+                }
+            }
+            index += len;
+        }
+        Ok(ranges)
+    })
+}
+#[rustler::nif]
 fn text_length(
     text: NifText,
     current_transaction: Option<ResourceArc<TransactionResource>>,
@@ -146,6 +311,154 @@ fn text_length(
     })
 }
 
+/// Characters overlapping `index..index + len`, measured in the doc's offset kind.
+/// Embeds take one position but contribute nothing to the result. Items are walked from the
+/// start and only those up to the end of the range are read.
+fn text_range(text: &TextRef, utf16: bool, index: u32, len: u32) -> String {
+    let end = index + len;
+    let branch: &Branch = text.as_ref();
+    let mut offset = 0;
+    let mut result = String::new();
+    let mut current = branch.start;
+    while let Some(item) = current.as_deref() {
+        if offset >= end {
+            break;
+        }
+        current = item.right;
+        if item.is_deleted() || !item.is_countable() {
+            continue;
+        }
+        let ItemContent::String(s) = &item.content else {
+            offset += item.len;
+            continue;
+        };
+        let s = s.as_str();
+        let width = if utf16 {
+            s.encode_utf16().count()
+        } else {
+            s.len()
+        } as u32;
+        if offset + width <= index {
+            offset += width;
+            continue;
+        }
+        for c in s.chars() {
+            if offset >= end {
+                break;
+            }
+            let width = if utf16 { c.len_utf16() } else { c.len_utf8() } as u32;
+            if offset + width > index {
+                result.push(c);
+            }
+            offset += width;
+        }
+    }
+    result
+}
+
+/// Offset, measured in the doc's offset kind, of the codepoint at `char_index`.
+/// Embeds count as a single codepoint. `char_index` may point right past the last one.
+#[rustler::nif]
+fn text_offset_of_char(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    char_index: u32,
+) -> NifResult<(Atom, u32)> {
+    let utf16 = matches!(text.doc().offset_kind(), OffsetKind::Utf16);
+    text.readonly(current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        let mut remaining = char_index;
+        let mut offset = 0;
+        for chunk in text.diff(txn, YChange::identity) {
+            if remaining == 0 {
+                break;
+            }
+            match &chunk.insert {
+                Out::Any(Any::String(s)) => {
+                    for c in s.chars().take(remaining as usize) {
+                        let width = if utf16 { c.len_utf16() } else { c.len_utf8() };
+                        offset += width as u32;
+                        remaining -= 1;
+                    }
+                }
+                _ => {
+                    offset += 1;
+                    remaining -= 1;
+                }
+            }
+        }
+        if remaining > 0 {
+            return Err(rustler::Error::Term(Box::new(atoms::out_of_bounds())));
+        }
+        Ok((atoms::ok(), offset))
+    })
+}
+
+#[rustler::nif]
+fn text_char_at(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    index: i64,
+) -> NifResult<(Atom, String)> {
+    let utf16 = matches!(text.doc().offset_kind(), OffsetKind::Utf16);
+    text.readonly(current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        let (index, len) = capped_index_and_length(text.len(txn), index, 1)
+            .ok_or(rustler::Error::Atom("error"))?;
+        let found = text_range(&text, utf16, index, len);
+        if found.is_empty() {
+            return Err(rustler::Error::Atom("error"));
+        }
+        Ok((atoms::ok(), found))
+    })
+}
+
+#[rustler::nif]
+fn text_substring(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    index: i64,
+    len: u32,
+) -> NifResult<String> {
+    let utf16 = matches!(text.doc().offset_kind(), OffsetKind::Utf16);
+    text.readonly(current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        Ok(capped_index_and_length(text.len(txn), index, len)
+            .map(|(index, len)| text_range(&text, utf16, index, len))
+            .unwrap_or_default())
+    })
+}
+
+/// Words are maximal runs of characters that are not Unicode whitespace,
+/// so punctuation attached to a word counts as part of it, as in `wc -w`.
+#[rustler::nif]
+fn text_word_count(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<u32> {
+    text.readonly(current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        Ok(text.get_string(txn).split_whitespace().count() as u32)
+    })
+}
+
+/// Lines are separated by `\n`, so a trailing newline opens an empty last line,
+/// as editors show it. Empty text has no lines.
+#[rustler::nif]
+fn text_line_count(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<u32> {
+    text.readonly(current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        let string = text.get_string(txn);
+        if string.is_empty() {
+            return Ok(0);
+        }
+        Ok(string.matches('\n').count() as u32 + 1)
+    })
+}
+
 #[rustler::nif]
 fn text_to_delta(
     env: Env<'_>,
@@ -162,6 +475,52 @@ fn text_to_delta(
     encode_diffs(diff, &text.doc, env)
 }
 
+/// Diffs the text between two snapshots encoded with `Snapshot::encode_v1`, so each run
+/// carries a `ychange` attribute telling whether it was added or removed in between.
+#[rustler::nif]
+fn text_to_delta_with_snapshots<'a>(
+    env: Env<'a>,
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    current_snapshot: Binary,
+    prev_snapshot: Option<Binary>,
+) -> NifResult<Term<'a>> {
+    let current = Snapshot::decode_v1(current_snapshot.as_slice()).map_err(Error::from)?;
+    let prev = prev_snapshot
+        .map(|prev| Snapshot::decode_v1(prev.as_slice()).map_err(Error::from))
+        .transpose()?;
+    let diff = text.mutably(env, current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        Ok(text.diff_range(txn, Some(&current), prev.as_ref(), YChange::identity))
+    })?;
+    encode_diffs(diff, &text.doc, env)
+}
+
+/// Counts the runs reported by the text diff, and how many remain once adjacent string runs
+/// with equal attributes are merged. yrs squashes adjacent blocks on commit, so both are equal
+/// unless redundant formatting markers split a run.
+#[rustler::nif]
+fn text_run_stats(
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<(usize, usize)> {
+    text.readonly(current_transaction, |txn| {
+        let diff = text.get_ref(txn)?.diff(txn, YChange::identity);
+        let runs = diff.len();
+        let coalesced = diff
+            .iter()
+            .zip(diff.iter().skip(1))
+            .filter(|(prev, next)| {
+                matches!(
+                    (&prev.insert, &next.insert),
+                    (Out::Any(Any::String(_)), Out::Any(Any::String(_)))
+                ) && prev.attributes == next.attributes
+            })
+            .count();
+        Ok((runs, runs - coalesced))
+    })
+}
+
 #[rustler::nif]
 fn text_quote(
     env: Env<'_>,
@@ -199,6 +558,98 @@ fn text_apply_delta(
     })
 }
 
+/// Like `text_apply_delta`, but fails with `{:error, :delta_out_of_range}` without applying
+/// anything when the retains and deletes of the delta reach past the end of the text.
+#[rustler::nif]
+fn text_apply_delta_checked(
+    env: Env<'_>,
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    delta: NifYInputDelta,
+) -> NifResult<Atom> {
+    text.mutably(env, current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        let consumed: u64 = delta
+            .0
+            .iter()
+            .map(|op| match op {
+                Delta::Retain(len, _) | Delta::Deleted(len) => *len as u64,
+                Delta::Inserted(_, _) => 0,
+            })
+            .sum();
+        if consumed > text.len(txn) as u64 {
+            return Err(rustler::Error::Term(Box::new(atoms::delta_out_of_range())));
+        }
+        text.apply_delta(txn, delta.0);
+        Ok(atoms::ok())
+    })
+}
+
+/// Parses a Quill Delta, either `{"ops": [...]}` or the bare list of ops.
+/// Embeds are inserted as maps, matching how `to_delta` reports them.
+fn parse_quill_delta(json: &[u8]) -> Option<Vec<Delta<Any>>> {
+    let ops = match serde_json::from_slice::<serde_json::Value>(json).ok()? {
+        serde_json::Value::Object(mut delta) => delta.remove("ops")?,
+        ops => ops,
+    };
+    let serde_json::Value::Array(ops) = ops else {
+        return None;
+    };
+
+    ops.into_iter()
+        .map(|op| {
+            let serde_json::Value::Object(mut op) = op else {
+                return None;
+            };
+            let attrs = match op.remove("attributes") {
+                None | Some(serde_json::Value::Null) => None,
+                Some(serde_json::Value::Object(attrs)) => Some(Box::new(
+                    attrs
+                        .into_iter()
+                        .map(|(k, v)| (k.into(), json_to_any(v)))
+                        .collect::<Attrs>(),
+                )),
+                Some(_) => return None,
+            };
+            let len = |v: &serde_json::Value| v.as_u64().and_then(|n| u32::try_from(n).ok());
+
+            if let Some(insert) = op.remove("insert") {
+                match insert {
+                    serde_json::Value::String(s) => {
+                        Some(Delta::Inserted(Any::String(s.into()), attrs))
+                    }
+                    serde_json::Value::Object(_) => {
+                        Some(Delta::Inserted(json_to_any(insert), attrs))
+                    }
+                    _ => None,
+                }
+            } else if let Some(retain) = op.get("retain") {
+                Some(Delta::Retain(len(retain)?, attrs))
+            } else if let Some(delete) = op.get("delete") {
+                Some(Delta::Deleted(len(delete)?))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[rustler::nif]
+fn text_apply_quill_delta(
+    env: Env<'_>,
+    text: NifText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    delta_json: Binary,
+) -> NifResult<Atom> {
+    let delta = parse_quill_delta(delta_json.as_slice())
+        .ok_or_else(|| rustler::Error::Term(Box::new(atoms::invalid_quill_delta())))?;
+    text.mutably(env, current_transaction, |txn| {
+        let text = text.get_ref(txn)?;
+        text.apply_delta(txn, delta);
+        Ok(atoms::ok())
+    })
+}
+
 pub fn encode_diffs<'a>(
     diff: Vec<Diff<YChange>>,
     doc: &NifDoc,
@@ -1,16 +1,95 @@
-use std::sync::RwLock;
-use yrs::{ReadTxn, Store, Transaction, TransactionMut};
+use std::sync::{Arc, Mutex, RwLock, Weak};
 
-pub struct TransactionResource(pub RwLock<Option<TransactionMut<'static>>>);
+use rustler::{Encoder, Env, NifResult, ResourceArc, Term};
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, Origin, ReadTxn, Store, Transaction, TransactionMut};
+
+use crate::{atoms, utils::origin_to_term, wrap::SliceIntoBinary, ENV};
+
+type TransactionCell = RwLock<Option<TransactionMut<'static>>>;
+
+/// An explicit transaction. It keeps a handle on its document so the store it borrows
+/// outlives it; the transaction is declared first so it is dropped first.
+pub struct TransactionResource(pub Arc<TransactionCell>, Doc);
+
+impl std::panic::RefUnwindSafe for TransactionResource {}
 
 #[rustler::resource_impl]
 impl rustler::Resource for TransactionResource {}
 
+/// Explicit transactions opened on a document and not committed yet.
+/// Entries are weak, so a transaction whose owner is gone is still committed when collected.
+#[derive(Default)]
+pub struct OpenTransactions(Mutex<Vec<Weak<TransactionCell>>>);
+
+impl OpenTransactions {
+    pub fn register(&self, txn: &TransactionResource) {
+        if let Ok(mut open) = self.0.lock() {
+            open.retain(is_open);
+            open.push(Arc::downgrade(&txn.0));
+        }
+    }
+
+    /// Commits the open transactions, firing the observers of the document in `env`,
+    /// and returns how many there were.
+    pub fn commit_all(&self, env: Env<'_>) -> usize {
+        // take them out first, committing fires observers that may open transactions again
+        let open: Vec<Arc<TransactionCell>> = match self.0.lock() {
+            Ok(mut open) => open
+                .drain(..)
+                .filter(is_open)
+                .filter_map(|txn| txn.upgrade())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        for txn in &open {
+            commit_cell(env, txn);
+        }
+        open.len()
+    }
+}
+
+fn is_open(txn: &Weak<TransactionCell>) -> bool {
+    txn.upgrade()
+        .is_some_and(|txn| txn.read().is_ok_and(|txn| txn.is_some()))
+}
+
+fn commit_cell(env: Env<'_>, txn: &TransactionCell) {
+    ENV.set(&mut env.clone(), || {
+        if let Ok(mut txn) = txn.write() {
+            *txn = None;
+        }
+    });
+}
+
+impl TransactionResource {
+    pub fn new(doc: &Doc, txn: TransactionMut<'static>) -> Self {
+        TransactionResource(Arc::new(RwLock::new(Some(txn))), doc.clone())
+    }
+
+    /// Commits the transaction, firing the observers of the document in `env`.
+    pub fn commit(&self, env: Env<'_>) {
+        commit_cell(env, &self.0);
+    }
+}
+
 pub enum ReadTransaction<'a, 'doc> {
     ReadOnly(&'a Transaction<'doc>),
     ReadWrite(&'a TransactionMut<'doc>),
 }
 
+impl ReadTransaction<'_, '_> {
+    /// Origin of the transaction this read runs in.
+    /// Reads that reuse an open write transaction see its origin,
+    /// standalone read transactions never carry one and return `None`.
+    pub fn origin(&self) -> Option<&Origin> {
+        match self {
+            ReadTransaction::ReadOnly(_) => None,
+            ReadTransaction::ReadWrite(txn) => txn.origin(),
+        }
+    }
+}
+
 impl ReadTxn for ReadTransaction<'_, '_> {
     fn store(&self) -> &Store {
         match &self {
@@ -19,3 +98,44 @@ impl ReadTxn for ReadTransaction<'_, '_> {
         }
     }
 }
+
+/// Runs `f` on the transaction, or fails with `{:error, :no_transaction}` once committed.
+fn with_live_transaction<T>(
+    resource: &TransactionResource,
+    f: impl FnOnce(&TransactionMut<'static>) -> T,
+) -> NifResult<T> {
+    let no_transaction = || rustler::Error::Term(Box::new(atoms::no_transaction()));
+    let txn = resource.0.read().map_err(|_| no_transaction())?;
+    txn.as_ref().map(f).ok_or_else(no_transaction)
+}
+
+/// Origin the transaction was opened with, or `{:error, :no_transaction}` once committed.
+#[rustler::nif]
+fn transaction_origin(
+    env: Env<'_>,
+    current_transaction: ResourceArc<TransactionResource>,
+) -> NifResult<Term<'_>> {
+    with_live_transaction(&current_transaction, |txn| {
+        origin_to_term(&mut env.clone(), txn.origin())
+    })
+}
+
+#[rustler::nif]
+fn transaction_before_state(
+    env: Env<'_>,
+    current_transaction: ResourceArc<TransactionResource>,
+) -> NifResult<Term<'_>> {
+    let state = with_live_transaction(&current_transaction, |txn| txn.before_state().encode_v1())?;
+    Ok((atoms::ok(), SliceIntoBinary::new(state.as_slice())).encode(env))
+}
+
+/// yrs only fills in the after state on commit, so the state vector the transaction has
+/// reached so far is read from the store instead; it is what the after state will be.
+#[rustler::nif]
+fn transaction_after_state(
+    env: Env<'_>,
+    current_transaction: ResourceArc<TransactionResource>,
+) -> NifResult<Term<'_>> {
+    let state = with_live_transaction(&current_transaction, |txn| txn.state_vector().encode_v1())?;
+    Ok((atoms::ok(), SliceIntoBinary::new(state.as_slice())).encode(env))
+}
@@ -1,12 +1,22 @@
 use crate::{
-    atoms, shared_type::NifSharedType, utils::term_to_origin_binary, wrap::NifWrap,
-    yinput::NifSharedTypeInput, Error, NifDoc, ENV,
+    atoms,
+    shared_type::NifSharedType,
+    subscription::{NifSubscription, SubscriptionResource},
+    term_box::TermBox,
+    utils::{origin_to_term, term_to_origin_binary},
+    wrap::NifWrap,
+    yinput::NifSharedTypeInput,
+    youtput::NifYOut,
+    Error, NifDoc, ENV,
 };
 
-use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc, Term};
+use rustler::{Atom, Env, LocalPid, NifMap, NifResult, NifStruct, NifUnitEnum, ResourceArc, Term};
 use std::ops::Deref;
 use std::sync::RwLock;
-use yrs::{undo::Options as UndoOptions, UndoManager};
+use yrs::{
+    undo::{Event as UndoEvent, EventKind, Options as UndoOptions},
+    Origin, Out, TransactionMut, UndoManager,
+};
 
 #[derive(NifStruct)]
 #[module = "Yex.UndoManager"]
@@ -32,8 +42,44 @@ impl rustler::Resource for UndoManagerResource {}
 
 #[derive(NifStruct)]
 #[module = "Yex.UndoManager.Options"]
-pub struct NifUndoOptions {
+pub struct NifUndoOptions<'a> {
     pub capture_timeout: u64,
+    pub tracked_origins: Vec<Term<'a>>,
+}
+
+#[derive(NifUnitEnum)]
+pub enum NifUndoEventKind {
+    Undo,
+    Redo,
+}
+
+impl From<EventKind> for NifUndoEventKind {
+    fn from(kind: EventKind) -> Self {
+        match kind {
+            EventKind::Undo => NifUndoEventKind::Undo,
+            EventKind::Redo => NifUndoEventKind::Redo,
+        }
+    }
+}
+
+#[derive(NifStruct)]
+#[module = "Yex.UndoManager.Event"]
+pub struct NifUndoEvent {
+    kind: NifUndoEventKind,
+    changed_parent_types: Vec<NifYOut>,
+}
+
+impl NifUndoEvent {
+    fn new(doc: &NifDoc, event: &UndoEvent<()>) -> Self {
+        NifUndoEvent {
+            kind: event.kind().into(),
+            changed_parent_types: event
+                .changed_parent_types()
+                .iter()
+                .map(|branch| NifYOut::from_native(Out::from(*branch), doc.clone()))
+                .collect(),
+        }
+    }
 }
 
 #[rustler::nif]
@@ -64,6 +110,7 @@ fn create_undo_manager<T: NifSharedType>(
         scope,
         NifUndoOptions {
             capture_timeout: 500,
+            tracked_origins: Vec::new(),
         },
     )
 }
@@ -72,16 +119,24 @@ fn create_undo_manager_with_options<T: NifSharedType>(
     _env: Env<'_>,
     doc: NifDoc,
     scope: T,
-    options: NifUndoOptions,
+    options: NifUndoOptions<'_>,
 ) -> NifResult<(Atom, NifUndoManager)> {
     let branch = scope
         .readonly(None, |txn| scope.get_ref(txn))
         .map_err(|_| Error::Message("Failed to get branch reference".to_string()))?;
 
-    let undo_options = UndoOptions {
+    let mut undo_options = UndoOptions {
         capture_timeout_millis: options.capture_timeout,
         ..Default::default()
     };
+    // An empty list leaves the set untouched, which tracks every origin.
+    for origin in options.tracked_origins {
+        if let Some(origin) = term_to_origin_binary(origin) {
+            undo_options
+                .tracked_origins
+                .insert(Origin::from(origin.as_slice()));
+        }
+    }
 
     let undo_manager = UndoManager::with_scope_and_options(&doc, &branch, undo_options);
     let wrapper = UndoManagerWrapper::new(undo_manager);
@@ -100,7 +155,7 @@ pub fn undo_manager_new_with_options(
     env: Env<'_>,
     doc: NifDoc,
     scope: NifSharedTypeInput,
-    options: NifUndoOptions,
+    options: NifUndoOptions<'_>,
 ) -> NifResult<(Atom, NifUndoManager)> {
     // Check if the document reference is valid by attempting to access its inner doc
     // will return an error tuple if it is not
@@ -170,7 +225,7 @@ pub fn undo_manager_exclude_origin(
 }
 
 #[rustler::nif]
-pub fn undo_manager_undo(env: Env, undo_manager: NifUndoManager) -> NifResult<Atom> {
+pub fn undo_manager_undo(env: Env, undo_manager: NifUndoManager) -> NifResult<(Atom, Atom)> {
     ENV.set(&mut env.clone(), || {
         let mut wrapper = undo_manager
             .reference
@@ -180,14 +235,15 @@ pub fn undo_manager_undo(env: Env, undo_manager: NifUndoManager) -> NifResult<At
 
         if wrapper.manager.can_undo() {
             wrapper.manager.undo_blocking();
+            Ok((atoms::ok(), atoms::undone()))
+        } else {
+            Ok((atoms::ok(), atoms::nothing()))
         }
-
-        Ok(atoms::ok())
     })
 }
 
 #[rustler::nif]
-pub fn undo_manager_redo(env: Env, undo_manager: NifUndoManager) -> NifResult<Atom> {
+pub fn undo_manager_redo(env: Env, undo_manager: NifUndoManager) -> NifResult<(Atom, Atom)> {
     ENV.set(&mut env.clone(), || {
         let mut wrapper = undo_manager
             .reference
@@ -197,9 +253,52 @@ pub fn undo_manager_redo(env: Env, undo_manager: NifUndoManager) -> NifResult<At
 
         if wrapper.manager.can_redo() {
             wrapper.manager.redo_blocking();
+            Ok((atoms::ok(), atoms::redone()))
+        } else {
+            Ok((atoms::ok(), atoms::nothing()))
         }
+    })
+}
 
-        Ok(atoms::ok())
+#[rustler::nif]
+pub fn undo_manager_can_undo(undo_manager: NifUndoManager) -> NifResult<bool> {
+    let wrapper = undo_manager
+        .reference
+        .0
+        .read()
+        .map_err(|_| Error::Message("Failed to acquire read lock".to_string()))?;
+
+    Ok(wrapper.manager.can_undo())
+}
+
+#[rustler::nif]
+pub fn undo_manager_can_redo(undo_manager: NifUndoManager) -> NifResult<bool> {
+    let wrapper = undo_manager
+        .reference
+        .0
+        .read()
+        .map_err(|_| Error::Message("Failed to acquire read lock".to_string()))?;
+
+    Ok(wrapper.manager.can_redo())
+}
+
+#[derive(NifMap)]
+pub struct NifUndoStackSizes {
+    undo: usize,
+    redo: usize,
+}
+
+#[rustler::nif]
+pub fn undo_manager_stack_sizes(undo_manager: NifUndoManager) -> NifResult<NifUndoStackSizes> {
+    let wrapper = undo_manager
+        .reference
+        .0
+        .read()
+        .map_err(|_| Error::Message("Failed to acquire read lock".to_string()))?;
+
+    Ok(NifUndoStackSizes {
+        undo: wrapper.manager.undo_stack().len(),
+        redo: wrapper.manager.redo_stack().len(),
     })
 }
 
@@ -301,3 +400,110 @@ pub fn undo_manager_clear(env: Env, undo_manager: NifUndoManager) -> NifResult<A
         Ok(atoms::ok())
     })
 }
+
+type UndoObserveFn = fn(
+    &UndoManager,
+    Box<dyn Fn(&TransactionMut, &mut UndoEvent<()>) + Send + Sync + 'static>,
+) -> yrs::Subscription;
+
+fn observe_undo_event(
+    env: Env<'_>,
+    undo_manager: NifUndoManager,
+    pid: LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+    message: fn() -> Atom,
+    observe: UndoObserveFn,
+) -> NifResult<NifSubscription> {
+    ENV.set(&mut env.clone(), || {
+        let wrapper = undo_manager
+            .reference
+            .0
+            .read()
+            .map_err(|_| Error::Message("Failed to acquire read lock".to_string()))?;
+
+        let ref_box = TermBox::new(ref_term);
+        let metadata_box = TermBox::new(metadata);
+        let doc = undo_manager.doc.clone();
+
+        let sub = observe(
+            &wrapper.manager,
+            Box::new(move |txn, event| {
+                ENV.with(|env| {
+                    let _ = env.send(
+                        &pid,
+                        (
+                            message(),
+                            ref_box.get(*env),
+                            NifUndoEvent::new(&doc, event),
+                            origin_to_term(env, event.origin().or(txn.origin())),
+                            metadata_box.get(*env),
+                        ),
+                    );
+                })
+            }),
+        );
+
+        Ok(NifSubscription {
+            reference: SubscriptionResource::arc(sub),
+            doc: undo_manager.doc.clone(),
+        })
+    })
+}
+
+#[rustler::nif]
+pub fn undo_manager_observe_item_added(
+    env: Env<'_>,
+    undo_manager: NifUndoManager,
+    pid: LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+) -> NifResult<NifSubscription> {
+    observe_undo_event(
+        env,
+        undo_manager,
+        pid,
+        ref_term,
+        metadata,
+        atoms::item_added,
+        |manager, f| manager.observe_item_added(f),
+    )
+}
+
+#[rustler::nif]
+pub fn undo_manager_observe_item_updated(
+    env: Env<'_>,
+    undo_manager: NifUndoManager,
+    pid: LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+) -> NifResult<NifSubscription> {
+    observe_undo_event(
+        env,
+        undo_manager,
+        pid,
+        ref_term,
+        metadata,
+        atoms::item_updated,
+        |manager, f| manager.observe_item_updated(f),
+    )
+}
+
+#[rustler::nif]
+pub fn undo_manager_observe_item_popped(
+    env: Env<'_>,
+    undo_manager: NifUndoManager,
+    pid: LocalPid,
+    ref_term: Term<'_>,
+    metadata: Term<'_>,
+) -> NifResult<NifSubscription> {
+    observe_undo_event(
+        env,
+        undo_manager,
+        pid,
+        ref_term,
+        metadata,
+        atoms::item_popped,
+        |manager, f| manager.observe_item_popped(f),
+    )
+}
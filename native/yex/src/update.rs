@@ -0,0 +1,53 @@
+use rustler::{Binary, Encoder, Env, NifResult, Term};
+use yrs::updates::{decoder::Decode, encoder::Encode};
+use yrs::{Doc, ReadTxn, StateVector, Transact, Update};
+
+use crate::{atoms, error::Error, wrap::SliceIntoBinary};
+
+/// Merges updates into one without a document. Decoding failures surface as
+/// `{:error, {:encoding_exception, message}}`, and an empty list merges into an empty update.
+#[rustler::nif]
+fn merge_updates_v1<'a>(env: Env<'a>, updates: Vec<Binary<'a>>) -> NifResult<Term<'a>> {
+    let merged = if updates.is_empty() {
+        Update::new().encode_v1()
+    } else {
+        yrs::merge_updates_v1(updates.iter().map(Binary::as_slice)).map_err(Error::from)?
+    };
+    Ok((atoms::ok(), SliceIntoBinary::new(merged.as_slice())).encode(env))
+}
+
+#[rustler::nif]
+fn merge_updates_v2<'a>(env: Env<'a>, updates: Vec<Binary<'a>>) -> NifResult<Term<'a>> {
+    let merged = if updates.is_empty() {
+        Update::new().encode_v2()
+    } else {
+        yrs::merge_updates_v2(updates.iter().map(Binary::as_slice)).map_err(Error::from)?
+    };
+    Ok((atoms::ok(), SliceIntoBinary::new(merged.as_slice())).encode(env))
+}
+
+/// Encodes the part of `update` that a peer at `state_vector` is missing, without a document.
+#[rustler::nif]
+fn diff_update_v1<'a>(
+    env: Env<'a>,
+    update: Binary<'a>,
+    state_vector: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    StateVector::decode_v1(state_vector.as_slice())
+        .map_err(|_| rustler::Error::Term(Box::new(atoms::invalid_state_vector())))?;
+    let diff =
+        yrs::diff_updates_v1(update.as_slice(), state_vector.as_slice()).map_err(Error::from)?;
+    Ok((atoms::ok(), SliceIntoBinary::new(diff.as_slice())).encode(env))
+}
+
+/// State vector of the document `update` describes, read by loading it into a scratch document.
+/// This costs as much as applying the whole update.
+#[rustler::nif]
+fn state_vector_from_update_v1<'a>(env: Env<'a>, update: Binary<'a>) -> NifResult<Term<'a>> {
+    let update = Update::decode_v1(update.as_slice()).map_err(Error::from)?;
+    let doc = Doc::new();
+    let mut txn = doc.transact_mut();
+    txn.apply_update(update).map_err(Error::from)?;
+    let state_vector = txn.state_vector().encode_v1();
+    Ok((atoms::ok(), SliceIntoBinary::new(state_vector.as_slice())).encode(env))
+}
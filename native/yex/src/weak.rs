@@ -1,13 +1,18 @@
+use crate::atoms;
 use crate::doc::NifDoc;
-use crate::event::{NifSharedTypeDeepObservable, NifSharedTypeObservable, NifWeakLinkEvent};
-use crate::shared_type::{NifSharedType, SharedTypeId};
+use crate::event::{
+    NifPath, NifSharedTypeDeepObservable, NifSharedTypeObservable, NifWeakLinkEvent,
+};
+use crate::shared_type::{branch_path, item_index, NifSharedType, SharedTypeId};
 use crate::transaction::TransactionResource;
+use crate::wrap::NifWrap;
 use crate::yinput::NifWeakPrelim;
 use crate::youtput::NifYOut;
 
-use rustler::{NifResult, NifStruct, ResourceArc};
+use rustler::{Atom, NifMap, NifResult, NifStruct, ResourceArc};
+use yrs::block::ItemPtr;
 use yrs::branch::BranchPtr;
-use yrs::types::AsPrelim;
+use yrs::types::{AsPrelim, TypePtr};
 use yrs::*;
 
 pub type WeakLinkRefId = SharedTypeId<WeakRef<BranchPtr>>;
@@ -102,3 +107,61 @@ fn weak_as_prelim(
         Ok(value)
     })
 }
+
+#[derive(NifMap)]
+pub struct NifWeakSource {
+    path: NifPath,
+    key: Option<String>,
+    range: Option<(u32, u32)>,
+}
+
+/// Number of live elements preceding the element `id` held by `item`, and whether that
+/// element is live itself. Quotes are bounded by the IDs of their first and last elements.
+fn element_position(item: ItemPtr, id: &ID) -> (u32, bool) {
+    if item.is_deleted() {
+        (item_index(item), false)
+    } else {
+        (item_index(item) + (id.clock - item.id.clock), true)
+    }
+}
+
+#[rustler::nif]
+fn weak_source_path(
+    weak: NifWeakLink,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<(Atom, NifWeakSource)> {
+    weak.readonly(current_transaction, |txn| {
+        let weak_ref = weak.get_ref(txn)?;
+        let (id, item) = weak_ref
+            .start_id()
+            .and_then(|id| Some((*id, txn.store().blocks.get_item(id)?)))
+            .ok_or(rustler::Error::Atom("error"))?;
+        let TypePtr::Branch(parent) = item.parent else {
+            return Err(rustler::Error::Atom("error"));
+        };
+        let path = branch_path(parent).ok_or(rustler::Error::Atom("error"))?;
+
+        let key = item.parent_sub.as_ref().map(|key| key.to_string());
+        // Ranges are only meaningful for array quotes, where each value is one element.
+        let range = match (&key, Out::from(parent), weak_ref.end_id()) {
+            (None, Out::YArray(_), Some(end_id)) => {
+                let (start, _) = element_position(item, &id);
+                let end = txn.store().blocks.get_item(end_id).map(|end_item| {
+                    let (end, live) = element_position(end_item, end_id);
+                    end + live as u32
+                });
+                end.map(|end| (start, end.max(start)))
+            }
+            _ => None,
+        };
+
+        Ok((
+            atoms::ok(),
+            NifWeakSource {
+                path: NifWrap(path),
+                key,
+                range,
+            },
+        ))
+    })
+}
@@ -2,8 +2,8 @@ use std::collections::HashMap;
 
 use rustler::{Atom, Env, NifResult, NifStruct, ResourceArc};
 use yrs::{
-    types::text::YChange, GetString, SharedRef as _, Text, Xml, XmlElementRef, XmlFragment,
-    XmlFragmentRef, XmlTextRef,
+    types::text::YChange, GetString, ReadTxn, SharedRef as _, StickyIndex, Text, Xml,
+    XmlElementRef, XmlFragment, XmlFragmentRef, XmlOut, XmlTextRef,
 };
 
 use crate::{
@@ -12,6 +12,7 @@ use crate::{
     doc::NifDoc,
     event::{NifSharedTypeDeepObservable, NifSharedTypeObservable, NifXmlEvent, NifXmlTextEvent},
     shared_type::{NifSharedType, SharedTypeId},
+    sticky_index::NifStickyIndex,
     text::encode_diffs,
     transaction::TransactionResource,
     utils::{capped_index_and_length, normalize_index, normalize_index_for_insert},
@@ -233,6 +234,63 @@ fn xml_fragment_parent(
     })
 }
 
+/// Appends the descendants of `node` to `out` in document (pre-)order along with their depth,
+/// direct children being at depth 0.
+fn walk_xml_tree<N, T>(node: &N, txn: &T, depth: u32, out: &mut Vec<(u32, XmlOut)>)
+where
+    N: XmlFragment,
+    T: ReadTxn,
+{
+    for child in node.children(txn) {
+        out.push((depth, child.clone()));
+        match child {
+            XmlOut::Element(element) => walk_xml_tree(&element, txn, depth + 1, out),
+            XmlOut::Fragment(fragment) => walk_xml_tree(&fragment, txn, depth + 1, out),
+            XmlOut::Text(_) => {}
+        }
+    }
+}
+
+#[rustler::nif]
+fn xml_fragment_tree_walk(
+    xml: NifXmlFragment,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Vec<(u32, NifYOut)>> {
+    let doc = xml.doc();
+    xml.readonly(current_transaction, |txn| {
+        let xml = xml.get_ref(txn)?;
+        let mut nodes = Vec::new();
+        walk_xml_tree(&xml, txn, 0, &mut nodes);
+        Ok(nodes
+            .into_iter()
+            .map(|(depth, node)| (depth, NifYOut::from_xml_out(node, doc.clone())))
+            .collect())
+    })
+}
+
+#[rustler::nif]
+fn xml_fragment_select_by_tag(
+    xml: NifXmlFragment,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    tag: &str,
+) -> NifResult<Vec<NifXmlElement>> {
+    let doc = xml.doc();
+    xml.readonly(current_transaction, |txn| {
+        let xml = xml.get_ref(txn)?;
+        let mut nodes = Vec::new();
+        walk_xml_tree(&xml, txn, 0, &mut nodes);
+        Ok(nodes
+            .into_iter()
+            .filter_map(|(_, node)| match node {
+                XmlOut::Element(element) if element.try_tag().is_some_and(|t| &**t == tag) => {
+                    Some(NifXmlElement::new(doc.clone(), element))
+                }
+                _ => None,
+            })
+            .collect())
+    })
+}
+
 #[rustler::nif]
 fn xml_element_insert(
     env: Env<'_>,
@@ -298,6 +356,33 @@ fn xml_element_get(
     })
 }
 #[rustler::nif]
+fn xml_element_first_child(
+    xml: NifXmlElement,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Option<NifYOut>> {
+    let doc = xml.doc();
+    xml.readonly(current_transaction, |txn| {
+        let xml = xml.get_ref(txn)?;
+        Ok(xml
+            .first_child()
+            .map(|b| NifYOut::from_xml_out(b, doc.clone())))
+    })
+}
+#[rustler::nif]
+fn xml_element_children(
+    xml: NifXmlElement,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+) -> NifResult<Vec<NifYOut>> {
+    let doc = xml.doc();
+    xml.readonly(current_transaction, |txn| {
+        let xml = xml.get_ref(txn)?;
+        Ok(xml
+            .children(txn)
+            .map(|b| NifYOut::from_xml_out(b, doc.clone()))
+            .collect())
+    })
+}
+#[rustler::nif]
 fn xml_element_delete_range(
     env: Env<'_>,
     xml: NifXmlElement,
@@ -342,6 +427,21 @@ fn xml_element_insert_attribute(
     })
 }
 #[rustler::nif]
+fn xml_element_set_attributes(
+    env: Env<'_>,
+    xml: NifXmlElement,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    attrs: HashMap<String, NifYInput>,
+) -> NifResult<Atom> {
+    xml.mutably(env, current_transaction, |txn| {
+        let xml = xml.get_ref(txn)?;
+        for (key, value) in attrs {
+            xml.insert_attribute(txn, key, value);
+        }
+        Ok(atoms::ok())
+    })
+}
+#[rustler::nif]
 fn xml_element_get_attribute(
     xml: NifXmlElement,
     current_transaction: Option<ResourceArc<TransactionResource>>,
@@ -475,6 +575,30 @@ fn xml_text_insert_with_attributes(
     })
 }
 
+#[rustler::nif]
+fn xml_text_insert_at_sticky(
+    env: Env<'_>,
+    xml: NifXmlText,
+    current_transaction: Option<ResourceArc<TransactionResource>>,
+    sticky_index: NifStickyIndex,
+    chunk: &str,
+    attr: Option<NifAttr>,
+) -> NifResult<Atom> {
+    xml.mutably(env, current_transaction, |txn| {
+        let xml = xml.get_ref(txn)?;
+        // resolve and insert within the same transaction so no edit can land in between
+        let offset = StickyIndex::from(&sticky_index)
+            .get_offset(txn)
+            .ok_or(rustler::Error::Atom("error"))?;
+        let index = offset.index.min(xml.len(txn));
+        match attr {
+            Some(attr) => xml.insert_with_attributes(txn, index, chunk, attr.0),
+            None => xml.insert(txn, index, chunk),
+        }
+        Ok(atoms::ok())
+    })
+}
+
 #[rustler::nif]
 fn xml_text_delete(
     env: Env<'_>,
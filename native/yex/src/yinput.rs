@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
-    any::{NifAny, NifAttr},
+    any::{NifAny, NifAttr, NifTypedAny},
     array::NifArray,
     atoms,
     doc::NifDoc,
@@ -135,6 +135,7 @@ impl NifWeakPrelim {
 #[derive(NifUntaggedEnum)]
 pub enum NifYInput {
     Any(NifAny),
+    Typed(NifTypedAny),
     MapPrelim(NifMapPrelim),
     ArrayPrelim(NifArrayPrelim),
     TextPrelim(NifTextPrelim),
@@ -163,6 +164,7 @@ impl Prelim for NifYInput {
                 let value: Any = any.0;
                 (ItemContent::Any(vec![value]), None)
             }
+            NifYInput::Typed(typed) => (ItemContent::Any(vec![typed.0]), None),
             NifYInput::MapPrelim(_) => {
                 let inner = Branch::new(TypeRef::Map);
                 (ItemContent::Type(inner), Some(self))
@@ -207,6 +209,7 @@ impl Prelim for NifYInput {
                 let any = any.0;
                 any.integrate(txn, inner_ref);
             }
+            NifYInput::Typed(typed) => typed.0.integrate(txn, inner_ref),
             NifYInput::MapPrelim(v) => {
                 let map = MapRef::from(inner_ref);
                 for (key, value) in v.map {
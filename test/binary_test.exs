@@ -0,0 +1,30 @@
+defmodule Yex.BinaryTest do
+  use ExUnit.Case, async: true
+  alias Yex.{Doc, Map, Array}
+  doctest Yex.Binary
+
+  setup do
+    doc = Doc.new()
+    %{doc: doc, map: Doc.get_map(doc, "map"), array: Doc.get_array(doc, "array")}
+  end
+
+  test "round-trips UTF-8 binaries as buffers", %{map: map} do
+    Map.set(map, "blob", Yex.Binary.new("text"))
+    Map.set(map, "nested", %{"blob" => Yex.Binary.new(["a", ?b])})
+    Map.set(map, "string", "text")
+
+    assert %Yex.Binary{data: "text"} == Map.fetch!(map, "blob")
+    assert %{"blob" => %Yex.Binary{data: "ab"}} == Map.fetch!(map, "nested")
+    assert "text" == Map.fetch!(map, "string")
+  end
+
+  test "round-trips buffers through updates", %{doc: doc, array: array} do
+    Array.push(array, Yex.Binary.new(<<0, 255>>))
+
+    remote = Doc.new()
+    {:ok, update} = Yex.encode_state_as_update(doc)
+    :ok = Yex.apply_update(remote, update)
+
+    assert [%Yex.Binary{data: <<0, 255>>}] == Array.to_list(Doc.get_array(remote, "array"))
+  end
+end
@@ -183,6 +183,101 @@ defmodule Yex.DocTest do
     Doc.demonitor_update(monitor_ref)
   end
 
+  describe "rename_root" do
+    test "moves nested and formatted content to the new name" do
+      doc = Doc.new()
+      map = Doc.get_map(doc, "settings")
+      Yex.Map.set(map, "theme", "dark")
+      Yex.Map.set(map, "list", Yex.ArrayPrelim.from([1, Yex.MapPrelim.from(%{"a" => true})]))
+
+      text = Doc.get_text(doc, "title")
+      Text.insert(text, 0, "Hello")
+      Text.format(text, 0, 5, %{"bold" => true})
+
+      %Yex.Map{} = renamed = Doc.rename_root(doc, "settings", "config", :map)
+      assert %{"theme" => "dark", "list" => [1.0, %{"a" => true}]} == Yex.Map.to_json(renamed)
+      assert %{} == Yex.Map.to_json(map)
+
+      %Text{} = renamed = Doc.rename_root(doc, "title", "heading", :text)
+      assert [%{insert: "Hello", attributes: %{"bold" => true}}] == Text.to_delta(renamed)
+      assert "" == Text.to_string(text)
+    end
+
+    test "refuses to overwrite an existing root" do
+      doc = Doc.new()
+      Doc.get_array(doc, "a")
+      Doc.get_array(doc, "b")
+      assert {:error, :already_exists} == Doc.rename_root(doc, "a", "b", :array)
+    end
+  end
+
+  test "memory_estimate follows the document size" do
+    doc = Doc.new()
+    text = Doc.get_text(doc, "text")
+    empty = Doc.memory_estimate(doc)
+
+    :ok = Text.insert(text, 0, String.duplicate("a", 10_000))
+    filled = Doc.memory_estimate(doc)
+    assert filled >= empty + 10_000
+
+    {:ok, update} = Yex.encode_state_as_update(doc)
+    assert filled == byte_size(update)
+  end
+
+  test "new_pair creates docs with the given client ids" do
+    {doc_a, doc_b} = Doc.new_pair(10, 20)
+    assert 10 == Doc.client_id(doc_a)
+    assert 20 == Doc.client_id(doc_b)
+
+    :ok = Text.insert(Doc.get_text(doc_a, "text"), 0, "A")
+    :ok = Text.insert(Doc.get_text(doc_b, "text"), 0, "B")
+    {:ok, update_a} = Yex.encode_state_as_update(doc_a)
+    {:ok, update_b} = Yex.encode_state_as_update(doc_b)
+    :ok = Yex.apply_update(doc_a, update_b)
+    :ok = Yex.apply_update(doc_b, update_a)
+
+    assert Text.to_string(Doc.get_text(doc_a, "text")) ==
+             Text.to_string(Doc.get_text(doc_b, "text"))
+
+    assert_raise ArgumentError, fn -> Doc.new_pair(1, 1) end
+  end
+
+  test "monitor_update with stats" do
+    doc = Doc.new()
+    text = Doc.get_text(doc, "text")
+    {:ok, monitor_ref} = Doc.monitor_update(doc, stats: true, metadata: :meta)
+
+    :ok = Text.insert(text, 0, "Hello World")
+    assert_receive {:update_v1, _update, nil, :meta, %{added: 11, deleted: 0}}
+
+    Doc.transaction(doc, fn ->
+      :ok = Text.delete(text, 0, 6)
+      :ok = Text.insert(text, 0, "Hi ")
+    end)
+
+    assert_receive {:update_v1, _update, nil, :meta, %{added: 3, deleted: 6}}
+    Doc.demonitor_update(monitor_ref)
+  end
+
+  test "mutations accept an origin option" do
+    doc = Doc.new()
+    {:ok, monitor_ref} = Doc.monitor_update(doc)
+
+    :ok = Yex.Map.set(Doc.get_map(doc, "map"), "key", "value", origin: "map")
+    assert_receive {:update_v1, _update, "map", ^doc}
+    :ok = Yex.Array.insert(Doc.get_array(doc, "array"), 0, 1, origin: "array")
+    assert_receive {:update_v1, _update, "array", ^doc}
+    :ok = Text.insert(Doc.get_text(doc, "text"), 0, "Hello", %{}, origin: "text")
+    assert_receive {:update_v1, _update, "text", ^doc}
+
+    Doc.transaction(doc, "outer", fn ->
+      :ok = Text.insert(Doc.get_text(doc, "text"), 0, "!", %{}, origin: "ignored")
+    end)
+
+    assert_receive {:update_v1, _update, "outer", ^doc}
+    Doc.demonitor_update(monitor_ref)
+  end
+
   test "origin accepts any types" do
     doc = Doc.new()
     {:ok, monitor_ref} = Doc.monitor_update(doc)
@@ -304,6 +399,328 @@ defmodule Yex.DocTest do
     end
   end
 
+  describe "snapshot" do
+    test "restores past versions and annotates the changes made since" do
+      doc = Doc.with_options(%Doc.Options{skip_gc: true})
+      text = Doc.get_text(doc, "text")
+      Yex.Text.insert(text, 0, "Hello World")
+      {:ok, before} = Doc.snapshot(doc)
+
+      Yex.Text.delete(text, 5, 6)
+      Yex.Text.insert(text, 5, "!")
+      {:ok, current} = Doc.snapshot(doc)
+
+      {:ok, update} = Yex.encode_state_as_update_from_snapshot(doc, before)
+      restored = Doc.new()
+      Yex.apply_update(restored, update)
+      assert "Hello World" == restored |> Doc.get_text("text") |> Yex.Text.to_string()
+
+      {:ok, sv} = Yex.encode_state_vector(restored)
+      {:ok, diff} = Yex.encode_state_as_update_from_snapshot(doc, current, sv)
+      Yex.apply_update(restored, diff)
+      assert "Hello!" == restored |> Doc.get_text("text") |> Yex.Text.to_string()
+
+      delta = Yex.Text.to_delta(text, current, before)
+      assert %{insert: "Hello"} == hd(delta)
+      assert %{insert: "!", attributes: %{"ychange" => %{"kind" => "added"}}} in delta
+      assert %{insert: " World", attributes: %{"ychange" => %{"kind" => "removed"}}} in delta
+    end
+
+    test "cannot be restored when gc is enabled" do
+      doc = Doc.new()
+      {:ok, snapshot} = Doc.snapshot(doc)
+
+      assert {:error, :gc_enabled} == Yex.encode_state_as_update_from_snapshot(doc, snapshot)
+    end
+  end
+
+  describe "canonical_state" do
+    test "is equal for documents that received the same updates in a different order" do
+      updates =
+        for client_id <- [1, 2, 3] do
+          doc = Doc.with_options(%Doc.Options{client_id: client_id})
+          text = Doc.get_text(doc, "text")
+          Text.insert(text, 0, "client #{client_id}")
+          Text.delete(text, 0, 2)
+          {:ok, update} = Yex.encode_state_as_update(doc)
+          update
+        end
+
+      states =
+        for order <- [updates, Enum.reverse(updates)] do
+          doc = Doc.new()
+          Enum.each(order, &Yex.apply_update(doc, &1))
+          {:ok, state} = Doc.canonical_state(doc)
+          state
+        end
+
+      assert [state, state] = states
+
+      doc = Doc.new()
+      Enum.each(updates, &Yex.apply_update(doc, &1))
+      Doc.get_text(doc, "text") |> Text.insert(0, "more")
+      assert {:ok, other} = Doc.canonical_state(doc)
+      assert other != state
+    end
+  end
+
+  describe "transaction contention" do
+    test "operations opening their own transaction report a retriable error" do
+      doc = Doc.new()
+      text = Doc.get_text(doc, "text")
+
+      Doc.transaction(doc, fn ->
+        # Hide the running transaction so the insert tries to open a second one.
+        txn = Process.delete(doc.reference)
+        assert {:error, :transaction_busy} = Text.insert(text, 0, "busy")
+        Process.put(doc.reference, txn)
+
+        :ok = Text.insert(text, 0, "Hello")
+      end)
+
+      assert :ok = Text.insert(text, 5, "!")
+      assert "Hello!" == Text.to_string(text)
+    end
+  end
+
+  describe "force_commit_all" do
+    test "commits a transaction left open" do
+      doc = Doc.new()
+      text = Doc.get_text(doc, "text")
+      txn = Yex.Nif.doc_begin_transaction(doc, nil)
+      Yex.Nif.text_insert(text, txn, 0, "Hello", nil)
+
+      assert 1 == Doc.force_commit_all(doc)
+      assert 0 == Doc.force_commit_all(doc)
+      assert "Hello" == Yex.Text.to_string(text)
+      assert :ok == Yex.Text.insert(text, 5, "!")
+    end
+
+    test "writes stay busy until a transaction left open is committed" do
+      doc = Doc.new()
+      text = Doc.get_text(doc, "text")
+      txn = Yex.Nif.doc_begin_transaction(doc, nil)
+
+      assert {:error, :transaction_busy} = Yex.Text.insert(text, 0, "busy")
+      assert :ok == Yex.Nif.text_insert(text, txn, 0, "Hello", nil)
+      assert 1 == Doc.force_commit_all(doc)
+      assert :ok == Yex.Text.insert(text, 5, "!")
+      assert "Hello!" == Yex.Text.to_string(text)
+    end
+  end
+
+  describe "init_roots" do
+    test "creates all roots in a single transaction" do
+      doc = Doc.new()
+      {:ok, _} = Doc.monitor_update(doc)
+
+      roots =
+        Doc.init_roots(doc, [
+          {"text", :text},
+          {"array", :array},
+          {"map", :map},
+          {"xml", :xml_fragment}
+        ])
+
+      assert %{
+               "text" => %Yex.Text{},
+               "array" => %Yex.Array{},
+               "map" => %Yex.Map{},
+               "xml" => %Yex.XmlFragment{}
+             } = roots
+
+      Doc.transaction(doc, fn ->
+        Yex.Text.insert(roots["text"], 0, "hello")
+        Yex.Map.set(roots["map"], "key", "value")
+      end)
+
+      assert "hello" == Doc.get_text(doc, "text") |> Yex.Text.to_string()
+      assert_receive {:update_v1, _, _, _}
+      refute_receive {:update_v1, _, _, _}
+    end
+
+    test "rejects unknown kinds" do
+      assert_raise ArgumentError, fn -> Doc.init_roots(Doc.new(), [{"a", :unknown}]) end
+    end
+  end
+
+  describe "has_type?" do
+    test "does not create the type" do
+      doc = Doc.new()
+      refute Doc.has_type?(doc, "text")
+      refute Doc.has_type?(doc, "text")
+
+      {:ok, update} = Yex.encode_state_as_update(doc)
+      doc2 = Doc.new()
+      :ok = Yex.apply_update(doc2, update)
+      refute Doc.has_type?(doc2, "text")
+    end
+
+    test "works inside a transaction" do
+      doc = Doc.new()
+      Doc.get_text(doc, "text")
+
+      Doc.transaction(doc, fn ->
+        assert Doc.has_type?(doc, "text")
+        refute Doc.has_type?(doc, "map")
+      end)
+    end
+  end
+
+  describe "get_shared_types" do
+    test "returns every root with its type" do
+      doc = Doc.new()
+      Doc.get_text(doc, "text")
+      Doc.get_array(doc, "array")
+      Doc.get_map(doc, "map")
+      Doc.get_xml_fragment(doc, "xml")
+
+      assert %{
+               "text" => %Yex.Text{},
+               "array" => %Yex.Array{},
+               "map" => %Yex.Map{},
+               "xml" => %Yex.XmlFragment{}
+             } = Doc.get_shared_types(doc)
+
+      assert 4 == map_size(Doc.get_shared_types(doc))
+      assert %{} == Doc.get_shared_types(Doc.new())
+    end
+  end
+
+  describe "current_origin" do
+    test "returns the origin of the running transaction" do
+      doc = Doc.new()
+      origin = %{user: "alice"}
+
+      assert Doc.transaction(doc, origin, fn -> Doc.current_origin(doc) end) == origin
+      assert Doc.transaction(doc, fn -> Doc.current_origin(doc) end) == nil
+      assert Doc.current_origin(doc) == nil
+    end
+
+    test "transaction_origin reads the transaction resource" do
+      doc = Doc.new()
+      txn = Yex.Nif.doc_begin_transaction(doc, "origin")
+      assert "origin" == Yex.Nif.transaction_origin(txn)
+
+      Yex.Nif.commit_transaction(txn)
+      assert {:error, :no_transaction} == Yex.Nif.transaction_origin(txn)
+      assert nil == Doc.transaction(doc, fn -> Doc.transaction_origin(doc) end)
+    end
+
+    test "before and after states bound the changes of the transaction" do
+      doc = Doc.new()
+      text = Doc.get_text(doc, "text")
+      Yex.Text.insert(text, 0, "Hello")
+      remote = Doc.new()
+      Yex.apply_update(remote, Yex.encode_state_as_update!(doc))
+
+      {before, after_state} =
+        Doc.transaction(doc, fn ->
+          Yex.Text.insert(text, 5, " World")
+          {:ok, before} = Doc.transaction_before_state(doc)
+          {:ok, after_state} = Doc.transaction_after_state(doc)
+          {before, after_state}
+        end)
+
+      {:ok, sv} = Yex.encode_state_vector(doc)
+      assert after_state == sv
+      assert before != sv
+
+      {:ok, update} = Yex.encode_state_as_update(doc, before)
+      assert :ok = Yex.apply_update(remote, update)
+      assert "Hello World" == remote |> Doc.get_text("text") |> Yex.Text.to_string()
+      assert {:error, :no_transaction} == Doc.transaction_after_state(doc)
+    end
+  end
+
+  describe "consistent?" do
+    test "is false until the missing dependency arrives" do
+      source = Doc.new()
+      text = Doc.get_text(source, "text")
+      Yex.Text.insert(text, 0, "a")
+      {:ok, sv} = Yex.encode_state_vector(source)
+      {:ok, first} = Yex.encode_state_as_update(source)
+      Yex.Text.insert(text, 1, "b")
+      {:ok, second} = Yex.encode_state_as_update(source, sv)
+
+      doc = Doc.new()
+      :ok = Yex.apply_update(doc, second)
+      refute Doc.consistent?(doc)
+      assert "" == Doc.get_text(doc, "text") |> Yex.Text.to_string()
+
+      :ok = Yex.apply_update(doc, first)
+      assert Doc.consistent?(doc)
+      assert "ab" == Doc.get_text(doc, "text") |> Yex.Text.to_string()
+    end
+  end
+
+  describe "destroy" do
+    test "makes further operations fail" do
+      doc = Doc.new()
+      Doc.get_text(doc, "text") |> Yex.Text.insert(0, "a")
+
+      assert :ok = Doc.destroy(doc)
+      assert {:error, :destroyed} = Yex.encode_state_vector(doc)
+      assert {:error, :destroyed} = Doc.get_text(doc, "text")
+      assert {:error, :destroyed} = Doc.destroy(doc)
+    end
+
+    test "makes every handle on the document fail" do
+      doc = Doc.new()
+      folder = Doc.get_map(doc, "folder")
+      Yex.Map.set(folder, "a.txt", Doc.new())
+      sub_doc = Yex.Map.fetch!(folder, "a.txt")
+      other = Yex.Map.fetch!(folder, "a.txt")
+
+      assert :ok = Doc.destroy(sub_doc)
+      assert {:error, :destroyed} = Yex.encode_state_vector(other)
+    end
+
+    test "destroys the sub-documents of a root document" do
+      doc = Doc.new()
+      sub_doc = Doc.get_map(doc, "folder") |> Yex.Map.set_and_get("a.txt", Doc.new())
+
+      assert :ok = Doc.destroy(doc)
+      assert {:error, :destroyed} = Yex.encode_state_vector(sub_doc)
+    end
+
+    test "notifies destroy monitors" do
+      doc = Doc.new()
+      {:ok, _sub} = Doc.monitor_destroy(doc, metadata: :meta)
+
+      assert :ok = Doc.destroy(doc)
+      assert_receive {:destroy, nil, :meta}
+      assert {:error, :destroyed} = Doc.monitor_destroy(doc)
+    end
+
+    test "notifies destroy monitors of a sub-document" do
+      doc = Doc.new()
+      folder = Doc.get_map(doc, "folder")
+      sub_doc = Yex.Map.set_and_get(folder, "a.txt", Doc.new())
+      {:ok, _sub} = Doc.monitor_destroy(sub_doc)
+
+      Doc.transaction(doc, "remote", fn ->
+        assert :ok = Doc.destroy(sub_doc, doc)
+      end)
+
+      assert_receive {:destroy, _origin, ^sub_doc}
+    end
+
+    test "replaces a sub-document in its parent" do
+      doc = Doc.new()
+      folder = Doc.get_map(doc, "folder")
+      sub_doc = Yex.Map.set_and_get(folder, "a.txt", Doc.new())
+      Doc.monitor_subdocs(doc)
+
+      assert :ok = Doc.destroy(sub_doc)
+
+      assert_receive {:subdocs, %{added: [added], removed: [removed]}, _, ^doc}
+      assert Doc.guid(sub_doc) == Doc.guid(removed)
+      assert Doc.guid(sub_doc) == Doc.guid(added)
+      refute Doc.loaded?(added)
+    end
+  end
+
   describe "get_xml_fragment" do
     test "creates and retrieves xml fragment" do
       doc = Doc.new()
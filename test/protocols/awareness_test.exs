@@ -18,6 +18,31 @@ defmodule Yex.AwarenessTest do
     assert %{"key" => "value"} === Awareness.get_local_state(awareness)
   end
 
+  test "set_local_state_field" do
+    {:ok, awareness} = Awareness.new(Yex.Doc.new())
+
+    assert :ok = Awareness.set_local_state_field(awareness, "status", "idle")
+    assert :ok = Awareness.set_local_state_field(awareness, "status", "typing")
+    assert %{"status" => "typing"} === Awareness.get_local_state(awareness)
+
+    Awareness.set_local_state(awareness, "not a map")
+    assert {:error, :not_a_map} = Awareness.set_local_state_field(awareness, "status", "idle")
+  end
+
+  test "remove_outdated reaps stale remote states" do
+    {:ok, awareness} = Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{client_id: 10}))
+    Awareness.set_local_state(awareness, %{"name" => "local"})
+    Awareness.apply_update(awareness, <<1, 210, 165, 202, 167, 8, 1, 2, 123, 125>>)
+
+    assert %{2_230_489_810 => %{clock: 1}, 10 => %{clock: 1}} =
+             Awareness.get_states_with_meta(awareness)
+
+    assert [] == Awareness.remove_outdated(awareness, 60_000)
+    Process.sleep(20)
+    assert [2_230_489_810] == Awareness.remove_outdated(awareness, 10)
+    assert [10] == Awareness.get_client_ids(awareness)
+  end
+
   test "monitor_update" do
     {:ok, awareness} = Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{client_id: 10}))
     monitor_ref = Awareness.monitor_update(awareness)
@@ -79,4 +104,23 @@ defmodule Yex.AwarenessTest do
     assert_receive {:awareness_change, %{removed: [], added: [2_230_489_810], updated: []},
                     "origin", _awareness}
   end
+
+  test "diff sends only states the remote is missing or has outdated" do
+    {:ok, awareness} = Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{client_id: 10}))
+    Awareness.set_local_state(awareness, %{"key" => "value"})
+    Awareness.apply_update(awareness, <<1, 210, 165, 202, 167, 8, 1, 2, 123, 125>>)
+
+    {:ok, remote} = Awareness.new(Yex.Doc.with_options(%Yex.Doc.Options{client_id: 20}))
+    {:ok, update} = Awareness.diff(awareness, %{2_230_489_810 => 1, 10 => 0})
+    Awareness.apply_update(remote, update)
+
+    assert %{10 => %{"key" => "value"}} == Map.delete(Awareness.get_states(remote), 20)
+  end
+
+  test "diff rejects invalid clocks" do
+    {:ok, awareness} = Awareness.new(Yex.Doc.new())
+
+    assert_raise ArgumentError, fn -> Awareness.diff(awareness, %{"client" => 1}) end
+    assert_raise ArgumentError, fn -> Awareness.diff(awareness, %{1 => -1}) end
+  end
 end
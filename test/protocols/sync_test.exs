@@ -75,6 +75,44 @@ defmodule Yex.SyncTest do
     end
   end
 
+  describe "message_decode_stream" do
+    test "returns the number of bytes consumed" do
+      {:ok, step1} = Sync.message_encode({:sync, {:sync_step1, <<1, 2, 3>>}})
+      {:ok, update} = Sync.message_encode({:sync, {:sync_update, <<4, 5>>}})
+      buffer = step1 <> update <> <<3>>
+
+      assert {:ok, {:sync, {:sync_step1, <<1, 2, 3>>}}, consumed} =
+               Sync.message_decode_stream(buffer)
+
+      assert consumed == byte_size(step1)
+      <<_::binary-size(consumed), rest::binary>> = buffer
+
+      assert {:ok, {:sync, {:sync_update, <<4, 5>>}}, consumed} =
+               Sync.message_decode_stream(rest)
+
+      <<_::binary-size(consumed), rest::binary>> = rest
+      assert {:ok, :query_awareness, 1} = Sync.message_decode_stream(rest)
+    end
+
+    test "reports incomplete messages" do
+      assert {:incomplete, 1} = Sync.message_decode_stream(<<>>)
+      assert {:incomplete, 1} = Sync.message_decode_stream(<<0>>)
+      assert {:incomplete, 1} = Sync.message_decode_stream(<<1, 0x80>>)
+      assert {:incomplete, 6} = Sync.message_decode_stream(<<0, 0, 7, 1>>)
+      assert {:incomplete, 2} = Sync.message_decode_stream(<<2, 0, 4, 116, 101>>)
+    end
+
+    test "every prefix of a message is incomplete" do
+      {:ok, message} = Sync.message_encode({:auth, "permission denied"})
+
+      for size <- 0..(byte_size(message) - 1) do
+        assert {:incomplete, _} = Sync.message_decode_stream(binary_part(message, 0, size))
+      end
+
+      assert {:ok, {:auth, "permission denied"}, _} = Sync.message_decode_stream(message)
+    end
+  end
+
   describe "message_encode" do
     test "sync_step1" do
       {:ok, <<0, 0, 1, 0>>} = Sync.message_encode({:sync, {:sync_step1, <<0>>}})
@@ -232,6 +270,25 @@ defmodule Yex.SyncTest do
     end
   end
 
+  test "every advertised message type round-trips" do
+    samples = %{
+      sync: {:sync, {:sync_update, <<0, 0>>}},
+      awareness: {:awareness, <<1, 2, 3>>},
+      auth: {:auth, "denied"},
+      query_awareness: :query_awareness,
+      custom: {:custom, 100, <<4, 5>>}
+    }
+
+    {version, supported} = Map.pop(Sync.capabilities(), :version)
+    assert version >= 1
+
+    for {tag, true} <- supported do
+      message = Map.fetch!(samples, tag)
+      assert {:ok, encoded} = Sync.message_encode(message)
+      assert {:ok, ^message} = Sync.message_decode(encoded)
+    end
+  end
+
   test "get_sync_step1" do
     doc = Doc.new()
 
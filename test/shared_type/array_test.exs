@@ -94,6 +94,23 @@ defmodule Yex.ArrayTest do
       assert ["first", "second"] = Array.to_list(array)
     end
 
+    test "push_list/2 appends multiple elements", %{array: array} do
+      Array.push(array, "first")
+      assert :ok = Array.push_list(array, ["second", "third"])
+      assert :ok = Array.push_list(array, [])
+      assert ["first", "second", "third"] == Array.to_list(array)
+    end
+
+    test "push/2 and push_list/2 inside a transaction", %{doc: doc, array: array} do
+      Doc.transaction(doc, fn ->
+        Array.push(array, 1)
+        Array.push_list(array, [2, 3])
+        Array.push(array, 4)
+      end)
+
+      assert [1, 2, 3, 4] == Array.to_json(array)
+    end
+
     test "push_and_get/2 pushes and returns the element", %{array: array} do
       assert "first" = Array.push_and_get(array, "first")
       assert "second" = Array.push_and_get(array, "second")
@@ -120,6 +137,40 @@ defmodule Yex.ArrayTest do
       assert ["first", "second"] == Array.to_list(array)
     end
 
+    test "insert_list/3 mixes prelims and plain values in order", %{array: array} do
+      Array.insert_list(array, 0, [1, 2])
+      assert :ok = Array.insert_list(array, -1, [])
+
+      assert :ok =
+               Array.insert_list(array, 1, [
+                 ArrayPrelim.from(["x"]),
+                 "plain",
+                 Yex.MapPrelim.from(%{"k" => 1})
+               ])
+
+      assert [1.0, ["x"], "plain", %{"k" => 1.0}, 2.0] == Array.to_json(array)
+    end
+
+    test "clear/1 removes all elements in one transaction", %{doc: doc, array: array} do
+      assert :ok = Array.clear(array)
+
+      Array.insert_list(array, 0, [1, 2])
+      Array.push(array, ArrayPrelim.from([3]))
+      {:ok, _ref} = Doc.monitor_update(doc)
+
+      assert :ok = Array.clear(array)
+      assert [] == Array.to_list(array)
+      assert_receive {:update_v1, _, nil, _}
+      refute_receive {:update_v1, _, nil, _}
+    end
+
+    test "push/2 and unshift/2 accept prelims", %{array: array} do
+      assert :ok = Array.push(array, ArrayPrelim.from([1]))
+      assert :ok = Array.unshift(array, Yex.MapPrelim.from(%{"k" => "v"}))
+      assert [%Yex.Map{}, %Array{}] = Array.to_list(array)
+      assert [%{"k" => "v"}, [1.0]] == Array.to_json(array)
+    end
+
     test "delete/2 removes element at index", %{array: array} do
       Array.insert_list(array, 0, [1, 2, 3])
       assert :ok = Array.delete(array, 1)
@@ -154,6 +205,7 @@ defmodule Yex.ArrayTest do
       assert_raise Yex.DeletedSharedTypeError, fn -> Array.push(deleted_array, "value") end
       assert_raise Yex.DeletedSharedTypeError, fn -> Array.length(deleted_array) end
       assert_raise Yex.DeletedSharedTypeError, fn -> Array.fetch(deleted_array, 0) end
+      assert_raise Yex.DeletedSharedTypeError, fn -> Array.clear(deleted_array) end
     end
   end
 
@@ -241,12 +293,17 @@ defmodule Yex.ArrayTest do
       assert [] = Array.slice_take_every(array, 0, 3, 0)
     end
 
-    test "slice_take_every/4 with invalid step raises error", %{array: array} do
+    test "slice_take_every/4 with negative step walks backwards", %{array: array} do
       Array.insert_list(array, 0, [1, 2, 3, 4, 5])
 
-      assert_raise FunctionClauseError, fn ->
-        Array.slice_take_every(array, 0, 3, -1)
-      end
+      assert [5.0, 4.0, 3.0] = Array.slice_take_every(array, 0, 3, -1)
+      assert [4.0, 2.0] = Array.slice_take_every(array, 1, 4, -2)
+      assert [] = Array.slice_take_every(array, 10, 3, -1)
+      assert [] = Array.slice_take_every(array, 10, 3, 1)
+    end
+
+    test "slice_take_every/4 with invalid step raises error", %{array: array} do
+      Array.insert_list(array, 0, [1, 2, 3, 4, 5])
 
       assert_raise FunctionClauseError, fn ->
         apply(Array, :slice_take_every, [array, 0, 3, 1.5])
@@ -383,6 +440,21 @@ defmodule Yex.ArrayTest do
     assert [["Hello"], ["Hello2"]] = Array.to_json(array)
   end
 
+  test "to_json_chunked and json_size" do
+    doc = Doc.new()
+    array = Doc.get_array(doc, "array")
+    assert [] == Array.to_json_chunked(array, 2)
+    assert 0 == Array.json_size(array)
+
+    Array.insert_list(array, 0, [1, "two", %{"three" => 3}])
+    Array.insert(array, 3, ArrayPrelim.from(["four"]))
+
+    assert [[1.0, "two"], [%{"three" => 3.0}, ["four"]]] == Array.to_json_chunked(array, 2)
+    assert [Array.to_json(array)] == Array.to_json_chunked(array, 4)
+    assert 7 == Array.json_size(array)
+    assert_raise FunctionClauseError, fn -> Array.to_json_chunked(array, 0) end
+  end
+
   test "transaction" do
     doc = Doc.new()
 
@@ -429,6 +501,111 @@ defmodule Yex.ArrayTest do
     assert byte_size(update) < 50
   end
 
+  test "element_id is unique per element and shared across replicas" do
+    doc = Yex.Doc.new()
+    array = Yex.Doc.get_array(doc, "array")
+    Yex.Array.insert_list(array, 0, [1, 2, 3])
+
+    ids = for i <- 0..2, do: Yex.Array.element_id(array, i)
+    assert 3 == ids |> Enum.uniq() |> length()
+    assert Yex.Array.element_id(array, -1) == Enum.at(ids, 2)
+
+    remote_doc = Yex.Doc.new()
+    {:ok, update} = Yex.encode_state_as_update(doc)
+    Yex.apply_update(remote_doc, update)
+    remote_array = Yex.Doc.get_array(remote_doc, "array")
+    Yex.Array.delete(remote_array, 0)
+
+    assert Yex.Array.element_id(remote_array, 0) == Enum.at(ids, 1)
+    assert :error == Yex.Array.element_id(remote_array, 2)
+  end
+
+  test "index_of only matches scalar elements" do
+    doc = Yex.Doc.new()
+    array = Yex.Doc.get_array(doc, "array")
+    Yex.Array.insert_list(array, 0, [Yex.MapPrelim.from(%{"a" => 1}), %{"a" => 1}, 2])
+
+    assert {:ok, 1} == Yex.Array.index_of(array, %{"a" => 1})
+    assert {:ok, 2} == Yex.Array.index_of(array, 2)
+    assert :error == Yex.Array.index_of(array, 3)
+  end
+
+  test "content_hash matches for equal content across documents" do
+    array = Yex.Doc.new() |> Yex.Doc.get_array("array")
+    other = Yex.Doc.new() |> Yex.Doc.get_array("array")
+    Yex.Array.insert_list(array, 0, [1, %{"b" => true, "a" => nil}, "text"])
+    Yex.Array.push(other, 1)
+    Yex.Array.push(other, %{"a" => nil, "b" => true})
+    Yex.Array.push(other, "text")
+
+    assert Yex.Array.content_hash(array) == Yex.Array.content_hash(other)
+
+    Yex.Array.move_to(other, 0, 3)
+    assert Yex.Array.content_hash(array) != Yex.Array.content_hash(other)
+  end
+
+  test "deep_equal? compares nested content with a plain term" do
+    array = Yex.Doc.new() |> Yex.Doc.get_array("array")
+    Yex.Array.insert_list(array, 0, [1, ["nested", 2.5], %{"key" => [true]}])
+
+    assert Yex.Array.deep_equal?(array, [1, ["nested", 2.5], %{"key" => [true]}])
+    refute Yex.Array.deep_equal?(array, [1, ["nested", 2.5], %{"key" => [false]}])
+    refute Yex.Array.deep_equal?(array, [1, ["nested", 2.5]])
+  end
+
+  test "remove_where removes matching map elements in one transaction" do
+    doc = Yex.Doc.new()
+    array = Yex.Doc.get_array(doc, "array")
+
+    Yex.Array.insert_list(array, 0, [
+      %{"id" => 1},
+      %{"id" => 2},
+      %{"id" => 1},
+      %{"id" => 1},
+      1,
+      %{"other" => 1}
+    ])
+
+    Yex.Array.push(array, Yex.MapPrelim.from(%{"id" => 1}))
+    {:ok, _ref} = Yex.Doc.monitor_update(doc)
+
+    assert 3 == Yex.Array.remove_where(array, "id", 1)
+    assert_receive {:update_v1, _, nil, _}
+    refute_receive {:update_v1, _, nil, _}
+
+    assert [%{"id" => 2.0}, 1.0, %{"other" => 1.0}, %Yex.Map{}] = Yex.Array.to_list(array)
+    assert 0 == Yex.Array.remove_where(array, "id", 3)
+  end
+
+  describe "iterator" do
+    test "reads a consistent view in batches until closed" do
+      doc = Yex.Doc.new()
+      array = Yex.Doc.get_array(doc, "array")
+      Yex.Array.insert_list(array, 0, Enum.to_list(1..5))
+
+      {:ok, iter} = Yex.Array.iter_open(array)
+      assert {:ok, [1.0, 2.0]} = Yex.Array.iter_next(iter, 2)
+      assert {:error, :transaction_busy} = Yex.Array.push(array, 6)
+      assert {:ok, [3.0, 4.0, 5.0]} = Yex.Array.iter_next(iter, 10)
+      assert {:ok, []} = Yex.Array.iter_next(iter, 10)
+
+      assert :ok = Yex.Array.iter_close(iter)
+      assert :ok = Yex.Array.iter_close(iter)
+      assert {:error, :iterator_closed} = Yex.Array.iter_next(iter, 1)
+      assert :ok = Yex.Array.push(array, 6)
+    end
+
+    test "stream closes the iterator when halted early" do
+      doc = Yex.Doc.new()
+      array = Yex.Doc.get_array(doc, "array")
+      Yex.Array.insert_list(array, 0, Enum.to_list(1..10))
+
+      assert [1.0, 2.0, 3.0] == array |> Yex.Array.stream(2) |> Enum.take(3)
+      assert :ok = Yex.Array.push(array, 11)
+      assert 11 == array |> Yex.Array.stream(4) |> Enum.count()
+    end
+  end
+
   test "out of bounds" do
     doc = Yex.Doc.new()
     array = Yex.Doc.get_array(doc, "array")
@@ -498,6 +675,102 @@ defmodule Yex.ArrayTest do
                       }, "origin_value", nil}
     end
 
+    test "old_length and new_length with mixed changes" do
+      doc = Doc.new()
+
+      array = Doc.get_array(doc, "text")
+      Array.insert_list(array, 0, [1, 2, 3, 4, 5])
+
+      ref = SharedType.observe(array)
+
+      :ok =
+        Doc.transaction(doc, fn ->
+          Array.delete_range(array, 1, 2)
+          Array.insert_list(array, 2, [6, 7, 8])
+          Array.delete(array, 0)
+        end)
+
+      assert_receive {:observe_event, ^ref,
+                      %Yex.ArrayEvent{old_length: 5, new_length: 5, change: change}, nil, nil}
+
+      assert Enum.any?(change, &Map.has_key?(&1, :insert))
+      assert Enum.any?(change, &Map.has_key?(&1, :delete))
+
+      Array.push_list(array, [9, 10])
+
+      assert_receive {:observe_event, ^ref, %Yex.ArrayEvent{old_length: 5, new_length: 7}, nil,
+                      nil}
+    end
+
+    test "deliver_snapshot" do
+      doc = Doc.new()
+
+      array = Doc.get_array(doc, "text")
+      Array.insert_list(array, 0, [1, 2])
+
+      ref = SharedType.observe(array, deliver_snapshot: true)
+
+      :ok =
+        Doc.transaction(doc, fn ->
+          Array.delete(array, 0)
+          Array.push(array, Yex.ArrayPrelim.from(["nested"]))
+        end)
+
+      assert_receive {:observe_event, ^ref,
+                      %Yex.ArrayEvent{change: nil, snapshot: [2.0, %Yex.Array{}], new_length: 2},
+                      nil, nil}
+    end
+
+    test "deleted_content" do
+      doc = Doc.new()
+
+      array = Doc.get_array(doc, "text")
+      Array.insert_list(array, 0, [1, 2, 3, 4, 5])
+
+      ref = SharedType.observe(array, deleted_content: true)
+
+      :ok =
+        Doc.transaction(doc, fn ->
+          Array.delete_range(array, 3, 2)
+          Array.push(array, 6)
+          Array.delete(array, 3)
+          Array.delete(array, 0)
+        end)
+
+      assert_receive {:observe_event, ^ref, %Yex.ArrayEvent{change: change}, nil, nil}
+
+      assert [
+               %{delete: 1, deleted: [1.0]},
+               %{retain: 2},
+               %{delete: 2, deleted: [4.0, 5.0]}
+             ] == change
+    end
+
+    test "deleted_content only holds the values removed from the observed array" do
+      doc = Doc.new()
+      array = Doc.get_array(doc, "array")
+      other = Doc.get_array(doc, "other")
+      Array.insert_list(other, 0, ["x", "y"])
+      Array.insert_list(array, 0, ["a", "b", "c"])
+
+      ref = SharedType.observe(array, deleted_content: true)
+
+      :ok =
+        Doc.transaction(doc, fn ->
+          Array.delete(other, 0)
+          Array.delete(array, 2)
+          Array.delete(array, 0)
+        end)
+
+      assert_receive {:observe_event, ^ref, %Yex.ArrayEvent{change: change}, nil, nil}
+
+      assert [
+               %{delete: 1, deleted: ["a"]},
+               %{retain: 1},
+               %{delete: 1, deleted: ["c"]}
+             ] == change
+    end
+
     test "unobserve" do
       doc = Doc.new()
 
@@ -16,6 +16,46 @@ defmodule Yex.MapTest do
       assert {:ok, "value"} = Map.fetch(map, "key")
     end
 
+    test "clear/1 removes all keys in a single event", %{map: map} do
+      Map.set(map, "a", 1)
+      Map.set(map, "b", ArrayPrelim.from([1]))
+      ref = Yex.SharedType.observe(map)
+
+      assert :ok = Map.clear(map)
+      assert 0 == Map.size(map)
+
+      assert_receive {:observe_event, ^ref, %Yex.MapEvent{keys: keys}, nil, nil}
+      assert %{"a" => %{action: :delete}, "b" => %{action: :delete}} = keys
+      refute_receive {:observe_event, ^ref, _, _, _}
+    end
+
+    test "compare_and_set/4 writes only when the current value matches", %{map: map} do
+      Map.set(map, "config", MapPrelim.from(%{"mode" => "a"}))
+
+      assert {:ok, :unchanged} =
+               Map.compare_and_set(map, "config", %{"mode" => "b"}, %{"mode" => "c"})
+
+      new_config = MapPrelim.from(%{"mode" => "c"})
+      assert {:ok, :set} = Map.compare_and_set(map, "config", %{"mode" => "a"}, new_config)
+
+      assert {:ok, %Map{} = config} = Map.fetch(map, "config")
+      assert %{"mode" => "c"} == Map.to_json(config)
+
+      Map.set(map, "nil", nil)
+      assert {:ok, :set} = Map.compare_and_set(map, "nil", nil, 1)
+      assert {:ok, :unchanged} = Map.compare_and_set(map, "missing", 1, 2)
+      assert :error = Map.fetch(map, "missing")
+    end
+
+    test "pop/2 removes a key and returns its value", %{map: map} do
+      Map.set(map, "a", 1)
+      Map.set(map, "b", "two")
+
+      assert {:ok, 1.0} = Map.pop(map, "a")
+      assert :error = Map.pop(map, "a")
+      assert %{"b" => "two"} == Map.to_json(map)
+    end
+
     test "set_and_get/3 sets and returns the value", %{map: map} do
       assert "value" = Map.set_and_get(map, "key", "value")
       assert {:ok, "value"} = Map.fetch(map, "key")
@@ -89,7 +129,30 @@ defmodule Yex.MapTest do
     test "fetch!/2 gets value or raises", %{map: map} do
       Map.set(map, "key", "value")
       assert "value" = Map.fetch!(map, "key")
-      assert_raise ArgumentError, fn -> Map.fetch!(map, "not_found") end
+      error = assert_raise Yex.KeyError, fn -> Map.fetch!(map, "not_found") end
+      assert error.key == "not_found"
+      assert error.message =~ "not_found"
+    end
+
+    test "increment/3 does not lose concurrent local increments", %{map: map} do
+      results =
+        1..20
+        |> Enum.map(fn _ -> Task.async(fn -> increment_when_free(map, "count", 2) end) end)
+        |> Task.await_many()
+
+      # Every increment saw the result of the previous one.
+      assert Enum.map(1..20, &{:ok, &1 * 2.0}) == Enum.sort(results)
+      assert 40.0 == Map.fetch!(map, "count")
+      assert {:ok, 39.0} = Map.increment(map, "count", -1)
+    end
+
+    test "increment/3 reports a busy document", %{doc: doc, map: map} do
+      txn = Yex.Nif.doc_begin_transaction(doc, nil)
+
+      assert {:error, :transaction_busy} = Map.increment(map, "count", 2)
+      assert {:ok, 2.0} = Yex.Nif.map_increment(map, txn, "count", 2)
+      assert 1 == Doc.force_commit_all(doc)
+      assert {:ok, 4.0} = Map.increment(map, "count", 2)
     end
 
     test "get/2 returns value by key", %{map: map} do
@@ -102,6 +165,13 @@ defmodule Yex.MapTest do
       assert "default" = Map.get(map, "not_found", "default")
     end
 
+    test "get/3 returns the default untouched and does not insert it", %{map: map} do
+      assert :missing = Map.get(map, "not_found", :missing)
+      assert {:tuple, self()} == Map.get(map, "not_found", {:tuple, self()})
+      assert 1 === Map.get(map, "not_found", 1)
+      refute Map.has_key?(map, "not_found")
+    end
+
     test "get_lazy/3 evaluates function only when key not found", %{map: map} do
       Map.set(map, "key", "value")
 
@@ -117,6 +187,21 @@ defmodule Yex.MapTest do
       assert Map.has_key?(map, "key")
       refute Map.has_key?(map, "not_found")
     end
+
+    test "value_id/2 changes on replacement but not on nested mutation", %{map: map} do
+      nested = Map.set_and_get(map, "nested", Yex.MapPrelim.from(%{"a" => 1}))
+      {:ok, id} = Map.value_id(map, "nested")
+
+      Map.set(nested, "b", 2)
+      assert {:ok, ^id} = Map.value_id(map, "nested")
+
+      Map.set(map, "nested", "replaced")
+      assert {:ok, new_id} = Map.value_id(map, "nested")
+      assert new_id != id
+
+      Map.delete(map, "nested")
+      assert :error = Map.value_id(map, "nested")
+    end
   end
 
   describe "conversion functions" do
@@ -144,9 +229,53 @@ defmodule Yex.MapTest do
                "plane" => ["Hello", "World"]
              } = json
     end
+
+    test "to_json_ordered/1 sorts entries by key", %{map: map} do
+      for key <- ~w(delta alpha charlie bravo), do: Map.set(map, key, key)
+      Map.set(map, "nested", MapPrelim.from(%{"z" => 1, "y" => 2}))
+
+      assert [
+               {"alpha", "alpha"},
+               {"bravo", "bravo"},
+               {"charlie", "charlie"},
+               {"delta", "delta"},
+               {"nested", %{"y" => 2.0, "z" => 1.0}}
+             ] == Map.to_json_ordered(map)
+    end
   end
 
   describe "utility functions" do
+    test "content_hash/1 ignores key insertion order", %{map: map} do
+      Map.set(map, "a", 1)
+      Map.set(map, "b", ArrayPrelim.from(["x", "y"]))
+
+      other = Yex.Doc.new() |> Yex.Doc.get_map("map")
+      Map.set(other, "b", ArrayPrelim.from(["x", "y"]))
+      Map.set(other, "a", 1)
+      assert Map.content_hash(map) == Map.content_hash(other)
+
+      Map.set(other, "b", ArrayPrelim.from(["y", "x"]))
+      assert Map.content_hash(map) != Map.content_hash(other)
+    end
+
+    test "deep_equal?/2 compares nested content with a plain term", %{map: map} do
+      Map.set(map, "a", 1)
+      Map.set(map, "b", ArrayPrelim.from(["x", %{"y" => nil}]))
+
+      assert Map.deep_equal?(map, %{"a" => 1.0, "b" => ["x", %{"y" => nil}]})
+      assert Map.deep_equal?(map, %{"b" => ["x", %{"y" => nil}], "a" => 1})
+      refute Map.deep_equal?(map, %{"a" => 1})
+      refute Map.deep_equal?(map, %{"a" => 1, "b" => ["x", %{"y" => false}]})
+      assert_raise ArgumentError, fn -> Map.deep_equal?(map, %{a: self()}) end
+    end
+
+    test "set/3 stores atom keys and atom values as strings", %{map: map} do
+      Map.set(map, "config", %{mode: :dark, flags: [:a, nil, true]})
+
+      assert %{"mode" => "dark", "flags" => ["a", nil, true]} == Map.fetch!(map, "config")
+      assert_raise ArgumentError, fn -> Map.set(map, "key", %{value: %URI{}}) end
+    end
+
     test "size/1 returns number of entries", %{map: map} do
       assert 0 = Map.size(map)
       Map.set(map, "key1", "value1")
@@ -242,4 +371,11 @@ defmodule Yex.MapTest do
       assert [{"key1", "value1"}, {"key2", "value2"}] = Enum.sort(result)
     end
   end
+
+  defp increment_when_free(map, key, delta) do
+    case Map.increment(map, key, delta) do
+      {:error, :transaction_busy} -> increment_when_free(map, key, delta)
+      result -> result
+    end
+  end
 end
@@ -2,6 +2,8 @@ defmodule Yex.SharedTypeTest do
   use ExUnit.Case, async: true
   alias Yex.{SharedType, Doc, Text, Output}
 
+  doctest SharedType, only: [equal?: 2, path: 1]
+
   setup do
     doc = Doc.new()
     text = Doc.get_text(doc, "text")
@@ -42,6 +44,68 @@ defmodule Yex.SharedTypeTest do
     end
   end
 
+  describe "observe/2 with json_values" do
+    test "delivers map values as JSON binaries", %{doc: doc} do
+      map = Doc.get_map(doc, "map")
+      Yex.Map.set(map, "replaced", "old")
+      Yex.Map.set(map, "removed", true)
+      ref = SharedType.observe(map, json_values: true)
+
+      Doc.transaction(doc, fn ->
+        Yex.Map.set(map, "added", Yex.ArrayPrelim.from([1, "two"]))
+        Yex.Map.set(map, "replaced", "new")
+        Yex.Map.delete(map, "removed")
+      end)
+
+      assert_receive {:observe_event, ^ref, %Yex.MapEvent{keys: keys}, _origin, _metadata}
+
+      assert keys == %{
+               "added" => %{action: :add, new_value: ~s([1.0,"two"])},
+               "replaced" => %{action: :update, old_value: ~s("old"), new_value: ~s("new")},
+               "removed" => %{action: :delete, old_value: "true"}
+             }
+    end
+
+    test "does not change events of other types", %{doc: doc, text: text} do
+      ref = SharedType.observe(text, json_values: true)
+      Text.insert(text, 0, "hello")
+
+      assert_receive {:observe_event, ^ref, %Yex.TextEvent{delta: [%{insert: "hello"}]}, _, _}
+    end
+  end
+
+  describe "observe/2 with throttle" do
+    test "coalesces map changes into the latest value per key", %{doc: doc} do
+      map = Doc.get_map(doc, "map")
+      Yex.Map.set(map, "existing", 0)
+      ref = SharedType.observe(map, throttle: 200, metadata: :meta)
+
+      for i <- 1..100, do: Yex.Map.set(map, "metric", i)
+      Yex.Map.set(map, "existing", 1)
+      Yex.Map.set(map, "existing", 0)
+      Yex.Map.set(map, "temp", true)
+      Yex.Map.delete(map, "temp")
+      Doc.transaction(doc, "last", fn -> Yex.Map.set(map, "metric", 101) end)
+
+      assert_receive {:observe_event, ^ref, %Yex.MapEvent{keys: keys}, "last", :meta}, 1000
+      assert keys == %{"metric" => %{action: :add, new_value: 101.0}}
+      refute_receive {:observe_event, ^ref, _, _, _}, 300
+
+      Yex.Map.set(map, "metric", 102)
+      assert_receive {:observe_event, ^ref, %Yex.MapEvent{keys: keys}, nil, :meta}, 1000
+      assert keys == %{"metric" => %{action: :update, old_value: 101.0, new_value: 102.0}}
+    end
+
+    test "drops pending changes on unobserve", %{doc: doc} do
+      map = Doc.get_map(doc, "map")
+      ref = SharedType.observe(map, throttle: 50)
+      Yex.Map.set(map, "key", "value")
+      SharedType.unobserve(ref)
+
+      refute_receive {:observe_event, ^ref, _, _, _}, 200
+    end
+  end
+
   describe "observe_deep/2" do
     test "deep observation with default options", %{text: text} do
       ref = SharedType.observe_deep(text)
@@ -76,4 +140,83 @@ defmodule Yex.SharedTypeTest do
       assert %Yex.TextPrelim{} = Output.as_prelim(text)
     end
   end
+
+  describe "equal?/2" do
+    test "compares the underlying branch", %{doc: doc, text: text} do
+      assert SharedType.equal?(text, Doc.get_text(doc, "text"))
+      refute SharedType.equal?(text, Doc.get_text(doc, "other"))
+    end
+
+    test "is false across documents and kinds", %{text: text} do
+      other = Doc.new()
+      refute SharedType.equal?(text, Doc.get_text(other, "text"))
+      refute SharedType.equal?(Doc.get_map(other, "a"), Doc.get_array(other, "b"))
+    end
+
+    test "is true for handles obtained separately on one sub-document", %{doc: doc} do
+      folder = Doc.get_map(doc, "folder")
+      Yex.Map.set(folder, "sub", Doc.new())
+      a = Yex.Map.fetch!(folder, "sub")
+      b = Yex.Map.fetch!(folder, "sub")
+
+      assert SharedType.equal?(Doc.get_text(a, "text"), Doc.get_text(b, "text"))
+    end
+  end
+
+  describe "path/1" do
+    test "tracks the index after siblings are inserted", %{doc: doc} do
+      array = Doc.get_array(doc, "array")
+      Yex.Array.push(array, Yex.MapPrelim.from(%{"a" => "b"}))
+      nested = Yex.Array.fetch!(array, 0)
+      assert [0] == SharedType.path(nested)
+
+      Doc.transaction(doc, fn ->
+        Yex.Array.insert(array, 0, "first")
+        assert [1] == SharedType.path(nested)
+      end)
+    end
+  end
+
+  describe "observe_many/2" do
+    test "delivers events of every shared type under one ref", %{doc: doc, text: text} do
+      map = Doc.get_map(doc, "map")
+      ref = SharedType.observe_many([text, map], metadata: :meta)
+
+      Text.insert(text, 0, "hello")
+      assert_receive {:observe_event, ^ref, %Yex.TextEvent{target: ^text}, nil, :meta}
+
+      Yex.Map.set(map, "key", "value")
+      assert_receive {:observe_event, ^ref, %Yex.MapEvent{target: ^map}, nil, :meta}
+
+      SharedType.unobserve(ref)
+      Text.insert(text, 0, "again")
+      Yex.Map.set(map, "key", "other")
+      refute_receive {:observe_event, ^ref, _, _, _}
+    end
+
+    test "rejects shared types of different documents", %{text: text} do
+      other = Doc.get_map(Doc.new(), "map")
+
+      assert_raise ArgumentError, fn -> SharedType.observe_many([text, other]) end
+    end
+  end
+
+  describe "ignore_origins option" do
+    test "skips changes from ignored origins", %{doc: doc, text: text} do
+      ref = SharedType.observe(text, ignore_origins: ["remote"])
+      deep_ref = SharedType.observe_deep(text, ignore_origins: [{:remote, 1}])
+
+      Doc.transaction(doc, "remote", fn -> Text.insert(text, 0, "a") end)
+      refute_receive {:observe_event, ^ref, _, _, _}
+      assert_receive {:observe_deep_event, ^deep_ref, _, "remote", _}
+
+      Doc.transaction(doc, {:remote, 1}, fn -> Text.insert(text, 0, "b") end)
+      assert_receive {:observe_event, ^ref, _, {:remote, 1}, _}
+      refute_receive {:observe_deep_event, ^deep_ref, _, _, _}
+
+      Text.insert(text, 0, "c")
+      assert_receive {:observe_event, ^ref, _, nil, _}
+      assert_receive {:observe_deep_event, ^deep_ref, _, nil, _}
+    end
+  end
 end
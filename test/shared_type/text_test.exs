@@ -446,6 +446,47 @@ defmodule Yex.TextTest do
       assert "15" = to_string(text)
     end
 
+    test "apply_ops/2 applies every op in a single event", %{text: text} do
+      Text.insert(text, 0, "abc")
+      ref = Yex.SharedType.observe(text)
+
+      ops = [
+        {:insert, 3, "def"},
+        {:delete, 0, 1},
+        {:format, 0, 2, %{"bold" => true}},
+        {:insert, -1, "!"}
+      ]
+
+      assert :ok = Text.apply_ops(text, ops)
+
+      assert [%{insert: "bc", attributes: %{"bold" => true}}, %{insert: "def!"}] ==
+               Text.to_delta(text)
+
+      assert_receive {:observe_event, ^ref, %Yex.TextEvent{}, nil, nil}
+      refute_receive {:observe_event, ^ref, _, _, _}
+    end
+
+    test "apply_ops/2 rejects malformed ops without applying any", %{text: text} do
+      assert_raise ArgumentError, fn ->
+        Text.apply_ops(text, [{:insert, 0, "a"}, {:delete, 0, "a"}])
+      end
+
+      assert "" == Text.to_string(text)
+    end
+
+    test "apply_delta_checked/2 rejects deltas past the end", %{text: text} do
+      Text.insert(text, 0, "12345")
+      ref = Yex.SharedType.observe(text)
+
+      delta = [%{"delete" => 1}, %{insert: "x"}, %{"retain" => 5}]
+      assert {:error, :delta_out_of_range} = Text.apply_delta_checked(text, delta)
+      assert "12345" == Text.to_string(text)
+      refute_receive {:observe_event, ^ref, _, _, _}
+
+      assert :ok = Text.apply_delta_checked(text, [%{"delete" => 1}, %{insert: "x"}])
+      assert "x2345" == Text.to_string(text)
+    end
+
     test "apply_delta/2 with insert and attributes", %{text: text} do
       delta = [
         %{insert: "hello"},
@@ -482,6 +523,20 @@ defmodule Yex.TextTest do
                %{insert: " world", attributes: %{"bold" => true}}
              ] = Text.to_delta(text)
     end
+
+    test "run_stats/1 matches the delta length", %{text: text} do
+      assert {0, 0} = Text.run_stats(text)
+
+      Text.insert(text, 0, "hello")
+      Text.insert(text, 5, " world")
+      assert {1, 1} = Text.run_stats(text)
+
+      Text.format(text, 0, 5, %{"bold" => true})
+      Text.format(text, 0, 3, %{"bold" => true})
+      {runs, coalesced} = Text.run_stats(text)
+      assert runs == length(Text.to_delta(text))
+      assert coalesced == 2
+    end
   end
 
   describe "TextPrelim" do
@@ -757,4 +812,169 @@ defmodule Yex.TextTest do
     result = Yex.Text.quote(text, 10, 5)
     assert result == {:error, :out_of_bounds}
   end
+
+  describe "insert_at_sticky" do
+    test "inserts with attributes at the tracked position", %{text: text} do
+      Text.insert(text, 0, "Hello world")
+      cursor = Yex.StickyIndex.new(text, 5, :before)
+      Text.delete(text, 0, 1)
+      Text.insert(text, 0, "Oh, h")
+
+      assert :ok = Text.insert_at_sticky(text, cursor, "!", %{"bold" => true})
+
+      assert [
+               %{insert: "Oh, hello"},
+               %{insert: "!", attributes: %{"bold" => true}},
+               %{insert: " world"}
+             ] == Text.to_delta(text)
+    end
+
+    test "follows edits applied from a remote peer", %{doc: doc, text: text} do
+      Text.insert(text, 0, "ab")
+      cursor = Yex.StickyIndex.new(text, 1, :after)
+
+      remote = Doc.new()
+      {:ok, update} = Yex.encode_state_as_update(doc)
+      Yex.apply_update(remote, update)
+      Doc.get_text(remote, "text") |> Text.insert(0, "xyz")
+      {:ok, update} = Yex.encode_state_as_update(remote)
+      Yex.apply_update(doc, update)
+
+      assert :ok = Text.insert_at_sticky(text, cursor, "-")
+      assert "xyza-b" == Text.to_string(text)
+    end
+  end
+
+  describe "apply_quill_delta" do
+    test "applies inserts, embeds and formatting", %{text: text} do
+      Text.insert(text, 0, "Hello world")
+
+      json = ~s([
+        {"retain": 6, "attributes": {"italic": true}},
+        {"delete": 5},
+        {"insert": {"image": "https://example.com/a.png"}, "attributes": {"width": 100}},
+        {"insert": "!"}
+      ])
+
+      assert :ok = Text.apply_quill_delta(text, json)
+
+      assert [
+               %{insert: "Hello ", attributes: %{"italic" => true}},
+               %{insert: %{"image" => "https://example.com/a.png"}, attributes: %{"width" => 100.0}},
+               %{insert: "!"}
+             ] == Text.to_delta(text)
+    end
+
+    test "rejects malformed input without changing the text", %{text: text} do
+      Text.insert(text, 0, "Hello")
+
+      for json <- ["not json", ~s({"ops": 1}), ~s([{"insert": 1}]), ~s([{"delete": -1}]), ~s([{}])] do
+        assert {:error, :invalid_quill_delta} = Text.apply_quill_delta(text, json)
+      end
+
+      assert "Hello" == Text.to_string(text)
+    end
+  end
+
+  describe "to_string_with_embeds" do
+    test "keeps offsets aligned with the text", %{text: text} do
+      Text.insert(text, 0, "Hello world")
+      Text.apply_delta(text, [%{retain: 5}, %{insert: %{"mention" => "@joe"}}])
+
+      plain = Text.to_string_with_embeds(text, "\uFFFC")
+      assert "Hello\uFFFC world" == plain
+      assert Text.length(text) == String.length(plain)
+
+      [{offset, _}] = :binary.matches(plain, "world")
+      utf16_offset = plain |> binary_part(0, offset) |> :unicode.characters_to_binary(:utf8, :utf16)
+      Text.delete(text, div(byte_size(utf16_offset), 2), 5)
+
+      assert "Hello[embed] " == Text.to_string_with_embeds(text, "[embed]")
+    end
+  end
+
+  describe "word_count and line_count" do
+    test "count over unicode text", %{text: text} do
+      assert 0 == Text.word_count(text)
+      assert 0 == Text.line_count(text)
+
+      Text.insert(text, 0, "  Grüße,\tWelt!\r\n日本語 テキスト\n")
+      assert 4 == Text.word_count(text)
+      assert 3 == Text.line_count(text)
+
+      Text.apply_delta(text, [%{insert: %{"image" => "a.png"}}])
+      assert 4 == Text.word_count(text)
+    end
+  end
+
+  describe "find_formatted" do
+    test "merges adjacent runs and counts in the offset kind" do
+      doc = Doc.with_options(%Doc.Options{offset_kind: :utf16})
+      text = Doc.get_text(doc, "text")
+      Text.insert(text, 0, "😀 bold italic plain")
+      Text.format(text, 0, 7, %{"bold" => true})
+      Text.format(text, 8, 6, %{"bold" => true, "italic" => true})
+      Text.format(text, 7, 1, %{"bold" => true})
+      embed = %{insert: %{"image" => "a.png"}, attributes: %{"bold" => true}}
+      Text.apply_delta(text, [%{retain: 14}, embed])
+
+      assert [{0, 15}] == Text.find_formatted(text, "bold", true)
+      assert [{8, 6}] == Text.find_formatted(text, "italic", true)
+      assert [] == Text.find_formatted(text, "bold", false)
+    end
+  end
+
+  describe "char_at and substring" do
+    test "follow the offset kind and skip embeds" do
+      doc = Doc.with_options(%Doc.Options{offset_kind: :utf16})
+      text = Doc.get_text(doc, "text")
+      Text.insert(text, 0, "a😀b")
+      Text.apply_delta(text, [%{retain: 4}, %{insert: %{"image" => "a.png"}}, %{insert: "c"}])
+
+      assert {:ok, "😀"} == Text.char_at(text, 1)
+      assert {:ok, "😀"} == Text.char_at(text, 2)
+      assert {:ok, "c"} == Text.char_at(text, -1)
+      assert :error == Text.char_at(text, 4)
+      assert :error == Text.char_at(text, 6)
+
+      assert "😀bc" == Text.substring(text, 1, 10)
+      assert "" == Text.substring(text, 10, 2)
+    end
+  end
+
+  describe "offset_of_char" do
+    test "translates codepoints into the offset kind" do
+      for {offset_kind, expected} <- [bytes: [0, 1, 5, 6, 7, 8], utf16: [0, 1, 3, 4, 5, 6]] do
+        doc = Doc.with_options(%Doc.Options{offset_kind: offset_kind})
+        text = Doc.get_text(doc, "text")
+        Text.insert(text, 0, "a😀b")
+        Text.apply_delta(text, [%{retain: Text.length(text)}, %{insert: %{"image" => "a.png"}}])
+        Text.insert(text, Text.length(text), "c")
+
+        assert expected == for(i <- 0..5, do: elem(Text.offset_of_char(text, i), 1))
+        assert {:error, :out_of_bounds} == Text.offset_of_char(text, 6)
+      end
+    end
+
+    test "offsets can be used to edit at a codepoint position", %{text: text} do
+      Text.insert(text, 0, "🎉 héllo")
+      {:ok, offset} = Text.offset_of_char(text, 3)
+      Text.insert(text, offset, "E")
+      assert "🎉 hEéllo" == Text.to_string(text)
+    end
+  end
+
+  test "insert_embed between formatted runs keeps three delta ops", %{text: text} do
+    Text.insert(text, 0, "ab", %{"bold" => true})
+    Text.insert(text, 2, "cd", %{"italic" => true})
+    Text.insert_embed(text, 2, %{"mention" => "alice"}, %{"link" => "/users/alice"})
+
+    assert [
+             %{insert: "ab", attributes: %{"bold" => true}},
+             %{insert: %{"mention" => "alice"}, attributes: %{"link" => "/users/alice"}},
+             %{insert: "cd", attributes: %{"italic" => true}}
+           ] = Text.to_delta(text)
+
+    assert 5 == Text.length(text)
+  end
 end
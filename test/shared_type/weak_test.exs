@@ -130,6 +130,45 @@ defmodule Yex.WeakTest do
     end
   end
 
+  describe "source_path" do
+    test "array quote reports the quoted range" do
+      doc = Doc.new()
+      array = Doc.get_array(doc, "array")
+      map = Doc.get_map(doc, "map")
+      Yex.Array.insert_list(array, 0, ["1", "2", "3", "4"])
+
+      link = Yex.Map.set_and_get(map, "key", Yex.Array.quote(array, 1, 2))
+
+      assert {:ok, %{path: ["array"], key: nil, range: {1, 3}}} = WeakLink.source_path(link)
+    end
+
+    test "array quote range follows edits around the quote" do
+      doc = Doc.new()
+      array = Doc.get_array(doc, "array")
+      map = Doc.get_map(doc, "map")
+      Yex.Array.insert_list(array, 0, ["1", "2", "3", "4"])
+      link = Yex.Map.set_and_get(map, "key", Yex.Array.quote(array, 1, 2))
+
+      Yex.Array.insert(array, 0, "0")
+      assert {:ok, %{range: {2, 4}}} = WeakLink.source_path(link)
+
+      Yex.Array.delete(array, 3)
+      assert {:ok, %{range: {2, 3}}} = WeakLink.source_path(link)
+    end
+
+    test "map link reports the parent path and key" do
+      doc = Doc.new()
+      map = Doc.get_map(doc, "map")
+      Yex.Map.set(map, "nested", Yex.MapPrelim.from(%{"a" => "hello"}))
+      nested = Yex.Map.fetch!(map, "nested")
+
+      link = Yex.Map.set_and_get(map, "b", Yex.Map.link(nested, "a"))
+
+      assert {:ok, %{path: ["map", "nested"], key: "a", range: nil}} =
+               WeakLink.source_path(link)
+    end
+  end
+
   describe "map link" do
     test "update" do
       d1 = Doc.new()
@@ -465,6 +465,24 @@ defmodule YexXmlElementTest do
                       }, "origin_value", nil}
     end
 
+    test "set_attributes emits a single event", %{xml_element: xml_element} do
+      XmlElement.insert_attribute(xml_element, "id", "old")
+      ref = SharedType.observe(xml_element)
+
+      :ok = XmlElement.set_attributes(xml_element, %{"id" => "new", "class" => "box"})
+
+      assert_receive {:observe_event, ^ref,
+                      %Yex.XmlEvent{
+                        keys: %{
+                          "id" => %{action: :update, new_value: "new"},
+                          "class" => %{action: :add, new_value: "box"}
+                        }
+                      }, nil, nil}
+
+      refute_receive {:observe_event, ^ref, _, _, _}
+      assert %{"id" => "new", "class" => "box"} == XmlElement.get_attributes(xml_element)
+    end
+
     test "observe delete ", %{doc: doc, xml_element: xml_element} do
       XmlElement.push(xml_element, XmlTextPrelim.from("Hello"))
       XmlElement.push(xml_element, XmlTextPrelim.from("World"))
@@ -537,6 +555,17 @@ defmodule YexXmlElementTest do
                       ], "origin_value", _metadata}
     end
 
+    test "prelim attributes accept non-string values", %{xml_element: xml} do
+      el =
+        XmlElement.push_and_get(
+          xml,
+          XmlElementPrelim.new("input", [], %{"type" => "number", "max" => 10, "disabled" => true})
+        )
+
+      assert %{"type" => "number", "max" => 10.0, "disabled" => true} ==
+               XmlElement.get_attributes(el)
+    end
+
     test "insert_attribute with map values", %{xml_element: xml} do
       XmlElement.insert_attribute(xml, "height", %{"value" => "10"})
 
@@ -371,6 +371,61 @@ defmodule YexXmlFragmentTest do
     end
   end
 
+  describe "tree_walk" do
+    test "returns an empty list for an empty fragment", %{xml_fragment: f} do
+      assert [] == XmlFragment.tree_walk(f)
+    end
+
+    test "visits nested nodes depth-first", %{xml_fragment: f} do
+      XmlFragment.push(
+        f,
+        XmlElementPrelim.new("ul", [
+          XmlElementPrelim.new("li", [XmlTextPrelim.from("one")]),
+          XmlElementPrelim.new("li", [XmlTextPrelim.from("two")])
+        ])
+      )
+
+      XmlFragment.push(f, XmlElementPrelim.empty("hr"))
+
+      assert [
+               {0, "ul"},
+               {1, "li"},
+               {2, "one"},
+               {1, "li"},
+               {2, "two"},
+               {0, "hr"}
+             ] ==
+               f
+               |> XmlFragment.tree_walk()
+               |> Enum.map(fn
+                 {depth, %XmlElement{} = node} -> {depth, XmlElement.get_tag(node)}
+                 {depth, %XmlText{} = node} -> {depth, XmlText.to_string(node)}
+               end)
+    end
+  end
+
+  describe "select_by_tag" do
+    test "collects matching elements at any depth", %{xml_fragment: f} do
+      XmlFragment.push(
+        f,
+        XmlElementPrelim.new("div", [
+          XmlElementPrelim.new("paragraph", [XmlTextPrelim.from("nested")]),
+          XmlTextPrelim.from("paragraph")
+        ])
+      )
+
+      XmlFragment.push(f, XmlElementPrelim.new("paragraph", [XmlTextPrelim.from("top")]))
+      XmlFragment.push(f, XmlElementPrelim.empty("Paragraph"))
+
+      assert ["<paragraph>nested</paragraph>", "<paragraph>top</paragraph>"] ==
+               f
+               |> XmlFragment.select_by_tag("paragraph")
+               |> Enum.map(&XmlElement.to_string/1)
+
+      assert [] == XmlFragment.select_by_tag(f, "span")
+    end
+  end
+
   describe "as_prelim" do
     test "converts empty XmlFragment to TextPrelim", %{xml_fragment: f} do
       prelim = XmlFragment.as_prelim(f)
@@ -13,6 +13,15 @@ defmodule YexXmlTextTest do
       %{doc: d1, xml_text: xml, xml_fragment: f}
     end
 
+    test "insert_at_sticky follows the cursor", %{xml_text: text} do
+      XmlText.insert(text, 0, "ac")
+      cursor = Yex.StickyIndex.new(text, 1, :after)
+      XmlText.insert(text, 0, "_")
+
+      assert :ok = XmlText.insert_at_sticky(text, cursor, "b", %{"bold" => true})
+      assert "_a<bold>b</bold>c" == to_string(text)
+    end
+
     test "Delete", %{xml_text: text} do
       XmlText.insert(text, 0, "1234")
       XmlText.insert(text, 5, "5", %{"bold" => true})
@@ -163,6 +163,81 @@ defmodule Yex.StickyIndexTest do
     end)
   end
 
+  test "encode and decode across documents" do
+    doc = Doc.new()
+    txt = Doc.get_text(doc, "text")
+    Text.insert(txt, 0, "abc")
+
+    binary = txt |> StickyIndex.new(2, :before) |> StickyIndex.encode()
+    assert is_binary(binary)
+
+    remote = Doc.new()
+    {:ok, update} = Yex.encode_state_as_update(doc)
+    Yex.apply_update(remote, update)
+    remote_txt = Doc.get_text(remote, "text")
+    Text.insert(remote_txt, 0, "xy")
+
+    assert {:ok, pos} = StickyIndex.decode(remote, binary, :before)
+    assert {:ok, %{index: 4, assoc: :before}} = StickyIndex.get_offset(pos)
+  end
+
+  test "decode keeps the encoded assoc unless overridden" do
+    doc = Doc.new()
+    txt = Doc.get_text(doc, "text")
+    Text.insert(txt, 0, "abc")
+    binary = txt |> StickyIndex.new(2, :before) |> StickyIndex.encode()
+
+    assert {:ok, %StickyIndex{assoc: :before}} = StickyIndex.decode(doc, binary)
+    assert {:ok, %StickyIndex{assoc: :after} = pos} = StickyIndex.decode(doc, binary, :after)
+    assert {:ok, %{assoc: :after}} = StickyIndex.get_offset(pos)
+  end
+
+  test "at_offset restores an offset returned by get_offset" do
+    doc = Doc.new()
+    txt = Doc.get_text(doc, "text")
+    Text.insert(txt, 0, "abc")
+
+    {:ok, offset} = txt |> StickyIndex.new(2, :after) |> StickyIndex.get_offset()
+    pos = StickyIndex.at_offset(txt, offset)
+    Text.insert(txt, 0, "xy")
+
+    assert {:ok, %{index: 4, assoc: :after}} = StickyIndex.get_offset(pos)
+  end
+
+  test "decode returns :error for an invalid binary" do
+    assert :error == StickyIndex.decode(Doc.new(), <<>>, :after)
+  end
+
+  test "compare follows edits and breaks ties by assoc" do
+    doc = Doc.new()
+    txt = Doc.get_text(doc, "text")
+    Text.insert(txt, 0, "abcd")
+
+    first = StickyIndex.new(txt, 1, :after)
+    second = StickyIndex.new(txt, 3, :after)
+    assert StickyIndex.compare(first, second) == :lt
+
+    Text.delete(txt, 1, 2)
+    # both now resolve to index 1
+    assert StickyIndex.compare(first, second) == :eq
+    assert StickyIndex.compare(first, first) == :eq
+
+    before = StickyIndex.new(txt, 1, :before)
+    assert StickyIndex.compare(before, first) == :lt
+  end
+
+  test "compare returns :error across shared types and documents" do
+    doc = Doc.new()
+    txt = Doc.get_text(doc, "text")
+    other = Doc.get_text(doc, "other")
+    remote = Doc.get_text(Doc.new(), "text")
+    Enum.each([txt, other, remote], &Text.insert(&1, 0, "abc"))
+
+    a = StickyIndex.new(txt, 1, :after)
+    assert :error == StickyIndex.compare(a, StickyIndex.new(other, 2, :after))
+    assert :error == StickyIndex.compare(a, StickyIndex.new(remote, 2, :after))
+  end
+
   test "get_offset for an invalid sticky index" do
     doc = Doc.new()
     txt = Doc.get_text(doc, "text")
@@ -26,4 +26,92 @@ defmodule Yex.SubdocsTest do
 
     Doc
   end
+
+  test "loaded?" do
+    root_doc = Doc.new()
+    assert Doc.loaded?(root_doc)
+
+    folder = Doc.get_map(root_doc, "folder")
+    sub_doc = Map.set_and_get(folder, "loaded.txt", Doc.new())
+    assert Doc.loaded?(sub_doc)
+
+    not_loaded =
+      Map.set_and_get(folder, "placeholder.txt", Doc.with_options(%Doc.Options{should_load: false}))
+
+    refute Doc.loaded?(not_loaded)
+  end
+
+  test "subdoc_by_guid" do
+    root_doc = Doc.new()
+    folder = Doc.get_map(root_doc, "folder")
+    Doc.monitor_subdocs(root_doc)
+
+    Map.set(folder, "my-document.txt", Doc.new())
+    assert_receive {:subdocs, %{added: [added]}, _, ^root_doc}
+
+    guid = Doc.guid(added)
+    sub_doc = Doc.subdoc_by_guid(root_doc, guid)
+    assert Doc.guid(sub_doc) == guid
+
+    Doc.get_text(sub_doc, "text") |> Text.insert(0, "hello")
+    assert "hello" == Doc.get_text(added, "text") |> Text.to_string()
+
+    assert nil == Doc.subdoc_by_guid(root_doc, "unknown")
+  end
+
+  test "get_subdocs" do
+    root_doc = Doc.new()
+    folder = Doc.get_map(root_doc, "folder")
+    assert %{} == Doc.get_subdocs(root_doc)
+
+    a = Map.set_and_get(folder, "a.txt", Doc.new())
+    b = Map.set_and_get(folder, "b.txt", Doc.new())
+
+    subdocs = Doc.get_subdocs(root_doc)
+    assert Enum.sort([Doc.guid(a), Doc.guid(b)]) == Enum.sort(Elixir.Map.keys(subdocs))
+
+    Doc.get_text(subdocs[Doc.guid(a)], "text") |> Text.insert(0, "hello")
+    assert "hello" == Doc.get_text(a, "text") |> Text.to_string()
+  end
+
+  describe "load" do
+    setup do
+      root_doc = Doc.new()
+      folder = Doc.get_map(root_doc, "folder")
+      placeholder = Doc.with_options(%Doc.Options{should_load: false})
+      sub_doc = Map.set_and_get(folder, "placeholder.txt", placeholder)
+      Doc.monitor_subdocs(root_doc)
+      %{root_doc: root_doc, sub_doc: sub_doc}
+    end
+
+    test "reports the document to the parent", %{root_doc: root_doc, sub_doc: sub_doc} do
+      assert :ok = Doc.load(sub_doc)
+      assert Doc.should_load(sub_doc)
+
+      assert_receive {:subdocs, %{added: [], removed: [], loaded: [loaded]}, _, ^root_doc}
+      assert Doc.guid(sub_doc) == Doc.guid(loaded)
+    end
+
+    test "joins a transaction of the parent", %{root_doc: root_doc, sub_doc: sub_doc} do
+      Doc.transaction(root_doc, fn ->
+        assert :ok = Doc.load(sub_doc, root_doc)
+      end)
+
+      assert_receive {:subdocs, %{loaded: [_]}, _, ^root_doc}
+      assert Doc.loaded?(sub_doc)
+    end
+
+    test "set_should_load/2", %{sub_doc: sub_doc} do
+      assert :ok = Doc.set_should_load(sub_doc, false)
+      refute Doc.loaded?(sub_doc)
+      assert :ok = Doc.set_should_load(sub_doc, true)
+      assert Doc.loaded?(sub_doc)
+      assert {:error, :already_loaded} = Doc.set_should_load(sub_doc, false)
+    end
+
+    test "is a no-op for root documents", %{root_doc: root_doc} do
+      assert :ok = Doc.load(root_doc)
+      assert Doc.loaded?(root_doc)
+    end
+  end
 end
@@ -0,0 +1,37 @@
+defmodule Yex.TypedTest do
+  use ExUnit.Case, async: true
+  alias Yex.{Doc, Map, Typed}
+  doctest Typed
+
+  setup do
+    doc = Doc.new()
+    map = Doc.get_map(doc, "map")
+    %{doc: doc, map: map}
+  end
+
+  test "stores values with the requested kind", %{map: map} do
+    Map.set(map, "integer", Typed.new(1.0, :integer))
+    Map.set(map, "big", Typed.new(2.0 ** 60, :integer))
+    Map.set(map, "float", Typed.new(1, :float))
+    Map.set(map, "string", Typed.new("text", :string))
+    Map.set(map, "list", Typed.new([1, 2, 3], :list))
+    Map.set(map, "binary", Typed.new(<<255, 0>>, :binary))
+
+    assert Map.fetch!(map, "integer") === 1
+    assert Map.fetch!(map, "big") == 1_152_921_504_606_846_976
+    assert Map.fetch!(map, "float") == 1.0
+    assert Map.fetch!(map, "string") == "text"
+    assert Map.fetch!(map, "list") == [1.0, 2.0, 3.0]
+    assert Map.fetch!(map, "binary") == %Yex.Binary{data: <<255, 0>>}
+  end
+
+  test "raises when the value does not match the kind", %{map: map} do
+    assert_raise ArgumentError, fn -> Map.set(map, "key", Typed.new(1.5, :integer)) end
+    assert_raise ArgumentError, fn -> Map.set(map, "key", Typed.new(<<255>>, :string)) end
+    assert_raise ArgumentError, fn -> Typed.normalize("text", :float) end
+  end
+
+  test "rejects unknown kinds" do
+    assert_raise FunctionClauseError, fn -> Typed.new(1, :unknown) end
+  end
+end
@@ -241,6 +241,35 @@ defmodule Yex.UndoManagerTest do
     UndoManager.redo(undo_manager)
   end
 
+  test "can_undo? and can_redo? follow the stacks", %{doc: doc, text: text} do
+    {:ok, undo_manager} = UndoManager.new(doc, text)
+    refute UndoManager.can_undo?(undo_manager)
+    refute UndoManager.can_redo?(undo_manager)
+
+    Text.insert(text, 0, "Hello")
+    assert UndoManager.can_undo?(undo_manager)
+    refute UndoManager.can_redo?(undo_manager)
+
+    UndoManager.undo(undo_manager)
+    refute UndoManager.can_undo?(undo_manager)
+    assert UndoManager.can_redo?(undo_manager)
+  end
+
+  test "undo and redo report what happened", %{doc: doc, text: text} do
+    {:ok, undo_manager} = UndoManager.new(doc, text)
+    assert {:ok, :nothing} == UndoManager.undo(undo_manager)
+
+    Text.insert(text, 0, "a")
+    UndoManager.stop_capturing(undo_manager)
+    Text.insert(text, 1, "b")
+    assert %{undo: 2, redo: 0} == UndoManager.stack_sizes(undo_manager)
+
+    assert {:ok, :undone} == UndoManager.undo(undo_manager)
+    assert %{undo: 1, redo: 1} == UndoManager.stack_sizes(undo_manager)
+    assert {:ok, :redone} == UndoManager.redo(undo_manager)
+    assert {:ok, :nothing} == UndoManager.redo(undo_manager)
+  end
+
   test "can redo text changes after undo", %{doc: doc, text: text} do
     {:ok, undo_manager} = UndoManager.new(doc, text)
     inserted_text = "Hello, world!"
@@ -525,6 +554,25 @@ defmodule Yex.UndoManagerTest do
     assert undo_manager.reference != nil
   end
 
+  test "tracked_origins option limits undo to the given origins", %{doc: doc, text: text} do
+    options = %UndoManager.Options{tracked_origins: [42]}
+    {:ok, undo_manager} = UndoManager.new_with_options(doc, text, options)
+
+    Doc.transaction(doc, 41, fn ->
+      Text.insert(text, 0, "abc")
+    end)
+
+    UndoManager.undo(undo_manager)
+    assert Text.to_string(text) == "abc"
+
+    Doc.transaction(doc, 42, fn ->
+      Text.insert(text, 3, "def")
+    end)
+
+    UndoManager.undo(undo_manager)
+    assert Text.to_string(text) == "abc"
+  end
+
   test "capture timeout works as expected", %{doc: doc, text: text} do
     options = %UndoManager.Options{capture_timeout: 100}
     {:ok, undo_manager} = UndoManager.new_with_options(doc, text, options)
@@ -1088,4 +1136,67 @@ defmodule Yex.UndoManagerTest do
   test "new_with_options handles NIF errors" do
     # Mock test removed - relies on NIF implementation
   end
+
+  describe "observers" do
+    test "observe_item_added and observe_item_updated", %{doc: doc, text: text} do
+      {:ok, undo_manager} = UndoManager.new(doc, text)
+      added_ref = UndoManager.observe_item_added(undo_manager, metadata: :added)
+      updated_ref = UndoManager.observe_item_updated(undo_manager, metadata: :updated)
+
+      Text.insert(text, 0, "a")
+
+      assert_receive {:item_added, ^added_ref, %UndoManager.Event{kind: :undo}, nil, :added}
+
+      Text.insert(text, 1, "b")
+
+      assert_receive {:item_updated, ^updated_ref,
+                      %UndoManager.Event{kind: :undo, changed_parent_types: [%Text{}]}, nil,
+                      :updated}
+    end
+
+    test "observe_item_popped", %{doc: doc, text: text} do
+      {:ok, undo_manager} = UndoManager.new(doc, text)
+      ref = UndoManager.observe_item_popped(undo_manager)
+
+      Text.insert(text, 0, "a")
+      UndoManager.undo(undo_manager)
+      assert_receive {:item_popped, ^ref, %UndoManager.Event{kind: :undo}, _origin, nil}
+
+      UndoManager.redo(undo_manager)
+      assert_receive {:item_popped, ^ref, %UndoManager.Event{kind: :redo}, _origin, nil}
+    end
+
+    test "unsubscribe releases the observer", %{doc: doc, text: text} do
+      {:ok, undo_manager} = UndoManager.new(doc, text)
+      ref = UndoManager.observe_item_added(undo_manager)
+
+      Text.insert(text, 0, "a")
+      assert_receive {:item_added, ^ref, _, _, _}
+
+      :ok = Yex.Subscription.unsubscribe(ref)
+      UndoManager.stop_capturing(undo_manager)
+      Text.insert(text, 1, "b")
+      refute_receive {:item_added, ^ref, _, _, _}
+      assert Process.get(ref) == nil
+    end
+
+    test "registering an observer twice keeps both subscriptions independent", %{
+      doc: doc,
+      text: text
+    } do
+      {:ok, undo_manager} = UndoManager.new(doc, text)
+      ref1 = UndoManager.observe_item_added(undo_manager)
+      ref2 = UndoManager.observe_item_added(undo_manager)
+
+      Text.insert(text, 0, "a")
+      assert_receive {:item_added, ^ref1, _, _, _}
+      assert_receive {:item_added, ^ref2, _, _, _}
+
+      :ok = Yex.Subscription.unsubscribe(ref1)
+      UndoManager.stop_capturing(undo_manager)
+      Text.insert(text, 1, "b")
+      refute_receive {:item_added, ^ref1, _, _, _}
+      assert_receive {:item_added, ^ref2, _, _, _}
+    end
+  end
 end
@@ -34,6 +34,76 @@ defmodule YexTest do
     end
   end
 
+  describe "apply_update_returning_summary" do
+    test "lists the changed root types, including through nested types" do
+      remote = Yex.Doc.new()
+      map = Yex.Doc.get_map(remote, "map")
+      Yex.Map.set(map, "nested", Yex.ArrayPrelim.from(["a"]))
+      Yex.Text.insert(Yex.Doc.get_text(remote, "text"), 0, "x")
+      {:ok, sv} = Yex.encode_state_vector(remote)
+
+      doc = Yex.Doc.new()
+      {:ok, update} = Yex.encode_state_as_update(remote)
+      assert {:ok, ["map", "text"]} = Yex.apply_update_returning_summary(doc, update)
+
+      remote |> Yex.Doc.get_array("other") |> Yex.Array.push("y")
+      remote |> Yex.Doc.get_map("map") |> Yex.Map.fetch!("nested") |> Yex.Array.push("b")
+      {:ok, update} = Yex.encode_state_as_update(remote, sv)
+      assert {:ok, ["map", "other"]} = Yex.apply_update_returning_summary(doc, update)
+    end
+
+    test "refuses to run inside a transaction" do
+      doc = Yex.Doc.new()
+      {:ok, update} = Yex.encode_state_as_update(Yex.Doc.new())
+
+      Yex.Doc.transaction(doc, fn ->
+        assert {:error, :transaction_busy} = Yex.apply_update_returning_summary(doc, update)
+      end)
+    end
+  end
+
+  describe "validate_update" do
+    test "rejects malformed updates" do
+      doc = Yex.Doc.new()
+      assert {:error, {:encoding_exception, _}} = Yex.validate_update(doc, <<100, 1, 2>>)
+    end
+
+    test "reports missing dependencies without touching the document" do
+      remote = Yex.Doc.new()
+      text = Yex.Doc.get_text(remote, "text")
+      Yex.Text.insert(text, 0, "Hello")
+      {:ok, sv} = Yex.encode_state_vector(remote)
+      Yex.Text.insert(text, 5, " World")
+      {:ok, diff} = Yex.encode_state_as_update(remote, sv)
+
+      doc = Yex.Doc.new()
+      assert {:error, :missing_dependencies} = Yex.validate_update(doc, diff)
+
+      {:ok, full} = Yex.encode_state_as_update(remote)
+      assert :ok = Yex.validate_update(doc, full)
+      assert "" == Yex.Doc.get_text(doc, "text") |> Yex.Text.to_string()
+    end
+  end
+
+  describe "negotiate_update_format" do
+    test "the negotiated format round-trips between peers" do
+      {:ok, format} = Yex.negotiate_update_format([:v2, :v1])
+      assert format == :v2
+
+      doc = Yex.Doc.new()
+      Yex.Doc.get_text(doc, "text") |> Yex.Text.insert(0, "hello")
+      {:ok, update} = Yex.encode_state_as_update_v2(doc)
+
+      remote = Yex.Doc.new()
+      assert :ok = Yex.apply_update_v2(remote, update)
+      assert "hello" == Yex.Doc.get_text(remote, "text") |> Yex.Text.to_string()
+    end
+
+    test "returns :error for an empty list" do
+      assert :error = Yex.negotiate_update_format([])
+    end
+  end
+
   describe "merge_updates" do
     test "merge_updates_v1" do
       doc1 = Yex.Doc.new()
@@ -72,6 +142,57 @@ defmodule YexTest do
 
       assert Yex.Text.to_string(text3) == "Hello"
     end
+
+    test "merges an empty list into an empty update" do
+      {:ok, merged} = Yex.merge_updates_v1([])
+      assert :ok = Yex.apply_update_v1(Yex.Doc.new(), merged)
+
+      {:ok, merged} = Yex.merge_updates_v2([])
+      assert :ok = Yex.apply_update_v2(Yex.Doc.new(), merged)
+    end
+
+    test "reports updates that cannot be decoded" do
+      assert {:error, {:encoding_exception, _}} = Yex.merge_updates_v1([<<255, 255, 255>>])
+    end
+  end
+
+  describe "diff_update" do
+    test "returns what a peer is missing" do
+      doc = Yex.Doc.new()
+      text = Yex.Doc.get_text(doc, "text")
+      Yex.Text.insert(text, 0, "Hello")
+      {:ok, base} = Yex.encode_state_as_update(doc)
+
+      remote = Yex.Doc.new()
+      Yex.apply_update(remote, base)
+      {:ok, remote_sv} = Yex.encode_state_vector(remote)
+
+      Yex.Text.insert(text, 5, " World")
+      {:ok, update} = Yex.encode_state_as_update(doc)
+      {:ok, diff} = Yex.diff_update(update, remote_sv)
+
+      assert :ok = Yex.apply_update(remote, diff)
+      assert "Hello World" == remote |> Yex.Doc.get_text("text") |> Yex.Text.to_string()
+    end
+  end
+
+  describe "state_vector_from_update" do
+    test "matches the state vector of a document built from merged updates" do
+      doc = Yex.Doc.new()
+      text = Yex.Doc.get_text(doc, "text")
+      Yex.Text.insert(text, 0, "a")
+      {:ok, sv} = Yex.encode_state_vector(doc)
+      {:ok, first} = Yex.encode_state_as_update(doc)
+      Yex.Text.insert(text, 1, "b")
+      {:ok, second} = Yex.encode_state_as_update(doc, sv)
+
+      {:ok, merged} = Yex.merge_updates([first, second])
+      assert Yex.state_vector_from_update(merged) == Yex.encode_state_vector(doc)
+    end
+
+    test "reports updates that cannot be decoded" do
+      assert {:error, {:encoding_exception, _}} = Yex.state_vector_from_update(<<255, 255, 255>>)
+    end
   end
 
   describe "encode_state_as_update" do
@@ -123,4 +244,23 @@ defmodule YexTest do
       {:ok, _binary} = Yex.encode_state_vector_v2(doc)
     end
   end
+
+  describe "narrow_integers" do
+    test "restores integers after a round-trip through a shared type" do
+      doc = Yex.Doc.new()
+      array = Yex.Doc.get_array(doc, "array")
+      Yex.Array.push(array, %{"id" => 7, "tags" => ["a"], "scores" => [2.5, 1]})
+      Yex.Array.push(array, Yex.Binary.new(<<1>>))
+
+      assert [
+               %{"id" => 7, "tags" => ["a"], "scores" => [2.5, 1]},
+               %Yex.Binary{data: <<1>>}
+             ] === Yex.narrow_integers(Yex.Array.to_json(array))
+    end
+
+    test "keeps floats outside the safe integer range" do
+      assert 1.0e300 === Yex.narrow_integers(1.0e300)
+      assert -3 === Yex.narrow_integers(-3.0)
+    end
+  end
 end